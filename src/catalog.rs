@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use database::Database;
+use information_schema;
+use allocators::allocator::Allocator;
+use transaction::TransactionManager;
+
+// the databases of a server. they share the transaction ids, so a snapshot taken in one database
+// is good for reading the others
+#[derive(Debug)]
+pub struct Catalog {
+    pub databases: RwLock<HashMap<String, Arc<Database>>>,
+    // each database puts its files in a directory of its own under here
+    pub data_dir: PathBuf,
+    pub transactions: Arc<Mutex<TransactionManager>>,
+    pub database_id_alloc: Mutex<Box<Allocator>>,
+}
+
+impl Catalog {
+    pub fn new(data_dir: PathBuf) -> Catalog {
+        Catalog {
+            databases: RwLock::new(HashMap::new()),
+            data_dir: data_dir,
+            transactions: Arc::new(Mutex::new(TransactionManager::new())),
+            database_id_alloc: Mutex::new(Allocator::new(0)),
+        }
+    }
+
+    pub fn create_database(&self, name: &str) -> Result<Arc<Database>, CatalogError> {
+        let mut databases = self.databases.write().unwrap();
+        if databases.contains_key(name) || name == information_schema::DATABASE_NAME {
+            return Err(CatalogError::DuplicateDatabaseError(name.to_string()));
+        }
+
+        let data_dir: PathBuf = self.data_dir.join(name);
+        try!(fs::create_dir_all(&data_dir));
+
+        let id: usize = {
+            let mut database_id_alloc = self.database_id_alloc.lock().unwrap();
+            let id: usize = database_id_alloc.base;
+            database_id_alloc.increment();
+            id
+        };
+        let db: Arc<Database> = Arc::new(Database::new(id, name, data_dir, self.transactions.clone()));
+        Database::start_vacuum(&db);
+        databases.insert(name.to_string(), db.clone());
+        Ok(db)
+    }
+
+    // the sessions using the database keep it until they leave it
+    pub fn drop_database(&self, name: &str) -> Result<(), CatalogError> {
+        match self.databases.write().unwrap().remove(name) {
+            None => Err(CatalogError::DatabaseNotFoundError(name.to_string())),
+            Some(_) => Ok(()),
+        }
+    }
+
+    pub fn database(&self, name: &str) -> Result<Arc<Database>, CatalogError> {
+        match self.databases.read().unwrap().get(name) {
+            None => Err(CatalogError::DatabaseNotFoundError(name.to_string())),
+            Some(db) => Ok(db.clone()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CatalogError {
+    DatabaseNotFoundError(String),
+    DuplicateDatabaseError(String),
+    IoError(String),
+}
+
+impl From<io::Error> for CatalogError {
+    fn from(err: io::Error) -> CatalogError {
+        CatalogError::IoError(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    // the directory of the test, removed at the end even when the test fails
+    struct DataDir(PathBuf);
+
+    impl Drop for DataDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_create_and_drop_database() {
+        let data_dir: DataDir = DataDir(env::temp_dir().join(format!("puresql_catalog_{}", process::id())));
+        let catalog: Catalog = Catalog::new(data_dir.0.clone());
+        let shop: Arc<Database> = catalog.create_database("shop").unwrap();
+        let office: Arc<Database> = catalog.create_database("office").unwrap();
+        assert!(shop.id != office.id);
+        assert_eq!(
+            catalog.create_database("shop").unwrap_err(),
+            CatalogError::DuplicateDatabaseError("shop".to_string())
+        );
+
+        // the transaction ids are taken from the same sequence
+        let tx = shop.begin();
+        assert!(office.begin().txid() > tx.txid());
+
+        catalog.drop_database("shop").unwrap();
+        assert_eq!(
+            catalog.database("shop").unwrap_err(),
+            CatalogError::DatabaseNotFoundError("shop".to_string())
+        );
+        assert_eq!(catalog.database("office").unwrap().name, "office");
+    }
+}
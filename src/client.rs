@@ -1,337 +1,3543 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
 use context::Context;
+use catalog::CatalogError;
+use information_schema;
 use database::{Database, DatabaseError};
-use meta::table_info::{TableInfo, TableInfoError};
+use meta::table_info::{column_list, TableInfo, TableInfoError};
 use meta::column_info::ColumnInfo;
-use columns::range::Range;
-use tables::memory_table::MemoryTable;
-use tables::field::Field;
+use meta::index_info::IndexInfo;
+use meta::foreign_key_info::ForeignKeyInfo;
+use meta::check_info::CheckInfo;
+use meta::view_info::ViewInfo;
+use columns::column::Column;
+use columns::range::{KeyRange, Range};
+use tables::table::{Table, TableError};
+use tables::field::{Field, FieldError};
+use tables::tuple::Tuple;
 use allocators::allocator::Allocator;
+use transaction::{Snapshot, Transaction, TransactionError};
+use lock_manager::LockError;
 
 use parser::statement::*;
 use parser::parser::{ParseError, Parser};
-use executors::memory_table_scan::MemoryTableScanExec;
+use parser::token::Literal;
+use executors::scan_iterator::ScanIterator;
+use executors::table_scan::TableScanExec;
+use executors::index_scan::IndexScanExec;
+use executors::tuple_scan::TupleScanExec;
+use planner::access_path::{plan_access_path, plan_index_join, AccessPath};
+use planner::subquery::{bind_outer, extract_scalar_subqueries, output_columns, scalar_column_name};
 use executors::projection::ProjectionExec;
 use executors::selection::SelectionExec;
 use executors::selector::*;
-use executors::join::NestedLoopJoinExec;
+use executors::join::{JoinExecError, NestedLoopJoinExec};
+use executors::set_operation::SetOperationExec;
+use executors::recursive_union::RecursiveUnionExec;
 use executors::aggregation::AggregationExec;
 use executors::aggregator::*;
 
+// the steps a recursive common table expression may take
+const MAX_RECURSION_DEPTH: usize = 1000;
+
 #[derive(Debug)]
 pub struct Client {
     pub ctx: Context,
+    // none while autocommitting
+    pub transaction: Option<Transaction>,
 }
 
 impl Client {
     pub fn new(ctx: Context) -> Client {
-        Client { ctx: ctx }
+        Client {
+            ctx: ctx,
+            transaction: None,
+        }
     }
 
     pub fn handle_query(&mut self, query: &str) -> Result<(), ClientError> {
         let mut parser: Parser = Parser::new(query);
         let stmt = try!(parser.parse());
-        match stmt.clone() {
-            Statement::DDL(stmt) => exec_ddl(&mut self.ctx, stmt),
-            Statement::DML(stmt) => exec_dml(&mut self.ctx, stmt),
+        let result: Result<(), ClientError> = match stmt {
+            Statement::TCL(stmt) => exec_tcl(&mut self.ctx, &mut self.transaction, stmt),
+            Statement::DML(DML::Set(stmt)) => exec_set(&mut self.ctx, &mut self.transaction, stmt),
+            stmt @ Statement::DML(DML::Use(_))
+            | stmt @ Statement::DDL(DDL::Create(CreateStmt::Database(_)))
+            | stmt @ Statement::DDL(DDL::Drop(DropStmt::Database(_))) => {
+                exec_catalog(&mut self.ctx, &self.transaction, stmt)
+            }
+            stmt => match self.transaction {
+                Some(ref mut tx) => exec_stmt(&mut self.ctx, tx, stmt),
+                None => exec_autocommit(&mut self.ctx, stmt),
+            },
+        };
+
+        // the victim of a deadlock gives its whole transaction up, so that the others can go on
+        if result == Err(ClientError::DeadlockError) {
+            if let Some(tx) = self.transaction.take() {
+                try!(rollback_transaction(&mut self.ctx, tx));
+            }
+        }
+        result
+    }
+}
+
+// a session which goes away in the middle of a transaction leaves nothing behind
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Some(tx) = self.transaction.take() {
+            let _ = rollback_transaction(&mut self.ctx, tx);
+        }
+    }
+}
+
+pub fn exec_stmt(ctx: &mut Context, tx: &mut Transaction, stmt: Statement) -> Result<(), ClientError> {
+    match stmt {
+        Statement::DDL(stmt) => exec_ddl(ctx, tx, stmt),
+        Statement::DML(stmt) => exec_dml(ctx, tx, stmt),
+        Statement::TCL(_) => Err(ClientError::BuildExecutorError),
+    }
+}
+
+// runs the statement in a transaction of its own
+pub fn exec_autocommit(ctx: &mut Context, stmt: Statement) -> Result<(), ClientError> {
+    let mut tx: Transaction = try!(begin_transaction(ctx));
+    let result: Result<(), ClientError> = exec_stmt(ctx, &mut tx, stmt);
+    match result {
+        Ok(_) => try!(commit_transaction(ctx, tx)),
+        Err(_) => try!(rollback_transaction(ctx, tx)),
+    }
+    result
+}
+
+fn begin_transaction(ctx: &mut Context) -> Result<Transaction, ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            let mut tx: Transaction = db.begin();
+            tx.lock_timeout = ctx.lock_timeout;
+            Ok(tx)
+        }
+    }
+}
+
+fn commit_transaction(ctx: &mut Context, tx: Transaction) -> Result<(), ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            try!(db.commit(tx));
+            Ok(())
+        }
+    }
+}
+
+fn rollback_transaction(ctx: &mut Context, tx: Transaction) -> Result<(), ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            try!(db.rollback(tx));
+            Ok(())
         }
     }
 }
 
-pub fn exec_ddl(ctx: &mut Context, stmt: DDL) -> Result<(), ClientError> {
+pub fn exec_tcl(ctx: &mut Context, transaction: &mut Option<Transaction>, stmt: TCL) -> Result<(), ClientError> {
     match stmt {
-        DDL::Create(stmt) => exec_create(ctx, stmt),
+        TCL::Begin => {
+            if transaction.is_some() {
+                return Err(ClientError::from(TransactionError::ActiveTransactionError));
+            }
+            *transaction = Some(try!(begin_transaction(ctx)));
+        }
+        TCL::Commit => {
+            try!(active_transaction(transaction));
+            if let Some(tx) = transaction.take() {
+                try!(commit_transaction(ctx, tx));
+            }
+        }
+        TCL::Rollback(None) => {
+            try!(active_transaction(transaction));
+            if let Some(tx) = transaction.take() {
+                try!(rollback_transaction(ctx, tx));
+            }
+        }
+        TCL::Rollback(Some(name)) => {
+            let tx: &mut Transaction = try!(active_transaction(transaction));
+            let mark: usize = try!(tx.rollback_to(&name));
+            match ctx.db {
+                None => return Err(ClientError::DatabaseNotFoundError),
+                Some(ref db) => try!(db.undo(tx, mark)),
+            }
+        }
+        TCL::Savepoint(name) => try!(active_transaction(transaction)).savepoint(name),
+        TCL::Release(name) => try!(try!(active_transaction(transaction)).release(&name)),
     }
+    Ok(())
 }
 
-pub fn exec_create(ctx: &mut Context, stmt: CreateStmt) -> Result<(), ClientError> {
+// the catalog is not versioned, so databases are not switched or altered inside a transaction
+pub fn exec_catalog(ctx: &mut Context, transaction: &Option<Transaction>, stmt: Statement) -> Result<(), ClientError> {
+    if transaction.is_some() {
+        return Err(ClientError::from(TransactionError::ActiveTransactionError));
+    }
+
     match stmt {
-        CreateStmt::Table(stmt) => create_table_stmt(ctx, stmt),
+        Statement::DML(DML::Use(stmt)) => ctx.db = Some(try!(ctx.catalog.database(&stmt.database_name))),
+        Statement::DDL(DDL::Create(CreateStmt::Database(name))) => {
+            try!(ctx.catalog.create_database(&name));
+        }
+        Statement::DDL(DDL::Drop(DropStmt::Database(name))) => {
+            try!(ctx.catalog.drop_database(&name));
+            // the session leaves the database it drops
+            if ctx.db.as_ref().is_some_and(|db| db.name == name) {
+                ctx.db = None;
+            }
+        }
+        _ => return Err(ClientError::BuildExecutorError),
     }
+    Ok(())
 }
 
-pub fn create_table_stmt(ctx: &mut Context, stmt: CreateTableStmt) -> Result<(), ClientError> {
-    println!("{:?}", stmt.columns.clone());
-    let columns: Vec<ColumnInfo> = stmt.columns
+// the setting is kept by the session, and applies to the transaction in progress too.
+// lock_timeout is in milliseconds, 0 waits for the locks as long as it takes
+pub fn exec_set(ctx: &mut Context, transaction: &mut Option<Transaction>, stmt: SetStmt) -> Result<(), ClientError> {
+    match &stmt.name.to_lowercase()[..] {
+        "lock_timeout" => {
+            ctx.lock_timeout = match stmt.value {
+                Literal::Int(0) => None,
+                Literal::Int(millis) if millis > 0 => Some(Duration::from_millis(millis as u64)),
+                value => return Err(ClientError::TypeMissmatchError(stmt.name, Field::from(value))),
+            };
+            if let Some(ref mut tx) = *transaction {
+                tx.lock_timeout = ctx.lock_timeout;
+            }
+            Ok(())
+        }
+        _ => Err(ClientError::UnknownVariableError(stmt.name)),
+    }
+}
+
+// a table is looked for in the database in use, unless the database is named
+fn resolve_database(ctx: &Context, database_name: &Option<String>) -> Result<Arc<Database>, ClientError> {
+    match *database_name {
+        Some(ref database_name) => Ok(try!(ctx.catalog.database(database_name))),
+        None => match ctx.db {
+            None => Err(ClientError::DatabaseNotFoundError),
+            Some(ref db) => Ok(db.clone()),
+        },
+    }
+}
+
+fn active_transaction(transaction: &mut Option<Transaction>) -> Result<&mut Transaction, ClientError> {
+    match *transaction {
+        None => Err(ClientError::from(TransactionError::NoActiveTransactionError)),
+        Some(ref mut tx) => Ok(tx),
+    }
+}
+
+pub fn exec_ddl(ctx: &mut Context, tx: &mut Transaction, stmt: DDL) -> Result<(), ClientError> {
+    match stmt {
+        DDL::Create(stmt) => exec_create(ctx, tx, stmt),
+        DDL::Drop(stmt) => exec_drop(ctx, tx, stmt),
+    }
+}
+
+pub fn exec_create(ctx: &mut Context, tx: &mut Transaction, stmt: CreateStmt) -> Result<(), ClientError> {
+    match stmt {
+        CreateStmt::Table(stmt) => create_table_stmt(ctx, tx, stmt),
+        CreateStmt::Index(stmt) => create_index_stmt(ctx, tx, stmt),
+        CreateStmt::View(view_info) => create_view_stmt(ctx, tx, view_info),
+        CreateStmt::MaterializedView(view_info) => create_materialized_view_stmt(ctx, tx, view_info),
+        CreateStmt::Database(_) => Err(ClientError::BuildExecutorError),
+    }
+}
+
+pub fn exec_drop(ctx: &mut Context, tx: &mut Transaction, stmt: DropStmt) -> Result<(), ClientError> {
+    match stmt {
+        DropStmt::Index(stmt) => drop_index_stmt(ctx, tx, stmt),
+        DropStmt::View(view_name) => match ctx.db {
+            None => Err(ClientError::DatabaseNotFoundError),
+            Some(ref db) => {
+                try!(db.drop_view(&view_name, tx));
+                Ok(())
+            }
+        },
+        DropStmt::MaterializedView(view_name) => match ctx.db {
+            None => Err(ClientError::DatabaseNotFoundError),
+            Some(ref db) => {
+                try!(db.drop_materialized_view(&view_name, tx));
+                Ok(())
+            }
+        },
+        DropStmt::Database(_) => Err(ClientError::BuildExecutorError),
+    }
+}
+
+pub fn create_index_stmt(ctx: &mut Context, tx: &mut Transaction, stmt: CreateIndexStmt) -> Result<(), ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            let column_names: Vec<&str> = stmt.column_names.iter().map(|c| &c[..]).collect();
+            try!(db.create_index(&stmt.table_name, &stmt.index_name, column_names, stmt.is_unique, stmt.index_type, tx));
+            Ok(())
+        }
+    }
+}
+
+pub fn drop_index_stmt(ctx: &mut Context, tx: &mut Transaction, stmt: DropIndexStmt) -> Result<(), ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            let table_name: Option<&str> = stmt.table_name.as_ref().map(|t| &t[..]);
+            try!(db.drop_index(table_name, &stmt.index_name, tx));
+            Ok(())
+        }
+    }
+}
+
+// the query is planned once to see that it can be read
+pub fn create_view_stmt(ctx: &mut Context, tx: &mut Transaction, view_info: ViewInfo) -> Result<(), ClientError> {
+    try!(query_columns(ctx, &tx.snapshot, &view_info.query));
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            try!(db.add_view(view_info, tx));
+            Ok(())
+        }
+    }
+}
+
+// the table takes the columns of the query, and keeps its result until the view is refreshed
+pub fn create_materialized_view_stmt(
+    ctx: &mut Context,
+    tx: &mut Transaction,
+    view_info: ViewInfo,
+) -> Result<(), ClientError> {
+    let columns: Vec<ColumnInfo> = try!(query_columns(ctx, &tx.snapshot, &view_info.query));
+    let rows: Vec<Vec<Field>> = try!(select_tuples(ctx, &tx.snapshot, *view_info.query.clone()))
         .into_iter()
-        .enumerate()
-        .map(|(i, col)| ColumnInfo {
+        .map(|tuple| tuple.fields)
+        .collect();
+
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            let table_info: TableInfo = TableInfo {
+                id: db.next_table_id(),
+                name: view_info.name.clone(),
+                columns: columns,
+                indices: Vec::new(),
+                foreign_keys: Vec::new(),
+                checks: Vec::new(),
+                engine: Engine::Memory,
+                next_record_id: Allocator::new(1),
+            };
+            try!(db.add_materialized_view(view_info, table_info, rows, tx));
+            Ok(())
+        }
+    }
+}
+
+pub fn exec_refresh(ctx: &mut Context, tx: &mut Transaction, view_name: String) -> Result<usize, ClientError> {
+    match ctx.db {
+        None => Err(ClientError::DatabaseNotFoundError),
+        Some(ref db) => {
+            let view_info: ViewInfo = match db.materialized_view(&view_name) {
+                None => return Err(ClientError::from(DatabaseError::TableNotFoundError)),
+                Some(view_info) => view_info,
+            };
+            let rows: Vec<Vec<Field>> = try!(select_tuples(ctx, &tx.snapshot, *view_info.query))
+                .into_iter()
+                .map(|tuple| tuple.fields)
+                .collect();
+            let refreshed: usize = rows.len();
+            try!(db.refresh_materialized_view(&view_name, rows, tx));
+            Ok(refreshed)
+        }
+    }
+}
+
+// the rows of a materialized view only change when it is refreshed
+fn validate_writable(db: &Database, table_name: &str) -> Result<(), ClientError> {
+    match db.materialized_view(table_name) {
+        None => Ok(()),
+        Some(_) => Err(ClientError::ReadOnlyTableError(table_name.to_string())),
+    }
+}
+
+pub fn create_table_stmt(ctx: &mut Context, tx: &mut Transaction, stmt: CreateTableStmt) -> Result<(), ClientError> {
+    println!("{:?}", stmt.columns.clone());
+    let mut columns: Vec<ColumnInfo> = Vec::new();
+    for (i, col) in stmt.columns.clone().into_iter().enumerate() {
+        let default: Option<Field> = match col.default {
+            None => None,
+            Some(lit) => {
+                let field: Field = lit.into();
+                match col.datatype.coerce(&field) {
+                    None => return Err(ClientError::TypeMissmatchError(col.name, field)),
+                    coerced => coerced,
+                }
+            }
+        };
+
+        columns.push(ColumnInfo {
             name: col.name,
             dtype: col.datatype,
             offset: i,
-        })
-        .collect();
+            default: default,
+        });
+    }
 
-    let table_info: TableInfo = TableInfo {
-        id: ctx.table_id_alloc.base,
+    let mut table_info: TableInfo = TableInfo {
+        id: 0,
         name: stmt.table_name,
         columns: columns,
         indices: Vec::new(),
+        foreign_keys: Vec::new(),
+        checks: Vec::new(),
+        engine: stmt.engine,
         next_record_id: Allocator::new(1),
     };
 
+    let mut constraints: Vec<TableConstraint> = Vec::new();
+    let mut nth: usize = 0;
+    for col in stmt.columns.iter() {
+        for constraint in col.constraints.iter() {
+            constraints.push(match *constraint {
+                ColumnConstraint::PrimaryKey => TableConstraint::PrimaryKey(vec![col.name.clone()]),
+                ColumnConstraint::Unique => TableConstraint::Unique(vec![col.name.clone()]),
+                ColumnConstraint::References(ref references) => {
+                    TableConstraint::ForeignKey(vec![col.name.clone()], references.clone())
+                }
+                ColumnConstraint::Check(ref condition) => {
+                    nth += 1;
+                    let name: String = CheckInfo::generate_name(&table_info.name, Some(&col.name), nth);
+                    TableConstraint::Check(Some(name), condition.clone())
+                }
+            });
+        }
+    }
+    constraints.extend(stmt.constraints);
+
+    // keys are registered before foreign keys, so that a table can reference itself
+    let mut references: Vec<(Vec<String>, References)> = Vec::new();
+    for constraint in constraints {
+        match constraint {
+            TableConstraint::ForeignKey(column_names, reference) => {
+                references.push((column_names, reference));
+            }
+            TableConstraint::PrimaryKey(column_names) => {
+                if table_info.primary_key().is_some() {
+                    return Err(ClientError::MultiplePrimaryKeyError);
+                }
+                let column_names: Vec<&str> = column_names.iter().map(|c| &c[..]).collect();
+                try!(IndexInfo::new(&mut table_info, None, column_names, true, true, IndexType::BTree));
+            }
+            TableConstraint::Unique(column_names) => {
+                let column_names: Vec<&str> = column_names.iter().map(|c| &c[..]).collect();
+                try!(IndexInfo::new(&mut table_info, None, column_names, false, true, IndexType::BTree));
+            }
+            TableConstraint::Check(name, condition) => {
+                let nth: usize = table_info.checks.len() + 1;
+                let name: String = name.unwrap_or(CheckInfo::generate_name(&table_info.name, None, nth));
+                let check: CheckInfo = try!(CheckInfo::new(name, condition));
+                let column_names: Vec<String> = check.column_names();
+                try!(table_info.find_column_infos_by_names(&column_names.iter().map(|c| &c[..]).collect()));
+                table_info.checks.push(check);
+            }
+        }
+    }
+
     match ctx.db {
         None => return Err(ClientError::DatabaseNotFoundError),
-        Some(ref mut db) => db.add_table(table_info.clone()),
+        Some(ref db) => {
+            for (column_names, reference) in references {
+                let fkey: ForeignKeyInfo = try!(build_foreign_key(db, &table_info, column_names, reference));
+                table_info.foreign_keys.push(fkey);
+            }
+            table_info.id = db.next_table_id();
+            try!(db.add_table(table_info.clone(), tx));
+        }
     };
 
-    ctx.table_id_alloc.increment();
     Ok(())
 }
 
-pub fn exec_dml(ctx: &mut Context, stmt: DML) -> Result<(), ClientError> {
-    match stmt {
-        DML::Insert(stmt) => exec_insert(ctx, stmt),
-        DML::Select(stmt) => exec_select(ctx, stmt),
-        _ => Err(ClientError::BuildExecutorError),
+pub fn build_foreign_key(
+    db: &Database,
+    table_info: &TableInfo,
+    column_names: Vec<String>,
+    reference: References,
+) -> Result<ForeignKeyInfo, ClientError> {
+    let ref_table_info: TableInfo = if reference.table_name == table_info.name {
+        table_info.clone()
+    } else {
+        try!(db.table_info_from_str(&reference.table_name))
+    };
+
+    // the referenced columns have to be covered by a primary key or an unique index
+    let ref_index: IndexInfo = match ref_table_info.indices.iter().find(|index_info| {
+        let names: Vec<&str> = index_info.columns.iter().map(|c| &c.name[..]).collect();
+        index_info.is_unique
+            && match reference.column_names.len() {
+                0 => index_info.is_pk_index,
+                _ => names == reference.column_names.iter().map(|c| &c[..]).collect::<Vec<&str>>(),
+            }
+    }) {
+        None => return Err(ClientError::InvalidReferenceError(reference.table_name)),
+        Some(index_info) => index_info.clone(),
+    };
+
+    let names: Vec<&str> = column_names.iter().map(|c| &c[..]).collect();
+    let columns: Vec<ColumnInfo> = try!(table_info.find_column_infos_by_names(&names));
+    if columns.len() != ref_index.columns.len()
+        || columns.iter().zip(ref_index.columns.iter()).any(|(a, b)| a.dtype != b.dtype)
+    {
+        return Err(ClientError::InvalidReferenceError(reference.table_name));
     }
+
+    if reference.on_delete == ReferentialAction::SetDefault
+        || reference.on_update == ReferentialAction::SetDefault
+    {
+        for column in columns.iter() {
+            if column.default.is_none() {
+                return Err(ClientError::MissingValueError(column.name.clone()));
+            }
+        }
+    }
+
+    Ok(ForeignKeyInfo {
+        name: ForeignKeyInfo::generate_name(&table_info.name, &names),
+        table_name: table_info.name.clone(),
+        columns: columns,
+        ref_table_name: reference.table_name,
+        ref_columns: ref_index.columns,
+        on_delete: reference.on_delete,
+        on_update: reference.on_update,
+    })
 }
 
-pub fn exec_insert(ctx: &mut Context, stmt: InsertStmt) -> Result<(), ClientError> {
-    let mut fields: Vec<Field> = Vec::new();
-    let literals = stmt.values;
-    for lit in literals {
-        fields.push(lit.into());
+pub fn exec_dml(ctx: &mut Context, tx: &mut Transaction, stmt: DML) -> Result<(), ClientError> {
+    match stmt {
+        DML::Insert(stmt) => {
+            let inserted: usize = try!(exec_insert(ctx, tx, stmt));
+            println!("{} rows inserted\n", inserted);
+            Ok(())
+        }
+        DML::Update(stmt) => {
+            let updated: usize = try!(exec_update(ctx, tx, stmt));
+            println!("{} rows updated\n", updated);
+            Ok(())
+        }
+        DML::Delete(stmt) => {
+            let deleted: usize = try!(exec_delete(ctx, tx, stmt));
+            println!("{} rows deleted\n", deleted);
+            Ok(())
+        }
+        DML::Refresh(view_name) => {
+            let refreshed: usize = try!(exec_refresh(ctx, tx, view_name));
+            println!("{} rows refreshed\n", refreshed);
+            Ok(())
+        }
+        DML::Select(stmt) => exec_select(ctx, tx, *stmt),
+        DML::SetOperation(stmt) => exec_set_operation(ctx, tx, stmt),
+        DML::With(stmt) => exec_with(ctx, tx, stmt),
+        DML::Describe(table_name) => exec_show(ctx, ShowStmt::Columns(table_name)),
+        DML::Show(stmt) => exec_show(ctx, stmt),
+        DML::Use(_) | DML::Set(_) => Err(ClientError::BuildExecutorError),
     }
+}
 
+// every row is validated before the first one is stored, so a failing statement inserts nothing
+pub fn exec_insert(ctx: &mut Context, tx: &mut Transaction, stmt: InsertStmt) -> Result<usize, ClientError> {
     match ctx.db {
         None => Err(ClientError::BuildExecutorError),
-        Some(ref mut db) => match db.load_table(stmt.table_name) {
-            Ok(ref mut mem_tbl) => {
-                mem_tbl.insert(fields);
-                Ok(())
+        Some(ref db) => {
+            try!(validate_writable(db, &stmt.table_name));
+            let rows: Vec<Vec<Field>> = match stmt.source {
+                InsertSource::Values(rows) => rows.into_iter()
+                    .map(|row| row.into_iter().map(|lit| lit.into()).collect())
+                    .collect(),
+                InsertSource::Select(select) => try!(select_tuples(ctx, &tx.snapshot, *select))
+                    .into_iter()
+                    .map(|tuple| tuple.fields)
+                    .collect(),
+            };
+
+            let meta: TableInfo = try!(db.table_info_from_str(&stmt.table_name));
+
+            let mut validated_rows: Vec<Vec<Field>> = Vec::new();
+            for row in rows {
+                validated_rows.push(try!(validate_fields(&meta, &stmt.column_names, row)));
             }
-            _ => Err(ClientError::BuildExecutorError),
-        },
+
+            let record_ids: Vec<usize> = try!(db.insert_rows(&stmt.table_name, validated_rows, tx));
+            Ok(record_ids.len())
+        }
     }
 }
 
-pub fn exec_select(ctx: &mut Context, stmt: SelectStmt) -> Result<(), ClientError> {
-    println!("{:?}", stmt);
+pub fn exec_update(ctx: &mut Context, tx: &mut Transaction, stmt: UpdateStmt) -> Result<usize, ClientError> {
+    let selectors: Option<Selectors> = match stmt.condition {
+        None => None,
+        Some(condition) => Some(try!(build_selectors(condition))),
+    };
+
     match ctx.db {
         None => Err(ClientError::BuildExecutorError),
-        Some(ref mut db) => {
-            let mut conditions: Option<Selectors> = None;
-            match stmt.condition.clone() {
-                None => {}
-                Some(condition) => {
-                    conditions = Some(try!(build_selectors(condition)));
-                }
-            }
-
-            match stmt.source.clone() {
-                DataSource::Join(_s1, _s2, _c) => {
-                    let mut scan_exec: NestedLoopJoinExec =
-                        try!(exec_join(db.clone(), stmt.source));
-                    let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
-
-                    let mut aggregators: Vec<Aggregator> = Vec::new();
-                    for target in stmt.targets.clone() {
-                        match target {
-                            Projectable::Aggregate(expr) => {
-                                aggregators.push(try!(build_aggregator(expr)))
-                            }
-                            _ => (),
-                        }
-                    }
+        Some(ref db) => {
+            try!(validate_writable(db, &stmt.table_name));
+            let mut rows: Vec<(usize, Vec<Field>)> = Vec::new();
+            {
+                let table: Arc<RwLock<Table>> = try!(db.table(&stmt.table_name));
+                let mem_tbl = table.read().unwrap();
 
-                    let limit :isize = stmt.limit.unwrap_or(-1);
-                    let mut iter: isize = 0;
-                    if aggregators.len() > 0 {
-                        let group_keys = match stmt.group_by {
-                            None => vec![],
-                            Some(v) => v,
-                        };
-
-                        let mut aggr_exec =
-                            AggregationExec::new(&mut selection_exec, group_keys, aggregators);
-                        loop {
-                            if limit == iter {
-                                break;
-                            } else {
-                                iter += 1;
-                            }
-
-                            match aggr_exec.next() {
-                                None => break,
-                                Some(tuples) => for tuple in tuples {
-                                    tuple.print();
-                                },
-                            };
-                        }
-                    } else {
-                        let mut proj_exec = ProjectionExec::new(&mut selection_exec, stmt.targets);
-                        loop {
-                            if limit == iter {
-                                break;
-                            } else {
-                                iter += 1;
-                            }
-
-                            match proj_exec.next() {
-                                None => break,
-                                Some(tuple) => tuple.print(),
-                            };
-                        }
+                let mut assignments: Vec<(usize, Field)> = Vec::new();
+                for assignment in stmt.assignments {
+                    let column: ColumnInfo = try!(mem_tbl.meta.column_info_from_str(&assignment.column_name));
+                    let field: Field = assignment.value.into();
+                    match column.dtype.coerce(&field) {
+                        None => return Err(ClientError::TypeMissmatchError(column.name, field)),
+                        Some(coerced) => assignments.push((column.offset, coerced)),
                     }
-                    println!("Scaned\n");
                 }
-                DataSource::Leaf(_s) => {
-                    let mut scan_exec: MemoryTableScanExec =
-                        try!(exec_scan(db.clone(), stmt.source));
-                    let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
 
-                    let mut aggregators: Vec<Aggregator> = Vec::new();
-                    for target in stmt.targets.clone() {
-                        match target {
-                            Projectable::Aggregate(expr) => {
-                                aggregators.push(try!(build_aggregator(expr)))
-                            }
-                            _ => (),
-                        }
-                    }
+                let stored: Vec<(usize, Tuple)> = try!(mem_tbl.rows(&tx.snapshot).map_err(DatabaseError::from));
+                for (record_id, tuple) in stored {
+                    let passed: bool = match selectors {
+                        None => true,
+                        Some(ref s) => eval_selectors(s.clone(), &tuple, &mem_tbl.columns),
+                    };
 
-                    let limit: isize = stmt.limit.unwrap_or(-1);
-                    let mut iter: isize = 0;
-                    if aggregators.len() > 0 {
-                        let group_keys = match stmt.group_by {
-                            None => vec![],
-                            Some(v) => v,
-                        };
-
-                        let mut aggr_exec =
-                            AggregationExec::new(&mut selection_exec, group_keys, aggregators);
-                        loop {
-                            if limit == iter {
-                                break;
-                            } else {
-                                iter += 1;
-                            }
-
-                            match aggr_exec.next() {
-                                None => break,
-                                Some(tuples) => for tuple in tuples {
-                                    tuple.print();
-                                },
-                            };
-                        }
-                    } else {
-                        let mut proj_exec = ProjectionExec::new(&mut selection_exec, stmt.targets);
-                        loop {
-                            if limit == iter {
-                                break;
-                            } else {
-                                iter += 1;
-                            }
-
-                            match proj_exec.next() {
-                                None => break,
-                                Some(tuple) => tuple.print(),
-                            };
+                    if passed {
+                        let mut fields: Vec<Field> = tuple.fields.clone();
+                        for &(offset, ref field) in assignments.iter() {
+                            fields[offset] = field.clone();
                         }
+                        rows.push((record_id, fields));
                     }
-                    println!("Scaned\n");
                 }
             }
-            Ok(())
+
+            let updated: usize = rows.len();
+            try!(db.update_rows(&stmt.table_name, rows, tx));
+            Ok(updated)
         }
     }
 }
 
-pub fn exec_join<'i>(
-    db: Database,
-    source: DataSource,
-) -> Result<NestedLoopJoinExec<'i>, ClientError> {
-    match source {
-        DataSource::Join(s1, s2, c) => match *s1.clone() {
-            DataSource::Join(_s1, _s2, _c) => {
-                let iter1 = try!(exec_join(db.clone(), *s1));
-                match *s2.clone() {
-                    DataSource::Join(_s1, _s2, _c) => {
-                        let iter2 = try!(exec_join(db.clone(), *s2));
-                        Ok(NestedLoopJoinExec::new(iter1, iter2, c))
-                    }
-                    DataSource::Leaf(_s) => {
-                        let iter2 = try!(exec_scan(db.clone(), *s2));
-                        Ok(NestedLoopJoinExec::new(iter1, iter2, c))
-                    }
-                }
-            }
-            DataSource::Leaf(_s) => {
-                let iter1 = try!(exec_scan(db.clone(), *s1));
-                match *s2.clone() {
-                    DataSource::Join(_s1, _s2, _c) => {
-                        let iter2 = try!(exec_join(db.clone(), *s2));
-                        Ok(NestedLoopJoinExec::new(iter1, iter2, c))
-                    }
-                    DataSource::Leaf(_s) => {
-                        let iter2 = try!(exec_scan(db.clone(), *s2));
-                        Ok(NestedLoopJoinExec::new(iter1, iter2, c))
-                    }
-                }
-            }
-        },
-        _ => Err(ClientError::BuildExecutorError),
+pub fn exec_delete(ctx: &mut Context, tx: &mut Transaction, stmt: DeleteStmt) -> Result<usize, ClientError> {
+    let selectors: Option<Selectors> = match stmt.condition {
+        None => None,
+        Some(condition) => Some(try!(build_selectors(condition))),
+    };
+
+    match ctx.db {
+        None => Err(ClientError::BuildExecutorError),
+        Some(ref db) => {
+            try!(validate_writable(db, &stmt.table_name));
+            let record_ids: Vec<usize> = {
+                let table: Arc<RwLock<Table>> = try!(db.table(&stmt.table_name));
+                let mem_tbl = table.read().unwrap();
+                try!(mem_tbl.rows(&tx.snapshot).map_err(DatabaseError::from))
+                    .into_iter()
+                    .filter(|(_, tuple)| match selectors {
+                        None => true,
+                        Some(ref s) => eval_selectors(s.clone(), tuple, &mem_tbl.columns),
+                    })
+                    .map(|(record_id, _)| record_id)
+                    .collect()
+            };
+
+            let deleted: usize = record_ids.len();
+            try!(db.delete_rows(&stmt.table_name, record_ids, tx));
+            Ok(deleted)
+        }
     }
 }
 
-pub fn exec_scan(mut db: Database, source: DataSource) -> Result<MemoryTableScanExec, ClientError> {
-    match source {
-        DataSource::Leaf(s) => match s {
-            Source::Table(t) => {
-                let tbl_name: String = t.name.clone();
-                let mut mem_tbl: &MemoryTable = try!(db.load_table(tbl_name));
-                let mem_tbl_info: TableInfo = mem_tbl.meta.clone();
-                Ok(MemoryTableScanExec::new(
-                    mem_tbl.clone(),
-                    mem_tbl_info,
-                    vec![Range::new(0, 10)],
-                ))
+// arranges the values in table column order, filling omitted columns with their defaults
+pub fn validate_fields(
+    meta: &TableInfo,
+    column_names: &[String],
+    row: Vec<Field>,
+) -> Result<Vec<Field>, ClientError> {
+    let mut values: Vec<Option<Field>> = vec![None; meta.columns.len()];
+    if column_names.is_empty() {
+        if meta.columns.len() != row.len() {
+            return Err(ClientError::MissmatchColumnNumberError);
+        }
+
+        for (i, field) in row.into_iter().enumerate() {
+            values[i] = Some(field);
+        }
+    } else {
+        if column_names.len() != row.len() {
+            return Err(ClientError::MissmatchColumnNumberError);
+        }
+
+        for (name, field) in column_names.iter().zip(row) {
+            let column: ColumnInfo = try!(meta.column_info_from_str(name));
+            if values[column.offset].is_some() {
+                return Err(ClientError::DuplicateColumnError(name.clone()));
             }
-        },
-        _ => Err(ClientError::BuildExecutorError),
+            values[column.offset] = Some(field);
+        }
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub enum ClientError {
-    ParseError(ParseError),
-    DatabaseError(DatabaseError),
-    TableInfoError(TableInfoError),
-    SelectorError(SelectorError),
-    AggregatorError(AggregatorError),
-    BuildExecutorError,
-    DatabaseNotFoundError,
-}
+    let mut fields: Vec<Field> = Vec::new();
+    for (column, value) in meta.columns.iter().zip(values) {
+        let field: Field = match value {
+            Some(field) => field,
+            None => match column.default {
+                Some(ref default) => default.clone(),
+                None => return Err(ClientError::MissingValueError(column.name.clone())),
+            },
+        };
 
-impl From<ParseError> for ClientError {
-    fn from(err: ParseError) -> ClientError {
-        ClientError::ParseError(err)
+        match column.dtype.coerce(&field) {
+            None => return Err(ClientError::TypeMissmatchError(column.name.clone(), field)),
+            Some(coerced) => fields.push(coerced),
+        }
     }
+    Ok(fields)
 }
 
-impl From<DatabaseError> for ClientError {
-    fn from(err: DatabaseError) -> ClientError {
-        ClientError::DatabaseError(err)
+pub fn exec_select(ctx: &mut Context, tx: &Transaction, stmt: SelectStmt) -> Result<(), ClientError> {
+    println!("{:?}", stmt);
+    for tuple in try!(select_tuples(ctx, &tx.snapshot, stmt)) {
+        tuple.print();
     }
+    println!("Scaned\n");
+    Ok(())
 }
 
-impl From<TableInfoError> for ClientError {
-    fn from(err: TableInfoError) -> ClientError {
-        ClientError::TableInfoError(err)
+// reads what the snapshot sees, so the tables are scanned as they were when the transaction began
+pub fn select_tuples(ctx: &Context, snapshot: &Snapshot, stmt: SelectStmt) -> Result<Vec<Tuple>, ClientError> {
+    if stmt.has_subqueries() {
+        return select_with_subqueries(ctx, snapshot, stmt);
     }
-}
 
-impl From<SelectorError> for ClientError {
-    fn from(err: SelectorError) -> ClientError {
-        ClientError::SelectorError(err)
+    let mut conditions: Option<Selectors> = None;
+    match stmt.condition.clone() {
+        None => {}
+        Some(condition) => {
+            conditions = Some(try!(build_selectors(condition)));
+        }
     }
-}
 
-impl From<AggregatorError> for ClientError {
-    fn from(err: AggregatorError) -> ClientError {
+    match stmt.source.clone() {
+        DataSource::Join(_s1, _s2, _c) => {
+            let mut scan_exec: NestedLoopJoinExec = try!(exec_join(ctx, snapshot, stmt.source.clone()));
+            let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
+            fetch_tuples(&mut selection_exec, stmt)
+        }
+        DataSource::Leaf(Source::SubQuery(_)) => {
+            let mut scan_exec: Box<dyn ScanIterator> = try!(exec_scan(ctx, snapshot, stmt.source.clone()));
+            let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
+            fetch_tuples(&mut selection_exec, stmt)
+        }
+        DataSource::Leaf(Source::Table(t)) => {
+            let access_path: AccessPath = if try!(is_generated(ctx, &t.database_name, &t.name)) {
+                AccessPath::FullScan
+            } else {
+                let meta: TableInfo = try!(try!(resolve_database(ctx, &t.database_name)).table_info_from_str(&t.name));
+                plan_access_path(&meta, &stmt.condition)
+            };
+
+            match access_path {
+                AccessPath::FullScan => {
+                    let mut scan_exec: Box<dyn ScanIterator> = try!(exec_scan(ctx, snapshot, stmt.source.clone()));
+                    let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
+                    fetch_tuples(&mut selection_exec, stmt)
+                }
+                AccessPath::IndexScan(index_name, range) => {
+                    let db: Arc<Database> = try!(resolve_database(ctx, &t.database_name));
+                    let mut scan_exec: IndexScanExec = try!(exec_index_scan(&db, snapshot, &t.name, &index_name, &range));
+                    let mut selection_exec = SelectionExec::new(&mut scan_exec, conditions);
+                    fetch_tuples(&mut selection_exec, stmt)
+                }
+            }
+        }
+    }
+}
+
+pub fn exec_set_operation(ctx: &mut Context, tx: &Transaction, stmt: SetOperationStmt) -> Result<(), ClientError> {
+    for tuple in try!(set_operation_tuples(ctx, &tx.snapshot, stmt)) {
+        tuple.print();
+    }
+    println!("Scaned\n");
+    Ok(())
+}
+
+pub fn set_operation_tuples(ctx: &Context, snapshot: &Snapshot, stmt: SetOperationStmt) -> Result<Vec<Tuple>, ClientError> {
+    Ok(try!(scan_set_operation(ctx, snapshot, stmt)).tuples)
+}
+
+// both queries are run, and their tuples converted into the types shared by their columns
+fn scan_set_operation(ctx: &Context, snapshot: &Snapshot, stmt: SetOperationStmt) -> Result<TupleScanExec, ClientError> {
+    let mut left: TupleScanExec = try!(scan_query(ctx, snapshot, stmt.left));
+    let mut right: TupleScanExec = try!(scan_query(ctx, snapshot, stmt.right));
+    let columns: Vec<Column> = try!(common_columns(&left.get_columns(), &right.get_columns()));
+    left.tuples = coerce_tuples(&columns, left.tuples);
+    right.tuples = coerce_tuples(&columns, right.tuples);
+
+    let mut meta: TableInfo = left.get_meta();
+    for (column_info, column) in meta.columns.iter_mut().zip(columns.iter()) {
+        column_info.dtype = column.dtype.clone();
+    }
+    let tuples: Vec<Tuple> = SetOperationExec::new(stmt.op, &mut left, &mut right).collect();
+    Ok(TupleScanExec::with_columns(meta, columns, tuples))
+}
+
+fn scan_query(ctx: &Context, snapshot: &Snapshot, query: Query) -> Result<TupleScanExec, ClientError> {
+    match query {
+        Query::Select(stmt) => exec_subquery(ctx, snapshot, "", *stmt),
+        Query::SetOperation(stmt) => scan_set_operation(ctx, snapshot, *stmt),
+    }
+}
+
+pub fn exec_with(ctx: &mut Context, tx: &Transaction, stmt: WithStmt) -> Result<(), ClientError> {
+    for tuple in try!(with_tuples(ctx, &tx.snapshot, stmt)) {
+        tuple.print();
+    }
+    println!("Scaned\n");
+    Ok(())
+}
+
+// the results of the common table expressions are kept by the context, where the scans find them
+pub fn with_tuples(ctx: &Context, snapshot: &Snapshot, stmt: WithStmt) -> Result<Vec<Tuple>, ClientError> {
+    let mut ctx: Context = ctx.clone();
+    for cte in stmt.ctes {
+        let scan_exec: TupleScanExec = try!(scan_cte(&ctx, snapshot, &cte, stmt.is_recursive));
+        ctx.ctes.insert(cte.name, scan_exec);
+    }
+
+    match stmt.query {
+        Query::Select(select) => select_tuples(&ctx, snapshot, *select),
+        Query::SetOperation(set_operation) => set_operation_tuples(&ctx, snapshot, *set_operation),
+    }
+}
+
+fn scan_cte(ctx: &Context, snapshot: &Snapshot, cte: &CommonTableExpr, is_recursive: bool) -> Result<TupleScanExec, ClientError> {
+    match cte.query {
+        Query::SetOperation(ref stmt) if is_recursive && is_recursive_union(stmt, &cte.name) => {
+            scan_recursive_cte(ctx, snapshot, cte, *stmt.clone())
+        }
+        ref query => {
+            let scan_exec: TupleScanExec = try!(scan_query(ctx, snapshot, query.clone()));
+            let column_infos: Vec<ColumnInfo> = try!(cte_columns(cte, scan_exec.meta.columns));
+            Ok(TupleScanExec::new(TableInfo::generated(&cte.name, column_infos), scan_exec.tuples))
+        }
+    }
+}
+
+// the columns are renamed by the column names of the expression, when it has them
+fn cte_columns(cte: &CommonTableExpr, mut column_infos: Vec<ColumnInfo>) -> Result<Vec<ColumnInfo>, ClientError> {
+    if let Some(ref column_names) = cte.column_names {
+        if column_names.len() != column_infos.len() {
+            return Err(ClientError::MissmatchColumnNumberError);
+        }
+        for (column_info, name) in column_infos.iter_mut().zip(column_names.iter()) {
+            column_info.name = name.clone();
+        }
+    }
+    Ok(column_infos)
+}
+
+// a recursive expression is the union of an anchor and a query which reads the expression itself
+fn is_recursive_union(stmt: &SetOperationStmt, name: &str) -> bool {
+    let is_union: bool = stmt.op == SetOperator::Union || stmt.op == SetOperator::UnionAll;
+    is_union && stmt.right.tables().iter().any(|t| t.database_name.is_none() && t.name == name)
+}
+
+// the recursive query reads the tuples yielded last under the name of the expression, and its
+// tuples are converted into the types of the anchor
+fn scan_recursive_cte(
+    ctx: &Context,
+    snapshot: &Snapshot,
+    cte: &CommonTableExpr,
+    stmt: SetOperationStmt,
+) -> Result<TupleScanExec, ClientError> {
+    let anchor: TupleScanExec = try!(scan_query(ctx, snapshot, stmt.left));
+    let recursive: Query = stmt.right;
+    let meta: TableInfo = TableInfo::generated(&cte.name, try!(cte_columns(cte, anchor.meta.columns.clone())));
+    let columns: Vec<Column> = anchor.get_columns();
+
+    // a cycle under UNION ALL yields rows forever, so the recursion is cut off
+    let mut depth: usize = 0;
+    let step = |working: &[Tuple]| -> Result<Vec<Tuple>, ClientError> {
+        depth += 1;
+        if depth > MAX_RECURSION_DEPTH {
+            return Err(ClientError::RecursionLimitError(cte.name.clone()));
+        }
+        let mut ctx: Context = ctx.clone();
+        ctx.ctes.insert(cte.name.clone(), TupleScanExec::new(meta.clone(), working.to_vec()));
+        let scan_exec: TupleScanExec = try!(scan_query(&ctx, snapshot, recursive.clone()));
+        try!(common_columns(&columns, &scan_exec.get_columns()));
+        Ok(coerce_tuples(&columns, scan_exec.tuples))
+    };
+
+    let is_distinct: bool = stmt.op == SetOperator::Union;
+    let mut recursive_exec = RecursiveUnionExec::new(meta.clone(), anchor.tuples, is_distinct, step);
+    let tuples: Vec<Tuple> = recursive_exec.by_ref().collect();
+    match recursive_exec.error {
+        Some(err) => Err(err),
+        None => Ok(TupleScanExec::new(meta, tuples)),
+    }
+}
+
+// the columns are named after the left ones, and typed as both sides can be converted into
+fn common_columns(left: &[Column], right: &[Column]) -> Result<Vec<Column>, ClientError> {
+    if left.len() != right.len() {
+        return Err(ClientError::MissmatchColumnNumberError);
+    }
+
+    let mut columns: Vec<Column> = Vec::new();
+    for (l, r) in left.iter().zip(right.iter()) {
+        let mut column: Column = l.clone();
+        column.dtype = match l.dtype.common(&r.dtype) {
+            None => return Err(ClientError::IncompatibleColumnError(l.name.clone())),
+            Some(dtype) => dtype,
+        };
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+// fields which can not be converted, as those without a value, are kept as they are
+fn coerce_tuples(columns: &[Column], tuples: Vec<Tuple>) -> Vec<Tuple> {
+    tuples
+        .into_iter()
+        .map(|tuple| {
+            let fields: Vec<Field> = tuple
+                .fields
+                .iter()
+                .zip(columns.iter())
+                .map(|(field, column)| column.dtype.coerce(field).unwrap_or(field.clone()))
+                .collect();
+            Tuple::new(fields)
+        })
+        .collect()
+}
+
+// the subqueries of the conditions are run for each tuple of the source, and the scalar subqueries
+// of the projection are read into columns appended to it. the rest of the query runs as usual over
+// the tuples kept
+fn select_with_subqueries(ctx: &Context, snapshot: &Snapshot, stmt: SelectStmt) -> Result<Vec<Tuple>, ClientError> {
+    let (mut stmt, scalars): (SelectStmt, Vec<SelectStmt>) = extract_scalar_subqueries(&stmt);
+    let mut scan_exec: Box<dyn ScanIterator> = try!(exec_source(ctx, snapshot, stmt.source.clone()));
+    let meta: TableInfo = scan_exec.get_meta();
+    let inputs: Vec<Column> = scan_exec.get_columns();
+
+    let mut runner: SubQueryRunner = SubQueryRunner::new(ctx, snapshot);
+    let mut tuples: Vec<Tuple> = Vec::new();
+    loop {
+        let tuple: Tuple = match scan_exec.next() {
+            None => break,
+            Some(tuple) => tuple,
+        };
+
+        if let Some(ref condition) = stmt.condition {
+            if !try!(runner.eval(condition, &tuple, &inputs)) {
+                continue;
+            }
+        }
+
+        let mut fields: Vec<Field> = tuple.fields.clone();
+        for query in scalars.iter() {
+            fields.push(try!(runner.scalar(query, &tuple, &inputs)));
+        }
+        tuples.push(Tuple::new(fields));
+    }
+
+    let columns: Vec<Column> = try!(scalar_columns(ctx, snapshot, inputs, &scalars));
+    stmt.condition = None;
+    let mut tuple_exec: TupleScanExec = TupleScanExec::with_columns(meta, columns, tuples);
+    fetch_tuples(&mut tuple_exec, stmt)
+}
+
+// appends the columns the scalar subqueries are read from, typed as the column each one selects
+fn scalar_columns(
+    ctx: &Context,
+    snapshot: &Snapshot,
+    mut columns: Vec<Column>,
+    scalars: &[SelectStmt],
+) -> Result<Vec<Column>, ClientError> {
+    for (i, query) in scalars.iter().enumerate() {
+        let column_info: ColumnInfo = match try!(query_columns(ctx, snapshot, query)).into_iter().next() {
+            None => return Err(ClientError::BuildExecutorError),
+            Some(column_info) => column_info,
+        };
+        let offset: usize = columns.len();
+        columns.push(Column {
+            table_name: String::new(),
+            name: scalar_column_name(i + 1),
+            dtype: column_info.dtype,
+            offset: offset,
+        });
+    }
+    Ok(columns)
+}
+
+// runs the subqueries of a query for each tuple of the outer one. the results of the subqueries
+// which do not refer to the outer query are the same for every tuple, so they are kept
+struct SubQueryRunner<'c> {
+    ctx: &'c Context,
+    snapshot: &'c Snapshot,
+    // the columns of the source of each subquery, and the results, by the sql of the subquery
+    columns: HashMap<String, Vec<Column>>,
+    results: HashMap<String, Vec<Tuple>>,
+}
+
+impl<'c> SubQueryRunner<'c> {
+    fn new(ctx: &'c Context, snapshot: &'c Snapshot) -> SubQueryRunner<'c> {
+        SubQueryRunner {
+            ctx: ctx,
+            snapshot: snapshot,
+            columns: HashMap::new(),
+            results: HashMap::new(),
+        }
+    }
+
+    fn run(&mut self, query: &SelectStmt, outer_tuple: &Tuple, outer: &[Column]) -> Result<Vec<Tuple>, ClientError> {
+        let key: String = query.to_sql();
+        if let Some(tuples) = self.results.get(&key) {
+            return Ok(tuples.clone());
+        }
+
+        let (ctx, snapshot): (&Context, &Snapshot) = (self.ctx, self.snapshot);
+        let columns: &mut HashMap<String, Vec<Column>> = &mut self.columns;
+        let bound: SelectStmt = try!(bind_outer(query, outer_tuple, outer, &mut |query: &SelectStmt| {
+            source_columns(ctx, snapshot, columns, query)
+        }));
+        let tuples: Vec<Tuple> = try!(select_tuples(self.ctx, self.snapshot, bound.clone()));
+        if bound == *query {
+            self.results.insert(key, tuples.clone());
+        }
+        Ok(tuples)
+    }
+
+    // the first field of the only row, a subquery without rows yields a field without a value
+    fn scalar(&mut self, query: &SelectStmt, outer_tuple: &Tuple, outer: &[Column]) -> Result<Field, ClientError> {
+        let tuples: Vec<Tuple> = try!(self.run(query, outer_tuple, outer));
+        if tuples.len() > 1 {
+            return Err(ClientError::MultipleRowsError(query.to_sql()));
+        }
+        Ok(tuples
+            .into_iter()
+            .next()
+            .and_then(|tuple| tuple.fields.into_iter().next())
+            .unwrap_or(Field::set_init()))
+    }
+
+    fn eval(&mut self, condition: &Conditions, tuple: &Tuple, columns: &[Column]) -> Result<bool, ClientError> {
+        match *condition {
+            Conditions::And(ref c1, ref c2) => Ok(try!(self.eval(c1, tuple, columns)) && try!(self.eval(c2, tuple, columns))),
+            Conditions::Or(ref c1, ref c2) => Ok(try!(self.eval(c1, tuple, columns)) || try!(self.eval(c2, tuple, columns))),
+            Conditions::Not(ref c) => Ok(!try!(self.eval(c, tuple, columns))),
+            Conditions::In(ref target, ref query) => {
+                let field: Field = try!(find_field(tuple, columns, target.table_name.clone(), target.name.clone()));
+                let tuples: Vec<Tuple> = try!(self.run(query, tuple, columns));
+                Ok(tuples.iter().any(|t| {
+                    t.fields.first().is_some_and(|f| field.compare(f) == Some(Ordering::Equal))
+                }))
+            }
+            Conditions::Exists(ref query) => Ok(!try!(self.run(query, tuple, columns)).is_empty()),
+            Conditions::Leaf(ref c) => {
+                let selectors: Selectors = match c.right {
+                    Comparable::SubQuery(ref query) => Selectors::Leaf(Box::new(Selector {
+                        kind: c.op.clone(),
+                        left_table: c.left.table_name.clone(),
+                        left_column: c.left.name.clone(),
+                        right_hand: None,
+                        scholar: Some(try!(self.scalar(query, tuple, columns))),
+                        list: Vec::new(),
+                    })),
+                    _ => try!(build_selectors(condition.clone())),
+                };
+                Ok(eval_selectors(selectors, tuple, columns))
+            }
+        }
+    }
+}
+
+// the columns of the source of a subquery, scanned once for every sql
+fn source_columns(
+    ctx: &Context,
+    snapshot: &Snapshot,
+    columns: &mut HashMap<String, Vec<Column>>,
+    query: &SelectStmt,
+) -> Result<Vec<Column>, ClientError> {
+    let key: String = query.to_sql();
+    if !columns.contains_key(&key) {
+        let source: Vec<Column> = try!(exec_source(ctx, snapshot, query.source.clone())).get_columns();
+        columns.insert(key.clone(), source);
+    }
+    Ok(columns[&key].clone())
+}
+
+pub fn exec_show(ctx: &mut Context, stmt: ShowStmt) -> Result<(), ClientError> {
+    for tuple in try!(show_tuples(ctx, stmt)) {
+        tuple.print();
+    }
+    println!("Scaned\n");
+    Ok(())
+}
+
+// the schema of the database in use is listed as rows, in the same shape as a select result
+pub fn show_tuples(ctx: &Context, stmt: ShowStmt) -> Result<Vec<Tuple>, ClientError> {
+    let db: Arc<Database> = try!(resolve_database(ctx, &None));
+    match stmt {
+        // name | kind
+        ShowStmt::Tables => {
+            let mut tables: Vec<(String, &str)> = db.tables
+                .read()
+                .unwrap()
+                .keys()
+                .map(|table_name| (table_name.clone(), information_schema::table_type(&db, table_name)))
+                .collect();
+            tables.extend(
+                db.views
+                    .read()
+                    .unwrap()
+                    .keys()
+                    .map(|view_name| (view_name.clone(), information_schema::VIEW)),
+            );
+            tables.sort();
+            Ok(tables
+                .iter()
+                .map(|&(ref table_name, kind)| Tuple::new(vec![Field::set_str(table_name), Field::set_str(kind)]))
+                .collect())
+        }
+        // name | type | offset | default | constraints
+        ShowStmt::Columns(table_name) => {
+            let meta: TableInfo = try!(db.table_info_from_str(&table_name));
+            Ok(meta.columns
+                .iter()
+                .map(|column| {
+                    let default: String = column.default.as_ref().map_or("".to_string(), |d| d.to_sql());
+                    Tuple::new(vec![
+                        Field::set_str(&column.name),
+                        Field::set_str(&column.dtype.to_sql()),
+                        Field::set_i64(column.offset as i64),
+                        Field::set_str(&default),
+                        Field::set_str(&column_constraints(&meta, &column.name).join(", ")),
+                    ])
+                })
+                .collect())
+        }
+        // name | columns | unique | primary | type
+        ShowStmt::Indexes(table_name) => {
+            let meta: TableInfo = try!(db.table_info_from_str(&table_name));
+            Ok(meta.indices
+                .iter()
+                .map(|index_info| {
+                    Tuple::new(vec![
+                        Field::set_str(&index_info.name),
+                        Field::set_str(&column_list(&index_info.columns)),
+                        Field::set_bool(index_info.is_unique),
+                        Field::set_bool(index_info.is_pk_index),
+                        Field::set_str(index_info.index_type.to_sql()),
+                    ])
+                })
+                .collect())
+        }
+        // name | statement
+        ShowStmt::CreateTable(table_name) => {
+            let meta: TableInfo = try!(db.table_info_from_str(&table_name));
+            Ok(vec![
+                Tuple::new(vec![Field::set_str(&meta.name), Field::set_str(&meta.create_table_sql())]),
+            ])
+        }
+    }
+}
+
+// the keys, references and checks the column takes part in
+fn column_constraints(meta: &TableInfo, column_name: &str) -> Vec<String> {
+    let mut constraints: Vec<String> = Vec::new();
+    for index_info in meta.indices.iter().filter(|index_info| index_info.is_unique) {
+        if index_info.columns.iter().any(|c| c.name == column_name) {
+            constraints.push(if index_info.is_pk_index { "primary key" } else { "unique" }.to_string());
+        }
+    }
+    for fkey in meta.foreign_keys.iter() {
+        if fkey.columns.iter().any(|c| c.name == column_name) {
+            constraints.push(format!("references {} ( {} )", fkey.ref_table_name, column_list(&fkey.ref_columns)));
+        }
+    }
+    for check in meta.checks.iter() {
+        if check.column_names().iter().any(|name| name == column_name) {
+            constraints.push(format!("check {}", check.name));
+        }
+    }
+    constraints
+}
+
+fn fetch_tuples<T: ScanIterator>(inputs: &mut T, stmt: SelectStmt) -> Result<Vec<Tuple>, ClientError> {
+    let mut aggregators: Vec<Aggregator> = Vec::new();
+    for target in stmt.targets.clone() {
+        if let Projectable::Aggregate(expr) = target {
+            aggregators.push(try!(build_aggregator(expr)));
+        }
+    }
+
+    let limit: isize = stmt.limit.unwrap_or(-1);
+    let mut tuples: Vec<Tuple> = Vec::new();
+    if aggregators.len() > 0 {
+        let group_keys = match stmt.group_by {
+            None => vec![],
+            Some(v) => v,
+        };
+
+        // each step yields every group aggregated so far, so the last one is the result
+        let mut aggr_exec = AggregationExec::new(inputs, group_keys, aggregators);
+        loop {
+            match aggr_exec.next() {
+                None => break,
+                Some(grouped) => tuples = grouped,
+            };
+        }
+
+        if limit >= 0 {
+            tuples.truncate(limit as usize);
+        }
+    } else {
+        let mut proj_exec = ProjectionExec::new(inputs, stmt.targets);
+        let mut iter: isize = 0;
+        loop {
+            if limit == iter {
+                break;
+            } else {
+                iter += 1;
+            }
+
+            match proj_exec.next() {
+                None => break,
+                Some(tuple) => tuples.push(tuple),
+            };
+        }
+    }
+    Ok(tuples)
+}
+
+pub fn exec_join<'i>(
+    ctx: &Context,
+    snapshot: &Snapshot,
+    source: DataSource,
+) -> Result<NestedLoopJoinExec<'i>, ClientError> {
+    match source {
+        DataSource::Join(s1, s2, c) => match *s1.clone() {
+            DataSource::Join(_s1, _s2, _c) => {
+                let iter1 = try!(exec_join(ctx, snapshot, *s1));
+                match *s2.clone() {
+                    DataSource::Join(_s1, _s2, _c) => {
+                        let iter2 = try!(exec_join(ctx, snapshot, *s2));
+                        Ok(try!(NestedLoopJoinExec::new(iter1, iter2, c)))
+                    }
+                    DataSource::Leaf(_s) => join_table(ctx, snapshot, iter1, *s2, c),
+                }
+            }
+            DataSource::Leaf(_s) => {
+                let iter1 = try!(exec_scan(ctx, snapshot, *s1));
+                match *s2.clone() {
+                    DataSource::Join(_s1, _s2, _c) => {
+                        let iter2 = try!(exec_join(ctx, snapshot, *s2));
+                        Ok(try!(NestedLoopJoinExec::new(iter1, iter2, c)))
+                    }
+                    DataSource::Leaf(_s) => join_table(ctx, snapshot, iter1, *s2, c),
+                }
+            }
+        },
+        _ => Err(ClientError::BuildExecutorError),
+    }
+}
+
+// joins a table as the inner side, through its index when the condition equates the index key
+fn join_table<'i, T: ScanIterator + 'i>(
+    ctx: &Context,
+    snapshot: &Snapshot,
+    outer: T,
+    inner: DataSource,
+    condition: Option<Conditions>,
+) -> Result<NestedLoopJoinExec<'i>, ClientError> {
+    let table: Arc<RwLock<Table>> = match inner {
+        DataSource::Leaf(Source::Table(ref t)) if !try!(is_generated(ctx, &t.database_name, &t.name)) => {
+            try!(try!(resolve_database(ctx, &t.database_name)).table(&t.name))
+        }
+        // the generated tables and the derived tables have no index to join through
+        DataSource::Leaf(_) => {
+            let iter2 = try!(exec_scan(ctx, snapshot, inner.clone()));
+            return Ok(try!(NestedLoopJoinExec::new(outer, iter2, condition)));
+        }
+        _ => return Err(ClientError::BuildExecutorError),
+    };
+
+    let meta: TableInfo = table.read().unwrap().meta.clone();
+    match plan_index_join(&outer.get_columns(), &meta, &condition) {
+        Some((index_name, outer_offsets)) => Ok(try!(NestedLoopJoinExec::with_index(
+            outer,
+            table,
+            index_name,
+            outer_offsets,
+            condition,
+            snapshot.clone(),
+        ))),
+        None => {
+            let iter2 = try!(exec_scan(ctx, snapshot, inner));
+            Ok(try!(NestedLoopJoinExec::new(outer, iter2, condition)))
+        }
+    }
+}
+
+// the common table expressions hide the tables of the same names
+fn is_cte(ctx: &Context, database_name: &Option<String>, name: &str) -> bool {
+    database_name.is_none() && ctx.ctes.contains_key(name)
+}
+
+fn is_system_table(database_name: &Option<String>) -> bool {
+    database_name.as_ref().is_some_and(|name| name == information_schema::DATABASE_NAME)
+}
+
+// the tables of information_schema and the views are built when they are scanned, without indexes
+fn is_generated(ctx: &Context, database_name: &Option<String>, name: &str) -> Result<bool, ClientError> {
+    if is_system_table(database_name) || is_cte(ctx, database_name, name) {
+        return Ok(true);
+    }
+    Ok(try!(resolve_database(ctx, database_name)).view(name).is_some())
+}
+
+// the query of a view or a derived table is run as a subquery, whose result is scanned under the
+// name of the view or the alias of the derived table
+fn exec_subquery(ctx: &Context, snapshot: &Snapshot, name: &str, query: SelectStmt) -> Result<TupleScanExec, ClientError> {
+    let columns: Vec<ColumnInfo> = try!(query_columns(ctx, snapshot, &query));
+    let tuples: Vec<Tuple> = try!(select_tuples(ctx, snapshot, query));
+    Ok(TupleScanExec::new(TableInfo::generated(name, columns), tuples))
+}
+
+fn query_columns(ctx: &Context, snapshot: &Snapshot, stmt: &SelectStmt) -> Result<Vec<ColumnInfo>, ClientError> {
+    let (stmt, scalars): (SelectStmt, Vec<SelectStmt>) = extract_scalar_subqueries(stmt);
+    let inputs: Vec<Column> = try!(exec_source(ctx, snapshot, stmt.source.clone())).get_columns();
+    let inputs: Vec<Column> = try!(scalar_columns(ctx, snapshot, inputs, &scalars));
+    Ok(output_columns(&inputs, &stmt))
+}
+
+pub fn exec_source(ctx: &Context, snapshot: &Snapshot, source: DataSource) -> Result<Box<dyn ScanIterator>, ClientError> {
+    match source {
+        DataSource::Join(_, _, _) => Ok(Box::new(try!(exec_join(ctx, snapshot, source)))),
+        DataSource::Leaf(_) => exec_scan(ctx, snapshot, source),
+    }
+}
+
+pub fn exec_scan(ctx: &Context, snapshot: &Snapshot, source: DataSource) -> Result<Box<dyn ScanIterator>, ClientError> {
+    match source {
+        DataSource::Leaf(s) => match s {
+            Source::Table(ref t) if is_system_table(&t.database_name) => {
+                match information_schema::scan(&ctx.catalog, &t.name) {
+                    None => Err(ClientError::from(DatabaseError::TableNotFoundError)),
+                    Some(scan_exec) => Ok(Box::new(scan_exec)),
+                }
+            }
+            Source::Table(ref t) if is_cte(ctx, &t.database_name, &t.name) => Ok(Box::new(ctx.ctes[&t.name].clone())),
+            Source::Table(t) => {
+                let db: Arc<Database> = try!(resolve_database(ctx, &t.database_name));
+                if let Some(view_info) = db.view(&t.name) {
+                    return Ok(Box::new(try!(exec_subquery(ctx, snapshot, &view_info.name, *view_info.query))));
+                }
+                let table: Arc<RwLock<Table>> = try!(db.table(&t.name));
+                let mem_tbl_info: TableInfo = table.read().unwrap().meta.clone();
+                Ok(Box::new(TableScanExec::new(
+                    table,
+                    mem_tbl_info,
+                    vec![Range::new(0, usize::MAX)],
+                    snapshot.clone(),
+                )))
+            }
+            Source::SubQuery(s) => Ok(Box::new(try!(exec_subquery(ctx, snapshot, &s.alias, *s.query)))),
+        },
+        _ => Err(ClientError::BuildExecutorError),
+    }
+}
+
+pub fn exec_index_scan(
+    db: &Database,
+    snapshot: &Snapshot,
+    table_name: &str,
+    index_name: &str,
+    range: &KeyRange,
+) -> Result<IndexScanExec, ClientError> {
+    let table: Arc<RwLock<Table>> = try!(db.table(table_name));
+    match IndexScanExec::new(table, index_name, range, snapshot) {
+        Ok(scan_exec) => Ok(scan_exec),
+        Err(err) => Err(ClientError::from(DatabaseError::from(TableError::from(err)))),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClientError {
+    ParseError(ParseError),
+    DatabaseError(DatabaseError),
+    TableInfoError(TableInfoError),
+    SelectorError(SelectorError),
+    AggregatorError(AggregatorError),
+    FieldError(FieldError),
+    TransactionError(TransactionError),
+    CatalogError(CatalogError),
+    BuildExecutorError,
+    DatabaseNotFoundError,
+    DeadlockError,
+    LockTimeoutError,
+    MissmatchColumnNumberError,
+    IncompatibleColumnError(String),
+    MultipleRowsError(String),
+    MultiplePrimaryKeyError,
+    RecursionLimitError(String),
+    UnknownVariableError(String),
+    InvalidReferenceError(String),
+    ReadOnlyTableError(String),
+    DuplicateColumnError(String),
+    MissingValueError(String),
+    TypeMissmatchError(String, Field),
+}
+
+impl From<ParseError> for ClientError {
+    fn from(err: ParseError) -> ClientError {
+        ClientError::ParseError(err)
+    }
+}
+
+impl From<DatabaseError> for ClientError {
+    fn from(err: DatabaseError) -> ClientError {
+        match err {
+            DatabaseError::LockError(LockError::DeadlockError) => ClientError::DeadlockError,
+            DatabaseError::LockError(LockError::LockTimeoutError) => ClientError::LockTimeoutError,
+            err => ClientError::DatabaseError(err),
+        }
+    }
+}
+
+impl From<TableInfoError> for ClientError {
+    fn from(err: TableInfoError) -> ClientError {
+        ClientError::TableInfoError(err)
+    }
+}
+
+impl From<SelectorError> for ClientError {
+    fn from(err: SelectorError) -> ClientError {
+        ClientError::SelectorError(err)
+    }
+}
+
+impl From<CatalogError> for ClientError {
+    fn from(err: CatalogError) -> ClientError {
+        ClientError::CatalogError(err)
+    }
+}
+
+impl From<TransactionError> for ClientError {
+    fn from(err: TransactionError) -> ClientError {
+        ClientError::TransactionError(err)
+    }
+}
+
+impl From<AggregatorError> for ClientError {
+    fn from(err: AggregatorError) -> ClientError {
         ClientError::AggregatorError(err)
     }
 }
 
+impl From<FieldError> for ClientError {
+    fn from(err: FieldError) -> ClientError {
+        ClientError::FieldError(err)
+    }
+}
+
+impl From<JoinExecError> for ClientError {
+    fn from(err: JoinExecError) -> ClientError {
+        match err {
+            JoinExecError::TableInfoError(err) => ClientError::TableInfoError(err),
+            JoinExecError::SelectorError(err) => ClientError::SelectorError(err),
+            JoinExecError::TableError(err) => ClientError::DatabaseError(DatabaseError::TableError(err)),
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::env;
+    use std::fs;
+    use std::ops::{Deref, DerefMut};
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::thread;
+    use tables::index::{Entry, IndexError, IndexTree};
+    use tables::table::TableError;
+    use transaction::{TransactionError, TxId, UndoEntry};
+    use database::{DatabaseError, VACUUM_INTERVAL};
+    use catalog::Catalog;
+
+    static DATA_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+    // a client whose files are put in a directory of its own, which is removed with the client
+    struct TestClient {
+        client: Client,
+        data_dir: PathBuf,
+    }
+
+    impl Deref for TestClient {
+        type Target = Client;
+        fn deref(&self) -> &Client {
+            &self.client
+        }
+    }
+
+    impl DerefMut for TestClient {
+        fn deref_mut(&mut self) -> &mut Client {
+            &mut self.client
+        }
+    }
+
+    impl Drop for TestClient {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.data_dir);
+        }
+    }
+
+    fn gen_client() -> TestClient {
+        let data_dir: PathBuf = env::temp_dir().join(format!(
+            "puresql_client_{}_{}",
+            process::id(),
+            DATA_DIRS.fetch_add(1, AtomicOrdering::SeqCst)
+        ));
+        let catalog: Arc<Catalog> = Arc::new(Catalog::new(data_dir.clone()));
+        let db: Arc<Database> = catalog.create_database("test").unwrap();
+        let mut client: Client = Client::new(Context {
+            catalog: catalog,
+            db: Some(db),
+            lock_timeout: None,
+            ctes: HashMap::new(),
+        });
+        client
+            .handle_query("create table shohin ( shohin_id int, shohin_name char(10), price float, discounted bool )")
+            .unwrap();
+        TestClient {
+            client: client,
+            data_dir: data_dir,
+        }
+    }
+
+    // what the session sees, inside its transaction if one is running
+    fn client_snapshot(client: &Client, db: &Database) -> Snapshot {
+        match client.transaction {
+            None => db.snapshot(),
+            Some(ref tx) => tx.snapshot.clone(),
+        }
+    }
+
+    fn fetch_rows(client: &mut Client, table_name: &str) -> Vec<Vec<Field>> {
+        match client.ctx.db {
+            None => vec![],
+            Some(ref db) => db.table(table_name)
+                .unwrap()
+                .read()
+                .unwrap()
+                .rows(&client_snapshot(client, db))
+                .unwrap()
+                .into_iter()
+                .map(|(_, tuple)| tuple.fields)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_insert_coerces_values() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("insert into shohin ( shohin_id, shohin_name, price, discounted ) values ( 1, 'apple', 300, true )");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "shohin"),
+            vec![vec![
+                Field::set_i64(1),
+                Field::set_str("apple"),
+                Field::set_f64(300.0),
+                Field::set_bool(true),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_insert_rejects_missmatched_type() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("insert into shohin ( shohin_id, shohin_name, price, discounted ) values ( true, 'apple', 300, true )");
+        assert_eq!(
+            result,
+            Err(ClientError::TypeMissmatchError(
+                "shohin_id".to_string(),
+                Field::set_bool(true)
+            ))
+        );
+
+        let result = client.handle_query("insert into shohin ( shohin_id, shohin_name, price, discounted ) values ( 1, 'apple', 1.5, 1 )");
+        assert_eq!(
+            result,
+            Err(ClientError::TypeMissmatchError(
+                "discounted".to_string(),
+                Field::set_i64(1)
+            ))
+        );
+        assert!(fetch_rows(&mut client, "shohin").is_empty());
+
+        let result = client.handle_query("insert into nothing values ( 1 )");
+        assert_eq!(result, Err(ClientError::DatabaseError(DatabaseError::TableNotFoundError)));
+    }
+
+    #[test]
+    fn test_insert_maps_values_by_column_name() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("insert into shohin ( price, discounted, shohin_name, shohin_id ) values ( 300, false, 'apple', 1 )");
+        assert_eq!(result, Ok(()));
+
+        let result = client.handle_query("insert into shohin values ( 2, 'orange', 130.5, true )");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "shohin"),
+            vec![
+                vec![
+                    Field::set_i64(1),
+                    Field::set_str("apple"),
+                    Field::set_f64(300.0),
+                    Field::set_bool(false),
+                ],
+                vec![
+                    Field::set_i64(2),
+                    Field::set_str("orange"),
+                    Field::set_f64(130.5),
+                    Field::set_bool(true),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_fills_defaults() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table kubun ( kubun_id int, kubun_name char(10) default 'unknown', sortable bool default false )")
+            .unwrap();
+
+        let result = client.handle_query("insert into kubun ( kubun_id ) values ( 1 )");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "kubun"),
+            vec![vec![
+                Field::set_i64(1),
+                Field::set_str("unknown"),
+                Field::set_bool(false),
+            ]]
+        );
+
+        let result = client.handle_query("insert into kubun ( kubun_name ) values ( 'fruit' )");
+        assert_eq!(
+            result,
+            Err(ClientError::MissingValueError("kubun_id".to_string()))
+        );
+
+        let result = client.handle_query("insert into kubun ( kubun_id, kubun_id ) values ( 1, 2 )");
+        assert_eq!(
+            result,
+            Err(ClientError::DuplicateColumnError("kubun_id".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_create_table_with_not_null() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table kubun ( kubun_id int not null, kubun_name char(10) not null default 'unknown' )")
+            .unwrap();
+
+        let result = client.handle_query("insert into kubun ( kubun_name ) values ( 'fruit' )");
+        assert_eq!(
+            result,
+            Err(ClientError::MissingValueError("kubun_id".to_string()))
+        );
+        client.handle_query("insert into kubun ( kubun_id ) values ( 1 )").unwrap();
+        assert_eq!(
+            fetch_rows(&mut client, "kubun"),
+            vec![vec![Field::set_i64(1), Field::set_str("unknown")]]
+        );
+
+        // a column can not hold NULL
+        match client.handle_query("create table shohin ( shohin_id int null )") {
+            Err(ClientError::ParseError(_)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_create_table_rejects_missmatched_default() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("create table kubun ( kubun_id int default 'one' )");
+        assert_eq!(
+            result,
+            Err(ClientError::TypeMissmatchError(
+                "kubun_id".to_string(),
+                Field::set_str("one")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_insert_multiple_rows() {
+        let mut client: TestClient = gen_client();
+        let mut tx: Transaction = begin_transaction(&mut client.ctx).unwrap();
+        let result = exec_insert(
+            &mut client.ctx,
+            &mut tx,
+            InsertStmt {
+                table_name: "shohin".to_string(),
+                column_names: vec![],
+                source: InsertSource::Values(vec![
+                    vec![
+                        Literal::Int(1),
+                        Literal::String("apple".to_string()),
+                        Literal::Int(300),
+                        Literal::Bool(0),
+                    ],
+                    vec![
+                        Literal::Int(2),
+                        Literal::String("orange".to_string()),
+                        Literal::Int(130),
+                        Literal::Bool(1),
+                    ],
+                ]),
+            },
+        );
+        commit_transaction(&mut client.ctx, tx).unwrap();
+        assert_eq!(result, Ok(2));
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 2);
+
+        let result = client.handle_query("insert into shohin ( shohin_id, shohin_name, price, discounted ) values ( 3, 'cabbage', 200, false ), ( 4, 'sea weed', 250, true )");
+        assert_eq!(result, Ok(()));
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 4);
+    }
+
+    #[test]
+    fn test_insert_multiple_rows_is_atomic() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("insert into shohin values ( 1, 'apple', 300, false ), ( 2, 'orange', 130, 'yes' )");
+        assert_eq!(
+            result,
+            Err(ClientError::TypeMissmatchError(
+                "discounted".to_string(),
+                Field::set_str("yes")
+            ))
+        );
+        assert!(fetch_rows(&mut client, "shohin").is_empty());
+    }
+
+    #[test]
+    fn test_insert_select() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false ), ( 2, 'orange', 130, true ), ( 3, 'cabbage', 200, true )")
+            .unwrap();
+        client
+            .handle_query("create table sale ( name char(10), sale_price float )")
+            .unwrap();
+
+        let stmt = match Parser::new("insert into sale ( sale_price, name ) select price, shohin_name from shohin where shohin_id > 1").parse() {
+            Ok(Statement::DML(DML::Insert(stmt))) => stmt,
+            _ => panic!("unexpected statement"),
+        };
+        let mut tx: Transaction = begin_transaction(&mut client.ctx).unwrap();
+        assert_eq!(exec_insert(&mut client.ctx, &mut tx, stmt), Ok(2));
+        commit_transaction(&mut client.ctx, tx).unwrap();
+        assert_eq!(
+            fetch_rows(&mut client, "sale"),
+            vec![
+                vec![Field::set_str("orange"), Field::set_f64(130.0)],
+                vec![Field::set_str("cabbage"), Field::set_f64(200.0)],
+            ]
+        );
+    }
+
+    fn select_rows(client: &Client, query: &str) -> Vec<Vec<Field>> {
+        let stmt: SelectStmt = match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::Select(stmt))) => *stmt,
+            other => panic!("unexpected statement {:?}", other),
+        };
+        match client.ctx.db {
+            None => vec![],
+            Some(ref db) => select_tuples(&client.ctx, &client_snapshot(client, db), stmt)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.fields)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_select_scans_whole_table() {
+        let mut client: TestClient = gen_client();
+        for i in 1..16 {
+            client
+                .handle_query(&format!("insert into shohin values ( {}, 'apple', 100, false )", i))
+                .unwrap();
+        }
+        assert_eq!(select_rows(&client, "select shohin_id from shohin").len(), 15);
+    }
+
+    #[test]
+    fn test_select_after_delete() {
+        let mut client: TestClient = gen_client();
+        for i in 1..16 {
+            client
+                .handle_query(&format!("insert into shohin values ( {}, 'apple', 100, false )", i))
+                .unwrap();
+        }
+        client
+            .handle_query("delete from shohin where shohin_id <= 10")
+            .unwrap();
+        assert_eq!(
+            select_rows(&client, "select shohin_id from shohin"),
+            (11..16).map(|i| vec![Field::set_i64(i)]).collect::<Vec<Vec<Field>>>()
+        );
+    }
+
+    #[test]
+    fn test_select_through_index() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 3, 'apple', 300, false ), ( 1, 'orange', 130, true ), ( 2, 'cabbage', 200, true ), ( 4, 'melon', 500, false )")
+            .unwrap();
+        client
+            .handle_query("create index shohin_price on shohin ( price )")
+            .unwrap();
+
+        // the index yields the rows by price, and the rest of the conditions still filters them
+        assert_eq!(
+            select_rows(&client, "select shohin_id from shohin where price >= 130 and price < 500 and shohin_id <> 2"),
+            vec![vec![Field::set_i64(1)], vec![Field::set_i64(3)]]
+        );
+        assert_eq!(
+            select_rows(&client, "select shohin_name from shohin where price = 200"),
+            vec![vec![Field::set_str("cabbage")]]
+        );
+    }
+
+    #[test]
+    fn test_table_engines() {
+        let mut client: TestClient = gen_client();
+        for engine in &["memory", "heap", "columnar"] {
+            let table_name: String = format!("{}_shohin", engine);
+            client
+                .handle_query(&format!(
+                    "create table {} ( shohin_id int primary key, shohin_name char(10), price int ) engine = {}",
+                    table_name, engine
+                ))
+                .unwrap();
+            client
+                .handle_query(&format!(
+                    "insert into {} values ( 1, 'apple', 300 ), ( 2, 'orange', 130 ), ( 3, 'melon', 500 )",
+                    table_name
+                ))
+                .unwrap();
+            client
+                .handle_query(&format!("update {} set price = 150 where shohin_id = 2", table_name))
+                .unwrap();
+            client
+                .handle_query(&format!("delete from {} where shohin_id = 3", table_name))
+                .unwrap();
+
+            assert_eq!(
+                select_rows(&client, &format!("select shohin_name, price from {}", table_name)),
+                vec![
+                    vec![Field::set_str("apple"), Field::set_i64(300)],
+                    vec![Field::set_str("orange"), Field::set_i64(150)],
+                ]
+            );
+            assert_eq!(
+                select_rows(&client, &format!("select shohin_name from {} where shohin_id = 2", table_name)),
+                vec![vec![Field::set_str("orange")]]
+            );
+        }
+
+        match client.handle_query("create table kubun ( kubun_id int ) engine = paper") {
+            Err(ClientError::ParseError(ParseError::UndefinedEngine(_))) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    fn transaction_error(err: TransactionError) -> Result<(), ClientError> {
+        Err(ClientError::TransactionError(err))
+    }
+
+    #[test]
+    fn test_transaction_rollback() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+
+        // a failed load is undone as a whole
+        client.handle_query("begin transaction").unwrap();
+        client
+            .handle_query("create table kubun ( kubun_id int primary key, kubun_name char(10) )")
+            .unwrap();
+        client
+            .handle_query("insert into shohin values ( 2, 'orange', 130, true )")
+            .unwrap();
+        client
+            .handle_query("update shohin set price = 500 where shohin_id = 1")
+            .unwrap();
+        assert!(client.handle_query("insert into shohin values ( 3, 'melon' )").is_err());
+        client.handle_query("rollback").unwrap();
+
+        assert_eq!(
+            fetch_rows(&mut client, "shohin"),
+            vec![vec![Field::set_i64(1), Field::set_str("apple"), Field::set_f64(300.0), Field::set_bool(false)]]
+        );
+        assert!(client.handle_query("insert into kubun values ( 1, 'fruit' )").is_err());
+        assert_eq!(client.handle_query("rollback"), transaction_error(TransactionError::NoActiveTransactionError));
+    }
+
+    #[test]
+    fn test_transaction_savepoints() {
+        let mut client: TestClient = gen_client();
+        client.handle_query("begin").unwrap();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+        client.handle_query("savepoint first").unwrap();
+        client
+            .handle_query("insert into shohin values ( 2, 'orange', 130, true )")
+            .unwrap();
+        client.handle_query("savepoint second").unwrap();
+        client
+            .handle_query("insert into shohin values ( 3, 'melon', 500, true )")
+            .unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 3);
+
+        client.handle_query("rollback to savepoint first").unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 1);
+        assert_eq!(
+            client.handle_query("release savepoint second"),
+            transaction_error(TransactionError::SavepointNotFoundError("second".to_string()))
+        );
+        assert_eq!(client.handle_query("begin"), transaction_error(TransactionError::ActiveTransactionError));
+
+        client
+            .handle_query("insert into shohin values ( 4, 'cabbage', 200, false )")
+            .unwrap();
+        client.handle_query("release first").unwrap();
+        client.handle_query("commit").unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 2);
+        assert_eq!(client.handle_query("commit"), transaction_error(TransactionError::NoActiveTransactionError));
+    }
+
+    // runs the query in a session of its own on the same database,
+    // which gives up soon on the locks held by the client
+    fn exec_other_session(client: &mut Client, query: &str) -> Result<(), ClientError> {
+        let mut session: Client = Client::new(client.ctx.clone());
+        session.ctx.lock_timeout = Some(Duration::from_millis(50));
+        session.handle_query(query)
+    }
+
+    // reclaims the versions nobody sees, as the commits do only now and then
+    fn vacuum(client: &Client) {
+        client.ctx.db.as_ref().unwrap().vacuum().unwrap();
+    }
+
+    fn count_versions(client: &Client, table_name: &str) -> usize {
+        match client.ctx.db {
+            None => 0,
+            Some(ref db) => db.table(table_name).unwrap().read().unwrap().versions.len(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_isolation() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+
+        client.handle_query("begin").unwrap();
+        exec_other_session(&mut client, "insert into shohin values ( 2, 'orange', 130, true )").unwrap();
+        exec_other_session(&mut client, "update shohin set price = 500 where shohin_id = 1").unwrap();
+
+        // the transaction keeps reading the rows as they were when it began
+        assert_eq!(
+            select_rows(&client, "select shohin_id, price from shohin"),
+            vec![vec![Field::set_i64(1), Field::set_f64(300.0)]]
+        );
+        assert_eq!(count_versions(&client, "shohin"), 3);
+
+        // the row was updated after the snapshot was taken
+        client
+            .handle_query("update shohin set price = 100 where shohin_id = 1")
+            .unwrap_err();
+        client.handle_query("commit").unwrap();
+
+        // nobody sees the old version anymore
+        vacuum(&client);
+        assert_eq!(count_versions(&client, "shohin"), 2);
+        assert_eq!(
+            select_rows(&client, "select shohin_id, price from shohin"),
+            vec![
+                vec![Field::set_i64(2), Field::set_f64(130.0)],
+                vec![Field::set_i64(1), Field::set_f64(500.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commits_vacuum_now_and_then() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 0, false )")
+            .unwrap();
+        vacuum(&client);
+
+        // the old versions are left until enough commits are made
+        for price in 1..VACUUM_INTERVAL {
+            client
+                .handle_query(&format!("update shohin set price = {} where shohin_id = 1", price))
+                .unwrap();
+        }
+        assert_eq!(count_versions(&client, "shohin"), VACUUM_INTERVAL);
+
+        // and then reclaimed by the vacuum thread, not by the commit
+        client
+            .handle_query("update shohin set price = 0 where shohin_id = 1")
+            .unwrap();
+        for _ in 0..100 {
+            if count_versions(&client, "shohin") == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(count_versions(&client, "shohin"), 1);
+    }
+
+    #[test]
+    fn test_failed_rollback_aborts() {
+        let mut client: TestClient = gen_client();
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        let mut tx: Transaction = db.begin();
+        let txid: TxId = tx.txid();
+        let row: Vec<Field> = vec![
+            Field::set_i64(1),
+            Field::set_str("apple"),
+            Field::set_f64(300.0),
+            Field::set_bool(false),
+        ];
+        db.insert_rows("shohin", vec![row], &mut tx).unwrap();
+        // the row is left behind, as its undo fails
+        tx.undo_log = vec![UndoEntry::Insert("missing".to_string(), 0)];
+        assert_eq!(db.rollback(tx), Err(DatabaseError::TableNotFoundError));
+
+        // the transaction is not taken as committed, and its locks are released
+        assert!(!db.snapshot().sees(txid));
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 0);
+        exec_other_session(&mut client, "create index shohin_price on shohin ( price )").unwrap();
+
+        // the vacuum reclaims the row, and the transaction is forgotten after that
+        assert_eq!(count_versions(&client, "shohin"), 1);
+        vacuum(&client);
+        assert_eq!(count_versions(&client, "shohin"), 0);
+        assert!(db.transactions.lock().unwrap().aborted.is_empty());
+    }
+
+    #[test]
+    fn test_write_conflict() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create unique index shohin_id on shohin ( shohin_id )")
+            .unwrap();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+
+        client.handle_query("begin").unwrap();
+        client
+            .handle_query("delete from shohin where shohin_id = 1")
+            .unwrap();
+
+        // the row stays visible to the others, but it can not be written until the delete ends
+        assert_eq!(
+            exec_other_session(&mut client, "update shohin set price = 100 where shohin_id = 1"),
+            Err(ClientError::LockTimeoutError)
+        );
+        assert_eq!(
+            exec_other_session(&mut client, "insert into shohin values ( 1, 'orange', 130, true )"),
+            Err(duplicate_key_error("shohin_id", vec![Field::set_i64(1)]))
+        );
+
+        client.handle_query("rollback").unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 1);
+        exec_other_session(&mut client, "update shohin set price = 100 where shohin_id = 1").unwrap();
+    }
+
+    #[test]
+    fn test_set_lock_timeout() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+        client.handle_query("begin").unwrap();
+        client
+            .handle_query("delete from shohin where shohin_id = 1")
+            .unwrap();
+
+        // the session gives up on the lock instead of waiting until the delete ends
+        let mut session: Client = Client::new(client.ctx.clone());
+        session.handle_query("set lock_timeout = 50").unwrap();
+        assert_eq!(session.ctx.lock_timeout, Some(Duration::from_millis(50)));
+        assert_eq!(
+            session.handle_query("update shohin set price = 100 where shohin_id = 1"),
+            Err(ClientError::LockTimeoutError)
+        );
+
+        // and it applies to the transaction in progress
+        session.handle_query("begin").unwrap();
+        session.handle_query("set lock_timeout to 0").unwrap();
+        assert_eq!(session.ctx.lock_timeout, None);
+        assert_eq!(session.transaction.as_ref().unwrap().lock_timeout, None);
+        session.handle_query("rollback").unwrap();
+
+        assert_eq!(
+            session.handle_query("set lock_timeout = 'soon'"),
+            Err(ClientError::TypeMissmatchError("lock_timeout".to_string(), Field::set_str("soon")))
+        );
+        assert_eq!(
+            session.handle_query("set statement_timeout = 50"),
+            Err(ClientError::UnknownVariableError("statement_timeout".to_string()))
+        );
+        client.handle_query("rollback").unwrap();
+    }
+
+    fn show_rows(client: &Client, query: &str) -> Vec<Vec<Field>> {
+        let stmt: ShowStmt = match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::Show(stmt))) => stmt,
+            Ok(Statement::DML(DML::Describe(table_name))) => ShowStmt::Columns(table_name),
+            _ => panic!("unexpected statement"),
+        };
+        show_tuples(&client.ctx, stmt)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.fields)
+            .collect()
+    }
+
+    fn gen_schema_client() -> TestClient {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table kubun ( kubun_id int primary key, kubun_name char(10) default 'none' unique )")
+            .unwrap();
+        client
+            .handle_query("create table uriage ( uriage_id int, kubun_id int references kubun on delete cascade, amount int check ( amount > 0 and ( amount < 100 or amount = 500 ) ), primary key ( uriage_id ) ) engine = heap")
+            .unwrap();
+        client
+            .handle_query("create index uriage_amount on uriage using hash ( amount )")
+            .unwrap();
+        client
+    }
+
+    #[test]
+    fn test_show_tables_and_describe() {
+        let client: TestClient = gen_schema_client();
+        assert_eq!(
+            show_rows(&client, "show tables"),
+            vec![
+                vec![Field::set_str("kubun"), Field::set_str("base table")],
+                vec![Field::set_str("shohin"), Field::set_str("base table")],
+                vec![Field::set_str("uriage"), Field::set_str("base table")],
+            ]
+        );
+        assert_eq!(
+            show_rows(&client, "describe kubun"),
+            vec![
+                vec![
+                    Field::set_str("kubun_id"),
+                    Field::set_str("int"),
+                    Field::set_i64(0),
+                    Field::set_str(""),
+                    Field::set_str("primary key"),
+                ],
+                vec![
+                    Field::set_str("kubun_name"),
+                    Field::set_str("char(10)"),
+                    Field::set_i64(1),
+                    Field::set_str("'none'"),
+                    Field::set_str("unique"),
+                ],
+            ]
+        );
+        assert_eq!(
+            show_rows(&client, "describe uriage")[1][4],
+            Field::set_str("references kubun ( kubun_id )")
+        );
+        assert_eq!(
+            show_rows(&client, "show indexes from uriage"),
+            vec![
+                vec![
+                    Field::set_str("uriage_id"),
+                    Field::set_str("uriage_id"),
+                    Field::set_bool(true),
+                    Field::set_bool(true),
+                    Field::set_str("btree"),
+                ],
+                vec![
+                    Field::set_str("uriage_amount"),
+                    Field::set_str("amount"),
+                    Field::set_bool(false),
+                    Field::set_bool(false),
+                    Field::set_str("hash"),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_show_create_table() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("create table memo ( memo_id int, body char(20) default 'it''s', ratio float default 2, check ( body <> 'don''t' ) )")
+            .unwrap();
+        let statements: Vec<Field> = ["kubun", "uriage", "memo"]
+            .iter()
+            .map(|table_name| show_rows(&client, &format!("show create table {}", table_name))[0][1].clone())
+            .collect();
+        assert_eq!(
+            statements[1],
+            Field::set_str("create table uriage ( uriage_id int, kubun_id int, amount int, primary key ( uriage_id ), foreign key ( kubun_id ) references kubun ( kubun_id ) on delete cascade on update restrict, constraint uriage_amount_check1 check ( amount > 0 and ( amount < 100 or amount = 500 ) ) ) engine = heap")
+        );
+        // quotation marks in the values are doubled
+        assert_eq!(
+            select_rows(&client, "select column_default from information_schema.columns where table_name = 'memo' and column_name = 'body'"),
+            vec![vec![Field::set_str("'it''s'")]]
+        );
+        // a float keeps its decimal point, to be read back as a float
+        assert_eq!(
+            select_rows(&client, "select column_default from information_schema.columns where table_name = 'memo' and column_name = 'ratio'"),
+            vec![vec![Field::set_str("2.0")]]
+        );
+        assert_eq!(Field::set_init().to_literal(), Err(FieldError::NoValueError));
+
+        // the statements build the same tables again
+        client.handle_query("create database copy").unwrap();
+        client.handle_query("use copy").unwrap();
+        for statement in statements.iter() {
+            client.handle_query(&statement.get_str()).unwrap();
+        }
+        for (table_name, statement) in ["kubun", "uriage", "memo"].iter().zip(statements.iter()) {
+            assert_eq!(&show_rows(&client, &format!("show create table {}", table_name))[0][1], statement);
+        }
+    }
+
+    #[test]
+    fn test_heap_table_indices_on_disk() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+        client
+            .handle_query("create index uriage_kubun_id on uriage ( kubun_id )")
+            .unwrap();
+
+        // the b-tree indices of the heap table are files, the hash one is kept in memory
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        let trees: Vec<(String, bool)> = db
+            .table("uriage")
+            .unwrap()
+            .read()
+            .unwrap()
+            .indices
+            .iter()
+            .map(|index| (index.meta.name.clone(), matches!(index.tree, IndexTree::Disk(_))))
+            .collect();
+        assert_eq!(
+            trees,
+            vec![
+                ("uriage_id".to_string(), true),
+                ("uriage_amount".to_string(), false),
+                ("uriage_kubun_id".to_string(), true),
+            ]
+        );
+
+        client
+            .handle_query("update uriage set kubun_id = 2 where uriage_id = 1")
+            .unwrap();
+        client.handle_query("delete from uriage where uriage_id = 3").unwrap();
+
+        let meta: TableInfo = db.table_info_from_str("uriage").unwrap();
+        let condition = |query: &str| match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::Select(stmt))) => stmt.condition,
+            other => panic!("unexpected statement {:?}", other),
+        };
+        match plan_access_path(&meta, &condition("select uriage_id from uriage where kubun_id = 2")) {
+            AccessPath::IndexScan(ref index_name, _) if index_name == "uriage_kubun_id" => (),
+            access_path => panic!("unexpected access path {:?}", access_path),
+        }
+        assert_eq!(
+            select_rows(&client, "select uriage_id from uriage where kubun_id = 2"),
+            vec![vec![Field::set_i64(1)]]
+        );
+        assert_eq!(
+            select_rows(&client, "select amount from uriage where uriage_id >= 2"),
+            vec![vec![Field::set_i64(20)]]
+        );
+    }
+
+    #[test]
+    fn test_reopen_heap_table() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+        client
+            .handle_query("update uriage set amount = 30 where uriage_id = 2")
+            .unwrap();
+        client.handle_query("delete from uriage where uriage_id = 3").unwrap();
+
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        let meta: TableInfo = db.table_info_from_str("uriage").unwrap();
+        let amount: ColumnInfo = meta.column_info_from_str("amount").unwrap();
+
+        // an entry only the index file knows of, the file is used as it was left
+        db.table("uriage").unwrap().write().unwrap().indices[0]
+            .insert(&[Field::set_i64(9), Field::set_i64(1), Field::set_i64(10)], 99)
+            .unwrap();
+
+        // the rows are read back from the file, and the hash index built again over them.
+        // the committed deletes are kept from coming back, though they are not vacuumed yet
+        let mut table: Table = Table::open(meta, &db.data_dir).unwrap();
+        assert_eq!(table.indices[0].lookup(&[Field::set_i64(9)]).unwrap(), vec![99]);
+        let snapshot: Snapshot = db.snapshot();
+        let rows: Vec<Vec<Field>> = table
+            .rows(&snapshot)
+            .unwrap()
+            .into_iter()
+            .map(|(_, tuple)| tuple.fields)
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![Field::set_i64(1), Field::set_i64(1), Field::set_i64(10)],
+                vec![Field::set_i64(2), Field::set_i64(1), Field::set_i64(30)],
+            ]
+        );
+        assert_eq!(
+            table
+                .find_records(&[amount], &[Field::set_i64(30)], &snapshot)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(table.vacuum(snapshot.xmin(), &HashSet::new()), Ok(2));
+        assert_eq!(Table::open(table.meta.clone(), &db.data_dir).unwrap().versions.len(), 2);
+    }
+
+    #[test]
+    fn test_rollback_create_heap_table() {
+        let mut client: TestClient = gen_client();
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        client.handle_query("begin").unwrap();
+        client
+            .handle_query("create table uriage ( uriage_id int primary key, amount int ) engine = heap")
+            .unwrap();
+        assert_eq!(fs::read_dir(&db.data_dir).unwrap().count(), 2);
+
+        // the files of the table go away with it
+        client.handle_query("rollback").unwrap();
+        assert_eq!(fs::read_dir(&db.data_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_create_existing_heap_table() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 )")
+            .unwrap();
+
+        // the statement fails without touching the files of the table
+        assert_eq!(
+            client.handle_query("create table uriage ( uriage_id int ) engine = heap"),
+            Err(ClientError::DatabaseError(DatabaseError::DuplicateTableError("uriage".to_string())))
+        );
+        let rows: Vec<Vec<Field>> = vec![
+            vec![Field::set_i64(1), Field::set_i64(1), Field::set_i64(10)],
+            vec![Field::set_i64(2), Field::set_i64(1), Field::set_i64(20)],
+        ];
+        assert_eq!(fetch_rows(&mut client, "uriage"), rows);
+
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        let table: Table = Table::open(db.table_info_from_str("uriage").unwrap(), &db.data_dir).unwrap();
+        assert_eq!(table.store.scan().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_views() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+        client
+            .handle_query("create view small_uriage as select uriage_id, kubun_id, amount from uriage where amount < 100")
+            .unwrap();
+        client
+            .handle_query("create view kubun_total as select kubun_id, sum(amount) from small_uriage group by kubun_id")
+            .unwrap();
+
+        // a view is read like a table, and sees the rows as they are when it is read
+        assert_eq!(
+            select_rows(&client, "select uriage_id from small_uriage where amount > 10"),
+            vec![vec![Field::set_i64(2)]]
+        );
+        client
+            .handle_query("insert into uriage values ( 4, 2, 30 )")
+            .unwrap();
+        assert_eq!(
+            select_rows(&client, "select kubun_id, sum_amount from kubun_total where kubun_id = 2"),
+            vec![vec![Field::set_i64(2), Field::set_i64(30)]]
+        );
+        assert_eq!(
+            select_rows(
+                &client,
+                "select kubun.kubun_name, small_uriage.amount from kubun join small_uriage on kubun.kubun_id = small_uriage.kubun_id where small_uriage.amount > 20"
+            ),
+            vec![vec![Field::set_str("drink"), Field::set_i64(30)]]
+        );
+        assert_eq!(
+            show_rows(&client, "show tables"),
+            vec![
+                vec![Field::set_str("kubun"), Field::set_str("base table")],
+                vec![Field::set_str("kubun_total"), Field::set_str("view")],
+                vec![Field::set_str("shohin"), Field::set_str("base table")],
+                vec![Field::set_str("small_uriage"), Field::set_str("view")],
+                vec![Field::set_str("uriage"), Field::set_str("base table")],
+            ]
+        );
+
+        // the names are shared with the tables, and the query has to be readable
+        assert_eq!(
+            client.handle_query("create view kubun as select kubun_id from uriage"),
+            Err(ClientError::DatabaseError(DatabaseError::DuplicateTableError("kubun".to_string())))
+        );
+        assert_eq!(
+            client.handle_query("create view missing as select kubun_id from nothing"),
+            Err(ClientError::DatabaseError(DatabaseError::TableNotFoundError))
+        );
+        assert!(client.handle_query("insert into small_uriage values ( 5, 1, 40 )").is_err());
+
+        // a view read by another view stays until the other is dropped
+        assert_eq!(
+            client.handle_query("drop view small_uriage"),
+            Err(ClientError::DatabaseError(DatabaseError::ViewInUseError("kubun_total".to_string())))
+        );
+        client.handle_query("begin").unwrap();
+        client.handle_query("drop view kubun_total").unwrap();
+        client.handle_query("drop view small_uriage").unwrap();
+        client.handle_query("rollback").unwrap();
+        assert_eq!(select_rows(&client, "select count(kubun_id) from kubun_total"), vec![vec![Field::set_i64(2)]]);
+
+        client.handle_query("drop view kubun_total").unwrap();
+        client.handle_query("drop view small_uriage").unwrap();
+        assert_eq!(
+            select_rows(&client, "select table_name from information_schema.tables where table_type = 'view'"),
+            Vec::<Vec<Field>>::new()
+        );
+    }
+
+    #[test]
+    fn test_materialized_views() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+        client
+            .handle_query("create materialized view kubun_total as select kubun_id, sum(amount) from uriage group by kubun_id")
+            .unwrap();
+        // the groups come in no particular order
+        let mut rows: Vec<Vec<Field>> = fetch_rows(&mut client, "kubun_total");
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![
+                vec![Field::set_i64(1), Field::set_i64(30)],
+                vec![Field::set_i64(2), Field::set_i64(500)],
+            ]
+        );
+
+        // the stored rows stay as they are until the view is refreshed
+        client
+            .handle_query("insert into uriage values ( 4, 1, 40 )")
+            .unwrap();
+        assert_eq!(
+            select_rows(&client, "select sum_amount from kubun_total where kubun_id = 1"),
+            vec![vec![Field::set_i64(30)]]
+        );
+        client.handle_query("refresh materialized view kubun_total").unwrap();
+        assert_eq!(
+            select_rows(&client, "select sum_amount from kubun_total where kubun_id = 1"),
+            vec![vec![Field::set_i64(70)]]
+        );
+        client.handle_query("begin").unwrap();
+        client.handle_query("delete from uriage where kubun_id = 2").unwrap();
+        client.handle_query("refresh materialized view kubun_total").unwrap();
+        client.handle_query("rollback").unwrap();
+        assert_eq!(select_rows(&client, "select kubun_id from kubun_total").len(), 2);
+
+        // only a refresh writes the rows
+        assert_eq!(
+            client.handle_query("delete from kubun_total"),
+            Err(ClientError::ReadOnlyTableError("kubun_total".to_string()))
+        );
+        assert_eq!(
+            show_rows(&client, "show tables")[1],
+            vec![Field::set_str("kubun_total"), Field::set_str("materialized view")]
+        );
+
+        client
+            .handle_query("create view big_total as select kubun_id from kubun_total where sum_amount > 100")
+            .unwrap();
+        assert_eq!(
+            client.handle_query("drop materialized view kubun_total"),
+            Err(ClientError::DatabaseError(DatabaseError::ViewInUseError("big_total".to_string())))
+        );
+        client.handle_query("drop view big_total").unwrap();
+        client.handle_query("begin").unwrap();
+        client.handle_query("drop materialized view kubun_total").unwrap();
+        client.handle_query("rollback").unwrap();
+        assert_eq!(fetch_rows(&mut client, "kubun_total").len(), 2);
+        client.handle_query("drop materialized view kubun_total").unwrap();
+        assert_eq!(
+            client.handle_query("refresh materialized view kubun_total"),
+            Err(ClientError::DatabaseError(DatabaseError::TableNotFoundError))
+        );
+    }
+
+    #[test]
+    fn test_subqueries() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' ), ( 3, 'book' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+
+        // a derived table is read under its alias
+        assert_eq!(
+            select_rows(
+                &client,
+                "select small.uriage_id from ( select uriage_id, amount from uriage where amount < 100 ) as small where small.amount > 10"
+            ),
+            vec![vec![Field::set_i64(2)]]
+        );
+        assert_eq!(
+            select_rows(
+                &client,
+                "select kubun.kubun_name, total.sum_amount from kubun join ( select kubun_id, sum(amount) from uriage where kubun_id = 2 group by kubun_id ) total on kubun.kubun_id = total.kubun_id"
+            ),
+            vec![vec![Field::set_str("drink"), Field::set_i64(500)]]
+        );
+
+        assert_eq!(
+            select_rows(&client, "select kubun_name from kubun where kubun_id in ( select kubun_id from uriage where amount < 100 )"),
+            vec![vec![Field::set_str("food")]]
+        );
+
+        // a correlated subquery is run for each row of the outer query
+        assert_eq!(
+            select_rows(
+                &client,
+                "select kubun_id from kubun where exists ( select uriage_id from uriage where uriage.kubun_id = kubun.kubun_id ) and kubun_id > 1"
+            ),
+            vec![vec![Field::set_i64(2)]]
+        );
+        assert_eq!(
+            select_rows(
+                &client,
+                "select kubun_id, ( select sum(amount) from uriage where uriage.kubun_id = kubun.kubun_id ) from kubun"
+            ),
+            vec![
+                vec![Field::set_i64(1), Field::set_i64(30)],
+                vec![Field::set_i64(2), Field::set_i64(500)],
+                vec![Field::set_i64(3), Field::set_init()],
+            ]
+        );
+        assert_eq!(
+            select_rows(&client, "select uriage_id from uriage where amount > ( select amount from uriage where uriage_id = 1 ) and amount < 100"),
+            vec![vec![Field::set_i64(2)]]
+        );
+
+        // a subquery nested in a correlated one sees the outermost query too
+        assert_eq!(
+            select_rows(
+                &client,
+                "select kubun_id from kubun where exists ( select uriage_id from uriage where amount > 100 and exists ( select uriage_id from uriage where kubun_id = kubun.kubun_id ) )"
+            ),
+            vec![vec![Field::set_i64(1)], vec![Field::set_i64(2)]]
+        );
+
+        // a scalar subquery must not yield more than a row
+        assert_eq!(
+            client.handle_query("select kubun_id, ( select amount from uriage where uriage.kubun_id = kubun.kubun_id ) from kubun"),
+            Err(ClientError::MultipleRowsError(
+                "select amount from uriage where uriage.kubun_id = kubun.kubun_id".to_string()
+            ))
+        );
+
+        let result = client.handle_query("create table broken ( a int, check ( a in ( select kubun_id from kubun ) ) )");
+        assert_eq!(result, Err(ClientError::SelectorError(SelectorError::SubQueryError)));
+    }
+
+    fn set_operation_rows(client: &Client, query: &str) -> Result<Vec<Vec<Field>>, ClientError> {
+        let stmt: SetOperationStmt = match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::SetOperation(stmt))) => stmt,
+            other => panic!("unexpected statement {:?}", other),
+        };
+        match client.ctx.db {
+            None => Ok(vec![]),
+            Some(ref db) => {
+                let tuples: Vec<Tuple> = try!(set_operation_tuples(&client.ctx, &client_snapshot(client, db), stmt));
+                let mut rows: Vec<Vec<Field>> = tuples.into_iter().map(|t| t.fields).collect();
+                rows.sort_by(|a, b| a[0].compare(&b[0]).unwrap());
+                Ok(rows)
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' ), ( 3, 'book' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+
+        let ids = |ids: &[i64]| -> Vec<Vec<Field>> { ids.iter().map(|id| vec![Field::set_i64(*id)]).collect() };
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_id from kubun union select kubun_id from uriage"),
+            Ok(ids(&[1, 2, 3]))
+        );
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_id from kubun union all select kubun_id from uriage"),
+            Ok(ids(&[1, 1, 1, 2, 2, 3]))
+        );
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_id from kubun intersect select kubun_id from uriage"),
+            Ok(ids(&[1, 2]))
+        );
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_id from kubun except select kubun_id from uriage"),
+            Ok(ids(&[3]))
+        );
+
+        // INTERSECT is applied before UNION
+        assert_eq!(
+            set_operation_rows(
+                &client,
+                "select kubun_id from kubun where kubun_id = 3 union select kubun_id from kubun intersect select kubun_id from uriage where amount > 100"
+            ),
+            Ok(ids(&[2, 3]))
+        );
+
+        // the integers are converted into floats to be compared with them
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 20, false )")
+            .unwrap();
+        assert_eq!(
+            set_operation_rows(&client, "select amount from uriage intersect select price from shohin"),
+            Ok(vec![vec![Field::set_f64(20.0)]])
+        );
+
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_id, kubun_name from kubun union select kubun_id from uriage"),
+            Err(ClientError::MissmatchColumnNumberError)
+        );
+        assert_eq!(
+            set_operation_rows(&client, "select kubun_name from kubun union select kubun_id from uriage"),
+            Err(ClientError::IncompatibleColumnError("kubun_name".to_string()))
+        );
+    }
+
+    fn with_rows(client: &Client, query: &str) -> Result<Vec<Vec<Field>>, ClientError> {
+        let stmt: WithStmt = match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::With(stmt))) => stmt,
+            other => panic!("unexpected statement {:?}", other),
+        };
+        match client.ctx.db {
+            None => Ok(vec![]),
+            Some(ref db) => {
+                let tuples: Vec<Tuple> = try!(with_tuples(&client.ctx, &client_snapshot(client, db), stmt));
+                let mut rows: Vec<Vec<Field>> = tuples.into_iter().map(|t| t.fields).collect();
+                rows.sort_by(|a, b| a[0].compare(&b[0]).unwrap());
+                Ok(rows)
+            }
+        }
+    }
+
+    #[test]
+    fn test_common_table_expressions() {
+        let mut client: TestClient = gen_schema_client();
+        client
+            .handle_query("insert into kubun values ( 1, 'food' ), ( 2, 'drink' ), ( 3, 'book' )")
+            .unwrap();
+        client
+            .handle_query("insert into uriage values ( 1, 1, 10 ), ( 2, 1, 20 ), ( 3, 2, 500 )")
+            .unwrap();
+
+        // an expression reads the ones before it, and hides the table of the same name
+        assert_eq!(
+            with_rows(
+                &client,
+                "with small as ( select uriage_id, kubun_id, amount from uriage where amount < 100 ), kubun ( id, total ) as ( select kubun_id, sum(amount) from small group by kubun_id ) select kubun.id, kubun.total from kubun"
+            ),
+            Ok(vec![vec![Field::set_i64(1), Field::set_i64(30)]])
+        );
+        assert_eq!(
+            with_rows(
+                &client,
+                "with sold as ( select kubun_id from uriage ) select kubun_id from kubun except select kubun_id from sold"
+            ),
+            Ok(vec![vec![Field::set_i64(3)]])
+        );
+        assert_eq!(
+            with_rows(&client, "with sold ( a, b ) as ( select kubun_id from uriage ) select a from sold"),
+            Err(ClientError::MissmatchColumnNumberError)
+        );
+    }
+
+    #[test]
+    fn test_recursive_common_table_expressions() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table category ( id int primary key, parent_id int, name char(10) )")
+            .unwrap();
+        client
+            .handle_query("insert into category values ( 1, 0, 'food' ), ( 2, 1, 'fruit' ), ( 3, 2, 'apple' ), ( 4, 1, 'drink' ), ( 5, 0, 'book' ), ( 6, 5, 'comic' )")
+            .unwrap();
+
+        // walks down the tree from food
+        assert_eq!(
+            with_rows(
+                &client,
+                "with recursive tree as ( select id, name from category where id = 1 union all select category.id, category.name from category join tree on category.parent_id = tree.id ) select id, name from tree"
+            ),
+            Ok(vec![
+                vec![Field::set_i64(1), Field::set_str("food")],
+                vec![Field::set_i64(2), Field::set_str("fruit")],
+                vec![Field::set_i64(3), Field::set_str("apple")],
+                vec![Field::set_i64(4), Field::set_str("drink")],
+            ])
+        );
+
+        // and up from apple, UNION ends a cycle once no new row is found
+        client
+            .handle_query("update category set parent_id = 3 where id = 1")
+            .unwrap();
+        assert_eq!(
+            with_rows(
+                &client,
+                "with recursive ancestors ( ancestor_id ) as ( select parent_id from category where id = 3 union select category.parent_id from category join ancestors on category.id = ancestors.ancestor_id ) select ancestor_id from ancestors"
+            ),
+            Ok(vec![vec![Field::set_i64(1)], vec![Field::set_i64(2)], vec![Field::set_i64(3)]])
+        );
+
+        // while UNION ALL goes around it until the recursion limit
+        assert_eq!(
+            with_rows(
+                &client,
+                "with recursive ancestors ( ancestor_id ) as ( select parent_id from category where id = 3 union all select category.parent_id from category join ancestors on category.id = ancestors.ancestor_id ) select ancestor_id from ancestors"
+            ),
+            Err(ClientError::RecursionLimitError("ancestors".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_information_schema() {
+        let mut client: TestClient = gen_schema_client();
+        client.handle_query("create database office").unwrap();
+        assert_eq!(
+            select_rows(&client, "select table_name, engine from information_schema.tables where table_schema = 'test'"),
+            vec![
+                vec![Field::set_str("kubun"), Field::set_str("memory")],
+                vec![Field::set_str("shohin"), Field::set_str("memory")],
+                vec![Field::set_str("uriage"), Field::set_str("heap")],
+            ]
+        );
+        assert_eq!(
+            select_rows(
+                &client,
+                "select column_name, ordinal_position, data_type from information_schema.columns where table_name = 'uriage' and column_name <> 'kubun_id'"
+            ),
+            vec![
+                vec![Field::set_str("uriage_id"), Field::set_i64(1), Field::set_str("int")],
+                vec![Field::set_str("amount"), Field::set_i64(3), Field::set_str("int")],
+            ]
+        );
+        assert_eq!(
+            select_rows(
+                &client,
+                "select index_name, column_names, is_unique, index_type from information_schema.indexes where table_name = 'uriage' and index_type = 'hash'"
+            ),
+            vec![vec![
+                Field::set_str("uriage_amount"),
+                Field::set_str("amount"),
+                Field::set_bool(false),
+                Field::set_str("hash"),
+            ]]
+        );
+
+        // the tables follow the catalog, and can not be written
+        client.handle_query("use office").unwrap();
+        client.handle_query("create table staff ( staff_id int )").unwrap();
+        assert_eq!(
+            select_rows(&client, "select table_schema from information_schema.tables where table_name = 'staff'"),
+            vec![vec![Field::set_str("office")]]
+        );
+        assert!(client.handle_query("insert into information_schema.tables values ( 'a', 'b', 'c', 'd' )").is_err());
+        assert_eq!(
+            client.handle_query("create database information_schema"),
+            Err(ClientError::CatalogError(CatalogError::DuplicateDatabaseError(
+                "information_schema".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_multiple_databases() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+            .unwrap();
+
+        client.handle_query("create database office").unwrap();
+        client.handle_query("use office").unwrap();
+        client
+            .handle_query("create table shohin ( shohin_id int, staff_name char(10) )")
+            .unwrap();
+        client
+            .handle_query("insert into shohin values ( 7, 'suzuki' )")
+            .unwrap();
+
+        // the same table name is free in each database, the other database is reached by its name
+        assert_eq!(
+            select_rows(&client, "select shohin_id, staff_name from shohin"),
+            vec![vec![Field::set_i64(7), Field::set_str("suzuki")]]
+        );
+        assert_eq!(
+            select_rows(&client, "select test.shohin.shohin_name from test.shohin where test.shohin.price > 100"),
+            vec![vec![Field::set_str("apple")]]
+        );
+
+        assert_eq!(
+            client.handle_query("use nowhere"),
+            Err(ClientError::CatalogError(CatalogError::DatabaseNotFoundError("nowhere".to_string())))
+        );
+        client.handle_query("begin").unwrap();
+        assert_eq!(
+            client.handle_query("use test"),
+            Err(ClientError::from(TransactionError::ActiveTransactionError))
+        );
+        client.handle_query("rollback").unwrap();
+
+        client.handle_query("drop database office").unwrap();
+        assert_eq!(
+            client.handle_query("select shohin_id from shohin"),
+            Err(ClientError::DatabaseNotFoundError)
+        );
+        client.handle_query("use test").unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_sessions() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create unique index shohin_id on shohin ( shohin_id )")
+            .unwrap();
+
+        let handles: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|n| {
+                let mut session: Client = Client::new(client.ctx.clone());
+                thread::spawn(move || {
+                    for i in 0..25 {
+                        let shohin_id: usize = n * 25 + i;
+                        session
+                            .handle_query(&format!("insert into shohin values ( {}, 'apple', 100, false )", shohin_id))
+                            .unwrap();
+                        session
+                            .handle_query(&format!("update shohin set price = 200 where shohin_id = {}", shohin_id))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let rows: Vec<Vec<Field>> = fetch_rows(&mut client, "shohin");
+        assert_eq!(rows.len(), 100);
+        assert!(rows.iter().all(|row| row[2] == Field::set_f64(200.0)));
+        vacuum(&client);
+        assert_eq!(count_versions(&client, "shohin"), 100);
+    }
+
+    #[test]
+    fn test_deadlock_victim() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false ), ( 2, 'orange', 130, true )")
+            .unwrap();
+
+        // each session locks its own row first and then the row of the other one
+        let barrier: Arc<Barrier> = Arc::new(Barrier::new(2));
+        let handles: Vec<thread::JoinHandle<(Result<(), ClientError>, bool)>> = vec![(1, 2), (2, 1)]
+            .into_iter()
+            .map(|(first, second)| {
+                let mut session: Client = Client::new(client.ctx.clone());
+                let barrier: Arc<Barrier> = barrier.clone();
+                thread::spawn(move || {
+                    session.handle_query("begin").unwrap();
+                    session
+                        .handle_query(&format!("update shohin set price = 0 where shohin_id = {}", first))
+                        .unwrap();
+                    barrier.wait();
+                    let result = session.handle_query(&format!("delete from shohin where shohin_id = {}", second));
+                    let in_transaction: bool = session.transaction.is_some();
+                    if in_transaction {
+                        session.handle_query("commit").unwrap();
+                    }
+                    (result, in_transaction)
+                })
+            })
+            .collect();
+        let results: Vec<(Result<(), ClientError>, bool)> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // the victim is rolled back as a whole, only the row updated by the other one is left
+        let victims: Vec<&(Result<(), ClientError>, bool)> = results.iter().filter(|r| r.0.is_err()).collect();
+        assert_eq!(victims.len(), 1);
+        assert_eq!(victims[0], &(Err(ClientError::DeadlockError), false));
+        let rows: Vec<Vec<Field>> = fetch_rows(&mut client, "shohin");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][2], Field::set_f64(0.0));
+    }
+
+    #[test]
+    fn test_dropped_session_rolls_back() {
+        let mut client: TestClient = gen_client();
+        {
+            let mut session: Client = Client::new(client.ctx.clone());
+            session.handle_query("begin").unwrap();
+            session
+                .handle_query("insert into shohin values ( 1, 'apple', 300, false )")
+                .unwrap();
+        }
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 0);
+        assert_eq!(count_versions(&client, "shohin"), 0);
+    }
+
+    #[test]
+    fn test_hash_index() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 300, false ), ( 2, 'orange', 130, true ), ( 3, 'melon', 300, true )")
+            .unwrap();
+        client
+            .handle_query("create index shohin_price on shohin using hash ( price )")
+            .unwrap();
+        client
+            .handle_query("update shohin set price = 500 where shohin_id = 3")
+            .unwrap();
+        vacuum(&client);
+
+        assert_eq!(
+            fetch_index(&mut client, "shohin", "shohin_price"),
+            vec![
+                (vec![Field::set_f64(130.0)], vec![2]),
+                (vec![Field::set_f64(300.0)], vec![1]),
+                (vec![Field::set_f64(500.0)], vec![4]),
+            ]
+        );
+        assert_eq!(
+            select_rows(&client, "select shohin_name from shohin where price = 300"),
+            vec![vec![Field::set_str("apple")]]
+        );
+        assert_eq!(
+            select_rows(&client, "select shohin_name from shohin where price > 200").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_join_through_index() {
+        let mut client: TestClient = gen_kubun_client("restrict");
+        client
+            .handle_query("create index kubun_id_hash on kubun using hash ( kubun_id )")
+            .unwrap();
+
+        assert_eq!(
+            select_rows(&client, "select item.item_id, kubun.kubun_name from item join kubun on item.kubun_id = kubun.kubun_id"),
+            vec![
+                vec![Field::set_i64(1), Field::set_str("fruit")],
+                vec![Field::set_i64(2), Field::set_str("fruit")],
+                vec![Field::set_i64(3), Field::set_str("vegetable")],
+            ]
+        );
+        assert_eq!(
+            select_rows(&client, "select item.item_id from item join kubun on kubun.kubun_id = item.kubun_id and kubun.kubun_name = 'fruit'"),
+            vec![vec![Field::set_i64(1)], vec![Field::set_i64(2)]]
+        );
+
+        // a condition which can not be evaluated fails the join, instead of pairing every row
+        assert_eq!(
+            client.handle_query("select item.item_id from item join kubun on item.kubun_id = kubun.kubun_id and exists ( select kubun_id from kubun )"),
+            Err(ClientError::SelectorError(SelectorError::SubQueryError))
+        );
+        assert_eq!(
+            client.handle_query("select item.item_id from item join kubun on exists ( select kubun_id from kubun )"),
+            Err(ClientError::SelectorError(SelectorError::SubQueryError))
+        );
+
+        // a lookup through an index which is gone fails the join, instead of ending it early
+        let db: Arc<Database> = client.ctx.db.clone().unwrap();
+        let snapshot: Snapshot = db.snapshot();
+        let outer: TableScanExec = TableScanExec::new(
+            db.table("item").unwrap(),
+            db.table_info_from_str("item").unwrap(),
+            vec![Range::new(0, usize::MAX)],
+            snapshot.clone(),
+        );
+        let result = NestedLoopJoinExec::with_index(outer, db.table("kubun").unwrap(), "missing".to_string(), vec![1], None, snapshot);
+        assert_eq!(
+            result.err(),
+            Some(JoinExecError::TableError(TableError::IndexNotFoundError("missing".to_string())))
+        );
+    }
+
+    fn duplicate_key_error(index_name: &str, key: Vec<Field>) -> ClientError {
+        ClientError::DatabaseError(DatabaseError::TableError(
+            TableError::IndexError(IndexError::DuplicateKeyError(index_name.to_string(), key)),
+        ))
+    }
+
+    #[test]
+    fn test_primary_key_and_unique() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table member ( id int primary key, email char(40) unique, name char(10) )")
+            .unwrap();
+        client
+            .handle_query("insert into member values ( 1, 'a@example.com', 'alice' )")
+            .unwrap();
+
+        let result = client.handle_query("insert into member values ( 1, 'b@example.com', 'bob' )");
+        assert_eq!(result, Err(duplicate_key_error("id", vec![Field::set_i64(1)])));
+
+        let result = client.handle_query("insert into member values ( 2, 'a@example.com', 'bob' )");
+        assert_eq!(
+            result,
+            Err(duplicate_key_error("email", vec![Field::set_str("a@example.com")]))
+        );
+
+        let result = client.handle_query("insert into member values ( 2, 'b@example.com', 'bob' ), ( 3, 'b@example.com', 'carol' )");
+        assert_eq!(
+            result,
+            Err(duplicate_key_error("email", vec![Field::set_str("b@example.com")]))
+        );
+        assert_eq!(fetch_rows(&mut client, "member").len(), 1);
+    }
+
+    #[test]
+    fn test_composite_primary_key() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table stock ( shop_id int, shohin_id int, amount int, primary key ( shop_id, shohin_id ) )")
+            .unwrap();
+        client
+            .handle_query("insert into stock values ( 1, 1, 10 ), ( 1, 2, 20 ), ( 2, 1, 30 )")
+            .unwrap();
+
+        let result = client.handle_query("insert into stock values ( 1, 2, 40 )");
+        assert_eq!(
+            result,
+            Err(duplicate_key_error(
+                "shop_id_shohin_id",
+                vec![Field::set_i64(1), Field::set_i64(2)]
+            ))
+        );
+
+        let result = client.handle_query("create table broken ( a int primary key, b int, primary key ( b ) )");
+        assert_eq!(result, Err(ClientError::MultiplePrimaryKeyError));
+    }
+
+    #[test]
+    fn test_update_checks_unique_keys() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table member ( id int primary key, name char(10) )")
+            .unwrap();
+        client
+            .handle_query("insert into member values ( 1, 'alice' ), ( 2, 'bob' )")
+            .unwrap();
+
+        let result = client.handle_query("update member set id = 1 where name = 'bob'");
+        assert_eq!(result, Err(duplicate_key_error("id", vec![Field::set_i64(1)])));
+
+        let result = client.handle_query("update member set id = 3, name = 'robert' where id = 2");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "member"),
+            vec![
+                vec![Field::set_i64(1), Field::set_str("alice")],
+                vec![Field::set_i64(3), Field::set_str("robert")],
+            ]
+        );
+
+        let result = client.handle_query("update member set id = 2 where id = 1");
+        assert_eq!(result, Ok(()));
+        let result = client.handle_query("insert into member values ( 1, 'carol' )");
+        assert_eq!(result, Ok(()));
+    }
+
+    fn gen_kubun_client(on_delete: &str) -> TestClient {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table kubun ( kubun_id int primary key, kubun_name char(10) )")
+            .unwrap();
+        client
+            .handle_query(&format!("create table item ( item_id int primary key, kubun_id int default 0 references kubun ( kubun_id ) on delete {} on update cascade )", on_delete))
+            .unwrap();
+        client
+            .handle_query("insert into kubun values ( 0, 'other' ), ( 1, 'fruit' ), ( 2, 'vegetable' )")
+            .unwrap();
+        client
+            .handle_query("insert into item values ( 1, 1 ), ( 2, 1 ), ( 3, 2 )")
+            .unwrap();
+        client
+    }
+
+    fn foreign_key_error(key: Vec<Field>) -> ClientError {
+        ClientError::DatabaseError(DatabaseError::ForeignKeyViolationError(
+            "item_kubun_id_fkey".to_string(),
+            key,
+        ))
+    }
+
+    #[test]
+    fn test_foreign_key_rejects_orphan_rows() {
+        let mut client: TestClient = gen_kubun_client("restrict");
+        let result = client.handle_query("insert into item values ( 4, 5 )");
+        assert_eq!(result, Err(foreign_key_error(vec![Field::set_i64(5)])));
+
+        let result = client.handle_query("update item set kubun_id = 5 where item_id = 1");
+        assert_eq!(result, Err(foreign_key_error(vec![Field::set_i64(5)])));
+        assert_eq!(fetch_rows(&mut client, "item").len(), 3);
+    }
+
+    #[test]
+    fn test_foreign_key_on_delete_restrict() {
+        let mut client: TestClient = gen_kubun_client("restrict");
+        let result = client.handle_query("delete from kubun where kubun_id = 1");
+        assert_eq!(result, Err(foreign_key_error(vec![Field::set_i64(1)])));
+        assert_eq!(fetch_rows(&mut client, "kubun").len(), 3);
+
+        let result = client.handle_query("delete from kubun where kubun_id = 0");
+        assert_eq!(result, Ok(()));
+        assert_eq!(fetch_rows(&mut client, "kubun").len(), 2);
+    }
+
+    #[test]
+    fn test_foreign_key_on_delete_cascade() {
+        let mut client: TestClient = gen_kubun_client("cascade");
+        let result = client.handle_query("delete from kubun where kubun_id = 1");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "item"),
+            vec![vec![Field::set_i64(3), Field::set_i64(2)]]
+        );
+    }
+
+    #[test]
+    fn test_foreign_key_on_delete_set_default() {
+        let mut client: TestClient = gen_kubun_client("set default");
+        let result = client.handle_query("delete from kubun where kubun_id = 1");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "item"),
+            vec![
+                vec![Field::set_i64(3), Field::set_i64(2)],
+                vec![Field::set_i64(1), Field::set_i64(0)],
+                vec![Field::set_i64(2), Field::set_i64(0)],
+            ]
+        );
+
+        // the default has to exist as well
+        let result = client.handle_query("delete from kubun where kubun_id = 0");
+        assert_eq!(result, Err(foreign_key_error(vec![Field::set_i64(0)])));
+        assert_eq!(fetch_rows(&mut client, "kubun").len(), 2);
+    }
+
+    #[test]
+    fn test_foreign_key_on_update_cascade() {
+        let mut client: TestClient = gen_kubun_client("restrict");
+        let result = client.handle_query("update kubun set kubun_id = 10 where kubun_id = 2");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "item")[2],
+            vec![Field::set_i64(3), Field::set_i64(10)]
+        );
+    }
+
+    fn assert_no_orphans(client: &mut Client) {
+        let kubun_ids: Vec<Field> = fetch_rows(client, "kubun").into_iter().map(|row| row[0].clone()).collect();
+        for row in fetch_rows(client, "item") {
+            assert!(kubun_ids.contains(&row[1]), "orphan item {:?}", row);
+        }
+    }
+
+    #[test]
+    fn test_foreign_key_with_concurrent_sessions() {
+        // the child is inserted first, the delete of the parent waits for it to commit
+        // and does not overlook the child its snapshot is too old to see
+        for &(on_delete, conflicts) in [("restrict", false), ("cascade", true)].iter() {
+            let mut client: TestClient = gen_kubun_client(on_delete);
+            client.handle_query("begin").unwrap();
+            client.handle_query("insert into item values ( 4, 0 )").unwrap();
+
+            let barrier: Arc<Barrier> = Arc::new(Barrier::new(2));
+            let handle: thread::JoinHandle<Result<(), ClientError>> = {
+                let mut session: Client = Client::new(client.ctx.clone());
+                let barrier: Arc<Barrier> = barrier.clone();
+                thread::spawn(move || {
+                    session.handle_query("begin").unwrap();
+                    barrier.wait();
+                    let result = session.handle_query("delete from kubun where kubun_id = 0");
+                    if session.transaction.is_some() {
+                        session.handle_query("commit").unwrap();
+                    }
+                    result
+                })
+            };
+            barrier.wait();
+            thread::sleep(Duration::from_millis(100));
+            client.handle_query("commit").unwrap();
+
+            let expected: ClientError = if conflicts {
+                ClientError::DatabaseError(DatabaseError::TableError(TableError::TransactionError(
+                    TransactionError::WriteConflictError("item".to_string(), 4),
+                )))
+            } else {
+                foreign_key_error(vec![Field::set_i64(0)])
+            };
+            assert_eq!(handle.join().unwrap(), Err(expected));
+            assert_eq!(fetch_rows(&mut client, "item").len(), 4);
+            assert_no_orphans(&mut client);
+        }
+
+        // the parent is deleted first, the child waits for it to commit and is rejected
+        let mut client: TestClient = gen_kubun_client("restrict");
+        client.handle_query("begin").unwrap();
+        client.handle_query("delete from kubun where kubun_id = 0").unwrap();
+
+        let handle: thread::JoinHandle<Result<(), ClientError>> = {
+            let mut session: Client = Client::new(client.ctx.clone());
+            thread::spawn(move || session.handle_query("insert into item values ( 4, 0 )"))
+        };
+        thread::sleep(Duration::from_millis(100));
+        client.handle_query("commit").unwrap();
+
+        assert_eq!(handle.join().unwrap(), Err(foreign_key_error(vec![Field::set_i64(0)])));
+        assert_eq!(fetch_rows(&mut client, "item").len(), 3);
+        assert_no_orphans(&mut client);
+    }
+
+    #[test]
+    fn test_table_level_foreign_key() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table category ( id int primary key, parent_id int, foreign key ( parent_id ) references category on delete cascade )")
+            .unwrap();
+        client
+            .handle_query("insert into category values ( 1, 1 ), ( 2, 1 ), ( 3, 2 ), ( 4, 4 )")
+            .unwrap();
+
+        let result = client.handle_query("delete from category where id = 2");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_rows(&mut client, "category"),
+            vec![
+                vec![Field::set_i64(1), Field::set_i64(1)],
+                vec![Field::set_i64(4), Field::set_i64(4)],
+            ]
+        );
+
+        let result = client.handle_query("create table broken ( shohin_id int references shohin )");
+        assert_eq!(
+            result,
+            Err(ClientError::InvalidReferenceError("shohin".to_string()))
+        );
+    }
+
+    fn check_error(name: &str) -> ClientError {
+        ClientError::DatabaseError(DatabaseError::TableError(
+            TableError::CheckViolationError(name.to_string()),
+        ))
+    }
+
+    #[test]
+    fn test_check_constraints() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create table stock ( id int primary key, price float check ( price >= 0 and price <= 1000 ), amount int, constraint stock_limit check ( amount < 100 or id = 0 ) )")
+            .unwrap();
+
+        let result = client.handle_query("insert into stock values ( 1, 100, 10 ), ( 2, 1500, 10 )");
+        assert_eq!(result, Err(check_error("stock_price_check1")));
+        assert_eq!(fetch_rows(&mut client, "stock").len(), 0);
+
+        let result = client.handle_query("insert into stock values ( 1, 100, 10 ), ( 0, 0, 500 )");
+        assert_eq!(result, Ok(()));
+
+        let result = client.handle_query("update stock set amount = 100 where id = 1");
+        assert_eq!(result, Err(check_error("stock_limit")));
+        assert_eq!(
+            fetch_rows(&mut client, "stock")[0],
+            vec![Field::set_i64(1), Field::set_f64(100.0), Field::set_i64(10)]
+        );
+
+        let result = client.handle_query("create table broken ( a int, check ( b > 0 ) )");
+        assert_eq!(
+            result,
+            Err(ClientError::TableInfoError(TableInfoError::ColumnNotFoundError))
+        );
+
+        // the checks of the same column are told apart by their position
+        client
+            .handle_query("create table bounded ( a int check ( a > 0 ) check ( a < 10 ), check ( a <> 5 ) )")
+            .unwrap();
+        let result = client.handle_query("insert into bounded values ( 0 )");
+        assert_eq!(result, Err(check_error("bounded_a_check1")));
+        let result = client.handle_query("insert into bounded values ( 10 )");
+        assert_eq!(result, Err(check_error("bounded_a_check2")));
+        let result = client.handle_query("insert into bounded values ( 5 )");
+        assert_eq!(result, Err(check_error("bounded_check3")));
+    }
+
+    #[test]
+    fn test_update_with_and_or_conditions() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 100, false ), ( 2, 'orange', 200, false ), ( 3, 'melon', 300, false )")
+            .unwrap();
+
+        let result = client.handle_query("delete from shohin where shohin_id = 1 and price > 150 or ( shohin_id = 3 and price = 300 )");
+        assert_eq!(result, Ok(()));
+        let rows: Vec<Vec<Field>> = fetch_rows(&mut client, "shohin");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][1], Field::set_str("orange"));
+    }
+
+    #[test]
+    fn test_in_between_like_and_not() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("create unique index shohin_id on shohin ( shohin_id )")
+            .unwrap();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 100, false ), ( 2, 'Apricot', 200, true ), ( 3, 'melon', 300, false ), ( 4, 'banana', 400, true )")
+            .unwrap();
+
+        let select_ids = |query: &str| -> Vec<Vec<Field>> {
+            let mut rows: Vec<Vec<Field>> = select_rows(&client, query);
+            rows.sort_by(|a, b| a[0].compare(&b[0]).unwrap());
+            rows
+        };
+        assert_eq!(
+            select_ids("select shohin_id from shohin where shohin_id in ( 4, 1, 9 )"),
+            vec![vec![Field::set_i64(1)], vec![Field::set_i64(4)]]
+        );
+        assert_eq!(
+            select_ids("select shohin_id from shohin where shohin_id between 2 and 3 and price between 250 and 1000"),
+            vec![vec![Field::set_i64(3)]]
+        );
+        assert_eq!(
+            select_ids("select shohin_id from shohin where shohin_name like 'ap%'"),
+            vec![vec![Field::set_i64(1)]]
+        );
+        assert_eq!(
+            select_ids("select shohin_id from shohin where shohin_name ilike 'ap_i%'"),
+            vec![vec![Field::set_i64(2)]]
+        );
+        assert_eq!(
+            select_ids("select shohin_id from shohin where not ( discounted = true or price > 250 )"),
+            vec![vec![Field::set_i64(1)]]
+        );
+        assert_eq!(
+            select_ids("select shohin_id from shohin where shohin_id not in ( 1, 2 ) and shohin_name not like '%n'"),
+            vec![vec![Field::set_i64(4)]]
+        );
+
+        client
+            .handle_query("delete from shohin where shohin_id not between 2 and 3")
+            .unwrap();
+        assert_eq!(fetch_rows(&mut client, "shohin").len(), 2);
+    }
+
+    fn fetch_index(client: &mut Client, table_name: &str, index_name: &str) -> Vec<Entry> {
+        match client.ctx.db {
+            None => vec![],
+            Some(ref db) => db.table(table_name)
+                .unwrap()
+                .read()
+                .unwrap()
+                .indices
+                .iter()
+                .find(|index| index.meta.name == index_name)
+                .map_or(vec![], |index| index.entries().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_create_index_is_maintained() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 100, false ), ( 2, 'orange', 100, true )")
+            .unwrap();
+
+        let result = client.handle_query("create index shohin_price on shohin ( price )");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            fetch_index(&mut client, "shohin", "shohin_price"),
+            vec![(vec![Field::set_f64(100.0)], vec![1, 2])]
+        );
+
+        client
+            .handle_query("insert into shohin values ( 3, 'melon', 300, false )")
+            .unwrap();
+        client
+            .handle_query("update shohin set price = 300 where shohin_id = 1")
+            .unwrap();
+        client
+            .handle_query("delete from shohin where shohin_id = 2")
+            .unwrap();
+        vacuum(&client);
+        assert_eq!(
+            fetch_index(&mut client, "shohin", "shohin_price"),
+            vec![(vec![Field::set_f64(300.0)], vec![3, 4])]
+        );
+
+        let result = client.handle_query("create index shohin_price on shohin ( shohin_name )");
+        assert_eq!(
+            result,
+            Err(ClientError::DatabaseError(DatabaseError::TableError(
+                TableError::TableInfoError(TableInfoError::DuplicateIndexError("shohin_price".to_string())),
+            )))
+        );
+
+        let result = client.handle_query("drop index shohin_price");
+        assert_eq!(result, Ok(()));
+        assert_eq!(fetch_index(&mut client, "shohin", "shohin_price"), vec![]);
+
+        let result = client.handle_query("drop index shohin_price on shohin");
+        assert_eq!(
+            result,
+            Err(ClientError::DatabaseError(DatabaseError::TableError(
+                TableError::IndexNotFoundError("shohin_price".to_string()),
+            )))
+        );
+    }
+
+    #[test]
+    fn test_create_unique_index() {
+        let mut client: TestClient = gen_client();
+        client
+            .handle_query("insert into shohin values ( 1, 'apple', 100, false ), ( 2, 'apple', 200, true )")
+            .unwrap();
+
+        let result = client.handle_query("create unique index shohin_name_key on shohin ( shohin_name )");
+        assert_eq!(
+            result,
+            Err(duplicate_key_error("shohin_name_key", vec![Field::set_str("apple")]))
+        );
+
+        client
+            .handle_query("update shohin set shohin_name = 'orange' where shohin_id = 2")
+            .unwrap();
+        let result = client.handle_query("create unique index shohin_name_key on shohin ( shohin_name )");
+        assert_eq!(result, Ok(()));
+
+        let result = client.handle_query("insert into shohin values ( 3, 'orange', 300, false )");
+        assert_eq!(
+            result,
+            Err(duplicate_key_error("shohin_name_key", vec![Field::set_str("orange")]))
+        );
+    }
+
+    #[test]
+    fn test_drop_index_in_use() {
+        let mut client: TestClient = gen_kubun_client("restrict");
+        let result = client.handle_query("drop index item_id on item");
+        assert_eq!(
+            result,
+            Err(ClientError::DatabaseError(DatabaseError::IndexInUseError("item_id".to_string())))
+        );
+
+        client
+            .handle_query("create index item_kubun on item ( kubun_id )")
+            .unwrap();
+        client
+            .handle_query("create index item_kubun on kubun ( kubun_id )")
+            .unwrap();
+        let result = client.handle_query("drop index item_kubun");
+        assert_eq!(
+            result,
+            Err(ClientError::DatabaseError(DatabaseError::AmbiguousIndexError("item_kubun".to_string())))
+        );
+        let result = client.handle_query("drop index item_kubun on item");
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_insert_rejects_too_long_char() {
+        let mut client: TestClient = gen_client();
+        let result = client.handle_query("insert into shohin ( shohin_id, shohin_name, price, discounted ) values ( 1, 'grapefruits', 300, true )");
+        assert_eq!(
+            result,
+            Err(ClientError::TypeMissmatchError(
+                "shohin_name".to_string(),
+                Field::set_str("grapefruits")
+            ))
+        );
+    }
+}
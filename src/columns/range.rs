@@ -1,3 +1,7 @@
+use std::collections::Bound;
+
+use tables::field::Field;
+
 #[derive(Debug, Clone)]
 pub struct Range {
     pub low: usize,
@@ -13,5 +17,87 @@ impl Range {
     }
 }
 
+// keys of an index whose leading columns equal the prefix,
+// and whose next column lies between low and high
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRange {
+    pub prefix: Vec<Field>,
+    pub low: Bound<Field>,
+    pub high: Bound<Field>,
+}
+
+impl KeyRange {
+    pub fn new(prefix: Vec<Field>, low: Bound<Field>, high: Bound<Field>) -> KeyRange {
+        KeyRange {
+            prefix: prefix,
+            low: low,
+            high: high,
+        }
+    }
+
+    pub fn contains(&self, key: &[Field]) -> bool {
+        if key.len() < self.prefix.len() || key[..self.prefix.len()] != self.prefix[..] {
+            return false;
+        }
+
+        match key.get(self.prefix.len()) {
+            None => true,
+            Some(field) => {
+                let above_low: bool = match self.low {
+                    Bound::Unbounded => true,
+                    Bound::Included(ref low) => field >= low,
+                    Bound::Excluded(ref low) => field > low,
+                };
+                let below_high: bool = match self.high {
+                    Bound::Unbounded => true,
+                    Bound::Included(ref high) => field <= high,
+                    Bound::Excluded(ref high) => field < high,
+                };
+                above_low && below_high
+            }
+        }
+    }
+
+    // the smallest key which may be contained
+    pub fn start_key(&self) -> Vec<Field> {
+        let mut key: Vec<Field> = self.prefix.clone();
+        match self.low {
+            Bound::Included(ref low) | Bound::Excluded(ref low) => key.push(low.clone()),
+            Bound::Unbounded => {}
+        }
+        key
+    }
+
+    // whether no key after this one can be contained
+    pub fn is_passed(&self, key: &[Field]) -> bool {
+        if key.len() < self.prefix.len() || key[..self.prefix.len()] != self.prefix[..] {
+            return true;
+        }
+
+        match (key.get(self.prefix.len()), &self.high) {
+            (Some(field), Bound::Included(high)) => field > high,
+            (Some(field), Bound::Excluded(high)) => field >= high,
+            _ => false,
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_range_contains() {
+        let range: KeyRange = KeyRange::new(
+            vec![Field::set_i64(1)],
+            Bound::Excluded(Field::set_i64(10)),
+            Bound::Included(Field::set_i64(20)),
+        );
+        assert!(!range.contains(&[Field::set_i64(1), Field::set_i64(10)]));
+        assert!(range.contains(&[Field::set_i64(1), Field::set_i64(11)]));
+        assert!(range.contains(&[Field::set_i64(1), Field::set_i64(20)]));
+        assert!(!range.contains(&[Field::set_i64(2), Field::set_i64(15)]));
+        assert!(range.is_passed(&[Field::set_i64(1), Field::set_i64(21)]));
+        assert!(range.is_passed(&[Field::set_i64(2), Field::set_i64(0)]));
+    }
+}
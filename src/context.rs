@@ -1,10 +1,21 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalog::Catalog;
 use database::Database;
-use allocators::allocator::Allocator;
+use executors::tuple_scan::TupleScanExec;
 
-#[derive(Debug)]
+// the state of a single session, the databases are shared with the other sessions
+#[derive(Debug, Clone)]
 pub struct Context {
-    pub db: Option<Database>,
-    pub table_id_alloc: Box<Allocator>,
+    pub catalog: Arc<Catalog>,
+    // the database in use, none until one is chosen
+    pub db: Option<Arc<Database>>,
+    // how long the transactions of the session wait for a lock, forever when none
+    pub lock_timeout: Option<Duration>,
+    // the results of the common table expressions of the running statement, by their names
+    pub ctes: HashMap<String, TupleScanExec>,
 }
 
 #[cfg(test)]
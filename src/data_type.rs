@@ -1,9 +1,103 @@
+use tables::field::*;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DataType {
     Int,
+    Float,
     Bool,
     Char(u8),
 }
 
+impl DataType {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            DataType::Int => "int".to_string(),
+            DataType::Float => "float".to_string(),
+            DataType::Bool => "bool".to_string(),
+            DataType::Char(length) => format!("char({})", length),
+        }
+    }
+
+    // returns the field converted into this type,
+    // or None when no implicit coercion is defined
+    pub fn coerce(&self, field: &Field) -> Option<Field> {
+        match (self, field.kind) {
+            (&DataType::Int, KIND_I64) => Some(field.clone()),
+            (&DataType::Int, KIND_U64) => {
+                let value: u64 = field.get_u64();
+                if value <= (i64::MAX as u64) {
+                    Some(Field::set_i64(value as i64))
+                } else {
+                    None
+                }
+            }
+            (&DataType::Float, KIND_F64) => Some(field.clone()),
+            (&DataType::Float, KIND_I64) => Some(Field::set_f64(field.get_i64() as f64)),
+            (&DataType::Float, KIND_U64) => Some(Field::set_f64(field.get_u64() as f64)),
+            (&DataType::Bool, KIND_BOOL) => Some(field.clone()),
+            (&DataType::Char(length), KIND_STR) => {
+                if field.get_str().chars().count() <= length as usize {
+                    Some(field.clone())
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // the type which the fields of both types are coerced into, when the columns of two queries
+    // are combined into one
+    pub fn common(&self, other: &DataType) -> Option<DataType> {
+        match (self, other) {
+            (&DataType::Char(a), &DataType::Char(b)) => Some(DataType::Char(a.max(b))),
+            (&DataType::Int, &DataType::Float) | (&DataType::Float, &DataType::Int) => Some(DataType::Float),
+            _ if self == other => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coerce_int() {
+        assert_eq!(DataType::Int.coerce(&Field::set_i64(1)), Some(Field::set_i64(1)));
+        assert_eq!(DataType::Int.coerce(&Field::set_u64(1)), Some(Field::set_i64(1)));
+        assert_eq!(DataType::Int.coerce(&Field::set_f64(1.5)), None);
+        assert_eq!(DataType::Int.coerce(&Field::set_bool(true)), None);
+        assert_eq!(DataType::Int.coerce(&Field::set_str("1")), None);
+    }
+
+    #[test]
+    fn test_coerce_float() {
+        assert_eq!(DataType::Float.coerce(&Field::set_i64(2)), Some(Field::set_f64(2.0)));
+        assert_eq!(DataType::Float.coerce(&Field::set_f64(1.5)), Some(Field::set_f64(1.5)));
+        assert_eq!(DataType::Float.coerce(&Field::set_bool(false)), None);
+    }
+
+    #[test]
+    fn test_coerce_char() {
+        let char5: DataType = DataType::Char(5);
+        assert_eq!(char5.coerce(&Field::set_str("apple")), Some(Field::set_str("apple")));
+        assert_eq!(char5.coerce(&Field::set_str("orange")), None);
+        assert_eq!(char5.coerce(&Field::set_i64(1)), None);
+    }
+
+    #[test]
+    fn test_common() {
+        assert_eq!(DataType::Int.common(&DataType::Int), Some(DataType::Int));
+        assert_eq!(DataType::Int.common(&DataType::Float), Some(DataType::Float));
+        assert_eq!(DataType::Char(5).common(&DataType::Char(10)), Some(DataType::Char(10)));
+        assert_eq!(DataType::Bool.common(&DataType::Int), None);
+        assert_eq!(DataType::Char(5).common(&DataType::Int), None);
+    }
+
+    #[test]
+    fn test_coerce_bool() {
+        assert_eq!(DataType::Bool.coerce(&Field::set_bool(true)), Some(Field::set_bool(true)));
+        assert_eq!(DataType::Bool.coerce(&Field::set_i64(1)), None);
+    }
+}
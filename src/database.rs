@@ -1,50 +1,732 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, RwLock, Weak};
+use std::thread;
+use std::time::Duration;
 
 use meta::table_info::TableInfo;
-use tables::memory_table::{MemoryTable, MemoryTableError};
+use meta::column_info::ColumnInfo;
+use meta::index_info::IndexInfo;
+use meta::foreign_key_info::ForeignKeyInfo;
+use meta::view_info::ViewInfo;
+use tables::table::{Table, TableError};
+use tables::field::Field;
+use tables::tuple::Tuple;
+use allocators::allocator::Allocator;
+use parser::statement::{IndexType, ReferentialAction};
+use transaction::{Snapshot, Transaction, TransactionError, TransactionManager, TxId, UndoEntry};
+use lock_manager::{LockError, LockManager, LockMode, LockTarget};
 
-#[derive(Debug, Clone)]
+// the commits between two vacuums
+pub const VACUUM_INTERVAL: usize = 64;
+// how long the vacuum thread sleeps before it looks whether the database is still used
+const VACUUM_IDLE_TIME: Duration = Duration::from_millis(500);
+
+// shared by every session through an Arc. each table has its own latch and is latched only for
+// a single step, so no two latches are held at once. the isolation between the transactions
+// comes from the snapshots and the locks of the lock manager
+#[derive(Debug)]
 pub struct Database {
     pub id: usize,
     pub name: String,
-    pub tables: HashMap<String, TableInfo>,
-    pub real_tables: HashMap<String, MemoryTable>,
+    pub tables: RwLock<HashMap<String, TableInfo>>,
+    pub real_tables: RwLock<HashMap<String, Arc<RwLock<Table>>>>,
+    // the views share the names with the tables, the tables are latched first
+    pub views: RwLock<HashMap<String, ViewInfo>>,
+    // the queries of the tables which store the results of their queries
+    pub materialized_views: RwLock<HashMap<String, ViewInfo>>,
+    // where the files of the tables are put
+    pub data_dir: PathBuf,
+    pub transactions: Arc<Mutex<TransactionManager>>,
+    pub table_id_alloc: Mutex<Box<Allocator>>,
+    pub locks: LockManager,
+    // shared with the vacuum thread, which is woken up when enough commits are made
+    pub commits_since_vacuum: Arc<Mutex<usize>>,
+    pub vacuum_wanted: Arc<Condvar>,
 }
 
 impl Database {
-    pub fn add_table(&mut self, table_info: TableInfo) {
-        self.tables
-            .insert(table_info.name.clone(), table_info.clone());
-        self.real_tables.insert(
-            table_info.name.clone(),
-            MemoryTable::new(table_info.clone()),
-        );
+    pub fn new(id: usize, name: &str, data_dir: PathBuf, transactions: Arc<Mutex<TransactionManager>>) -> Database {
+        Database {
+            id: id,
+            name: name.to_string(),
+            tables: RwLock::new(HashMap::new()),
+            real_tables: RwLock::new(HashMap::new()),
+            views: RwLock::new(HashMap::new()),
+            materialized_views: RwLock::new(HashMap::new()),
+            data_dir: data_dir,
+            transactions: transactions,
+            table_id_alloc: Mutex::new(Allocator::new(1)),
+            locks: LockManager::new(),
+            commits_since_vacuum: Arc::new(Mutex::new(0)),
+            vacuum_wanted: Arc::new(Condvar::new()),
+        }
+    }
+
+    // the dead versions are reclaimed by a thread of the database, off the path of the commits.
+    // the thread ends once nobody uses the database anymore
+    pub fn start_vacuum(db: &Arc<Database>) {
+        let weak: Weak<Database> = Arc::downgrade(db);
+        let commits_since_vacuum: Arc<Mutex<usize>> = db.commits_since_vacuum.clone();
+        let vacuum_wanted: Arc<Condvar> = db.vacuum_wanted.clone();
+        thread::spawn(move || loop {
+            let is_vacuum_due: bool = {
+                let commits = commits_since_vacuum.lock().unwrap();
+                let (commits, _) = vacuum_wanted.wait_timeout(commits, VACUUM_IDLE_TIME).unwrap();
+                *commits >= VACUUM_INTERVAL
+            };
+            match weak.upgrade() {
+                None => return,
+                // a failed vacuum is left to the next one
+                Some(db) => if is_vacuum_due {
+                    let _ = db.vacuum();
+                },
+            }
+        });
+    }
+
+    // hands out the id for a new table
+    pub fn next_table_id(&self) -> usize {
+        let mut table_id_alloc = self.table_id_alloc.lock().unwrap();
+        let table_id: usize = table_id_alloc.base;
+        table_id_alloc.increment();
+        table_id
+    }
+
+    // the name is checked before the files of the table are created, they belong to the table
+    // of that name otherwise
+    pub fn add_table(&self, table_info: TableInfo, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        let table: Table = {
+            let mut tables = self.tables.write().unwrap();
+            if tables.contains_key(&table_info.name) || self.views.read().unwrap().contains_key(&table_info.name) {
+                return Err(DatabaseError::DuplicateTableError(table_info.name));
+            }
+            let table: Table = try!(Table::create(table_info.clone(), &self.data_dir));
+            tables.insert(table_info.name.clone(), table_info.clone());
+            table
+        };
+        self.real_tables
+            .write()
+            .unwrap()
+            .insert(table_info.name.clone(), Arc::new(RwLock::new(table)));
+        tx.undo_log.push(UndoEntry::CreateTable(table_info.name));
+        Ok(())
+    }
+
+    pub fn add_view(&self, view_info: ViewInfo, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        {
+            let tables = self.tables.read().unwrap();
+            let mut views = self.views.write().unwrap();
+            if tables.contains_key(&view_info.name) || views.contains_key(&view_info.name) {
+                return Err(DatabaseError::DuplicateTableError(view_info.name));
+            }
+            views.insert(view_info.name.clone(), view_info.clone());
+        }
+        tx.undo_log.push(UndoEntry::CreateView(view_info.name));
+        Ok(())
+    }
+
+    pub fn drop_view(&self, name: &str, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        if !self.views.read().unwrap().contains_key(name) {
+            return Err(DatabaseError::TableNotFoundError);
+        }
+        try!(self.validate_unread(name));
+
+        let view_info: ViewInfo = match self.views.write().unwrap().remove(name) {
+            None => return Err(DatabaseError::TableNotFoundError),
+            Some(view_info) => view_info,
+        };
+        tx.undo_log.push(UndoEntry::DropView(view_info));
+        Ok(())
+    }
+
+    // the result of the query is stored in a table of the memory engine, under the name of the view
+    pub fn add_materialized_view(
+        &self,
+        view_info: ViewInfo,
+        table_info: TableInfo,
+        rows: Vec<Vec<Field>>,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        try!(self.add_table(table_info, tx));
+        self.materialized_views
+            .write()
+            .unwrap()
+            .insert(view_info.name.clone(), view_info.clone());
+        tx.undo_log.push(UndoEntry::CreateMaterializedView(view_info.name.clone()));
+        try!(self.insert_rows(&view_info.name, rows, tx));
+        Ok(())
+    }
+
+    // replaces the stored rows with the new result. the table is locked exclusively,
+    // so two refreshes do not mix their rows
+    pub fn refresh_materialized_view(
+        &self,
+        name: &str,
+        rows: Vec<Vec<Field>>,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        if self.materialized_view(name).is_none() {
+            return Err(DatabaseError::TableNotFoundError);
+        }
+        try!(self.lock(tx, LockTarget::Table(name.to_string()), LockMode::Exclusive));
+
+        let record_ids: Vec<usize> = {
+            let table: Arc<RwLock<Table>> = try!(self.table(name));
+            let mem_tbl = table.read().unwrap();
+            try!(mem_tbl.rows(&tx.snapshot))
+                .into_iter()
+                .map(|(record_id, _)| record_id)
+                .collect()
+        };
+        try!(self.delete_rows(name, record_ids, tx));
+        try!(self.insert_rows(name, rows, tx));
+        Ok(())
+    }
+
+    pub fn drop_materialized_view(&self, name: &str, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        if self.materialized_view(name).is_none() {
+            return Err(DatabaseError::TableNotFoundError);
+        }
+        try!(self.validate_unread(name));
+        try!(self.lock(tx, LockTarget::Table(name.to_string()), LockMode::Exclusive));
+
+        let table_info: TableInfo = match self.tables.write().unwrap().remove(name) {
+            None => return Err(DatabaseError::TableNotFoundError),
+            Some(table_info) => table_info,
+        };
+        let table: Arc<RwLock<Table>> = match self.real_tables.write().unwrap().remove(name) {
+            None => return Err(DatabaseError::TableNotFoundError),
+            Some(table) => table,
+        };
+        tx.undo_log.push(UndoEntry::DropTable(table_info, table));
+        if let Some(view_info) = self.materialized_views.write().unwrap().remove(name) {
+            tx.undo_log.push(UndoEntry::DropMaterializedView(view_info));
+        }
+        Ok(())
+    }
+
+    pub fn materialized_view(&self, name: &str) -> Option<ViewInfo> {
+        self.materialized_views.read().unwrap().get(name).cloned()
+    }
+
+    // a view read by another view of this database has to stay, or the other could not be run
+    fn validate_unread(&self, name: &str) -> Result<(), DatabaseError> {
+        let is_reading = |view_info: &ViewInfo| {
+            view_info.query.source.tables().iter().any(|t| {
+                t.name == name && t.database_name.as_ref().is_none_or(|database_name| *database_name == self.name)
+            })
+        };
+        for views in [&self.views, &self.materialized_views].iter() {
+            if let Some(reader) = views.read().unwrap().values().find(|view_info| is_reading(view_info)) {
+                return Err(DatabaseError::ViewInUseError(reader.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn view(&self, name: &str) -> Option<ViewInfo> {
+        self.views.read().unwrap().get(name).cloned()
+    }
+
+    pub fn begin(&self) -> Transaction {
+        self.transactions.lock().unwrap().begin()
+    }
+
+    // the deletes are written to the files of the tables before the vacuum may reclaim the rows,
+    // the first write which fails is returned and the transaction is committed either way.
+    // the vacuum thread is woken up once in VACUUM_INTERVAL commits
+    pub fn commit(&self, tx: Transaction) -> Result<(), DatabaseError> {
+        let mut result: Result<(), DatabaseError> = Ok(());
+        for entry in tx.undo_log.iter() {
+            if let UndoEntry::Delete(ref table_name, record_id) = *entry {
+                // the table may be dropped later in the transaction
+                let committed: Result<(), DatabaseError> = match self.table(table_name) {
+                    Err(_) => Ok(()),
+                    Ok(table) => table.write().unwrap().commit_delete(record_id).map_err(DatabaseError::from),
+                };
+                if result.is_ok() {
+                    result = committed;
+                }
+            }
+        }
+        self.transactions.lock().unwrap().end(tx.txid());
+        self.locks.release_all(tx.txid());
+
+        let mut commits = self.commits_since_vacuum.lock().unwrap();
+        *commits += 1;
+        if *commits >= VACUUM_INTERVAL {
+            self.vacuum_wanted.notify_one();
+        }
+        result
+    }
+
+    // a transaction whose writes are not all undone is aborted, so that the rest stays hidden
+    pub fn rollback(&self, mut tx: Transaction) -> Result<(), DatabaseError> {
+        let result = self.undo(&mut tx, 0);
+        match result {
+            Ok(_) => self.transactions.lock().unwrap().end(tx.txid()),
+            Err(_) => self.transactions.lock().unwrap().abort(tx.txid(), self.id),
+        }
+        self.locks.release_all(tx.txid());
+        result
+    }
+
+    // a view of the committed rows for readers outside of any transaction
+    pub fn snapshot(&self) -> Snapshot {
+        self.transactions.lock().unwrap().snapshot()
+    }
+
+    // locks are taken before the latch of the table, so nobody waits for a lock with a latch held
+    fn lock(&self, tx: &Transaction, target: LockTarget, mode: LockMode) -> Result<(), DatabaseError> {
+        try!(self.locks.acquire(tx.txid(), target, mode, tx.lock_timeout));
+        Ok(())
+    }
+
+    // reverts the writes logged after the mark, the newest first. an entry which fails does not
+    // keep the older ones from being reverted, the first failure is returned
+    pub fn undo(&self, tx: &mut Transaction, mark: usize) -> Result<(), DatabaseError> {
+        let undo_log: Vec<UndoEntry> = tx.undo_log.split_off(mark);
+        let mut result: Result<(), DatabaseError> = Ok(());
+        for entry in undo_log.into_iter().rev() {
+            let undone: Result<(), DatabaseError> = self.undo_entry(entry);
+            if result.is_ok() {
+                result = undone;
+            }
+        }
+        result
+    }
+
+    fn undo_entry(&self, entry: UndoEntry) -> Result<(), DatabaseError> {
+        match entry {
+            UndoEntry::Insert(table_name, record_id) => {
+                let table: Arc<RwLock<Table>> = try!(self.table(&table_name));
+                try!(table.write().unwrap().undo_insert(record_id));
+            }
+            UndoEntry::Delete(table_name, record_id) => {
+                let table: Arc<RwLock<Table>> = try!(self.table(&table_name));
+                table.write().unwrap().undo_delete(record_id);
+            }
+            UndoEntry::CreateTable(table_name) => {
+                self.tables.write().unwrap().remove(&table_name);
+                if let Some(table) = self.real_tables.write().unwrap().remove(&table_name) {
+                    try!(table.read().unwrap().remove_files());
+                }
+            }
+            UndoEntry::CreateView(view_name) => {
+                self.views.write().unwrap().remove(&view_name);
+            }
+            UndoEntry::DropView(view_info) => {
+                self.views.write().unwrap().insert(view_info.name.clone(), view_info);
+            }
+            UndoEntry::DropTable(table_info, table) => {
+                self.tables.write().unwrap().insert(table_info.name.clone(), table_info.clone());
+                self.real_tables.write().unwrap().insert(table_info.name, table);
+            }
+            UndoEntry::CreateMaterializedView(view_name) => {
+                self.materialized_views.write().unwrap().remove(&view_name);
+            }
+            UndoEntry::DropMaterializedView(view_info) => {
+                self.materialized_views
+                    .write()
+                    .unwrap()
+                    .insert(view_info.name.clone(), view_info);
+            }
+            UndoEntry::CreateIndex(table_name, index_name) => {
+                let meta: TableInfo = {
+                    let table: Arc<RwLock<Table>> = try!(self.table(&table_name));
+                    let mut mem_tbl = table.write().unwrap();
+                    try!(mem_tbl.drop_index(&index_name));
+                    mem_tbl.meta.clone()
+                };
+                self.tables.write().unwrap().insert(table_name, meta);
+            }
+            UndoEntry::DropIndex(table_name, index_info) => {
+                let meta: TableInfo = {
+                    let table: Arc<RwLock<Table>> = try!(self.table(&table_name));
+                    let mut mem_tbl = table.write().unwrap();
+                    let column_names: Vec<&str> = index_info.columns.iter().map(|c| &c.name[..]).collect();
+                    try!(mem_tbl.create_index(
+                        &index_info.name,
+                        column_names,
+                        index_info.is_unique,
+                        index_info.index_type.clone()
+                    ));
+                    mem_tbl.meta.clone()
+                };
+                self.tables.write().unwrap().insert(table_name, meta);
+            }
+        }
+        Ok(())
+    }
+
+    // returns how many versions are reclaimed. once every table is vacuumed, nothing is left of
+    // the aborted transactions of the database
+    pub fn vacuum(&self) -> Result<usize, DatabaseError> {
+        *self.commits_since_vacuum.lock().unwrap() = 0;
+        let (horizon, aborted): (TxId, HashSet<TxId>) = {
+            let transactions = self.transactions.lock().unwrap();
+            (transactions.horizon(), transactions.aborted_in(self.id))
+        };
+        let tables: Vec<Arc<RwLock<Table>>> = self.real_tables.read().unwrap().values().cloned().collect();
+        let mut reclaimed: usize = 0;
+        for table in tables {
+            reclaimed += try!(table.write().unwrap().vacuum(horizon, &aborted));
+        }
+        self.transactions.lock().unwrap().forget_aborted(&aborted);
+        Ok(reclaimed)
     }
 
     pub fn table_info_from_str(&self, name: &str) -> Result<TableInfo, DatabaseError> {
-        match self.tables.get(name) {
+        match self.tables.read().unwrap().get(name) {
             None => Err(DatabaseError::TableNotFoundError),
             Some(tbl_info) => Ok(tbl_info.clone()),
         }
     }
 
-    pub fn load_table(&mut self, name: String) -> Result<&mut MemoryTable, DatabaseError> {
-        match self.real_tables.get_mut(&name) {
+    // the handle stays usable after the catalog lock is released
+    pub fn table(&self, name: &str) -> Result<Arc<RwLock<Table>>, DatabaseError> {
+        match self.real_tables.read().unwrap().get(name) {
             None => Err(DatabaseError::TableNotFoundError),
-            Some(mem_tbl) => Ok(mem_tbl),
+            Some(table) => Ok(table.clone()),
         }
     }
+
+    pub fn create_index(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        column_names: Vec<&str>,
+        is_unique: bool,
+        index_type: IndexType,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        try!(self.lock(tx, LockTarget::Table(table_name.to_string()), LockMode::Exclusive));
+        let meta: TableInfo = {
+            let table: Arc<RwLock<Table>> = try!(self.table(table_name));
+            let mut mem_tbl = table.write().unwrap();
+            try!(mem_tbl.create_index(index_name, column_names, is_unique, index_type));
+            mem_tbl.meta.clone()
+        };
+        self.tables.write().unwrap().insert(table_name.to_string(), meta);
+        tx.undo_log.push(UndoEntry::CreateIndex(table_name.to_string(), index_name.to_string()));
+        Ok(())
+    }
+
+    // the table may be omitted as long as the index name is not shared with another table
+    pub fn drop_index(
+        &self,
+        table_name: Option<&str>,
+        index_name: &str,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        let table_name: String = match table_name {
+            Some(table_name) => table_name.to_string(),
+            None => {
+                let owners: Vec<String> = self.tables
+                    .read()
+                    .unwrap()
+                    .values()
+                    .filter(|table_info| table_info.find_index_by_name(index_name).is_some())
+                    .map(|table_info| table_info.name.clone())
+                    .collect();
+                match owners.len() {
+                    0 => return Err(DatabaseError::from(TableError::IndexNotFoundError(index_name.to_string()))),
+                    1 => owners[0].clone(),
+                    _ => return Err(DatabaseError::AmbiguousIndexError(index_name.to_string())),
+                }
+            }
+        };
+
+        let index_info: IndexInfo = match try!(self.table_info_from_str(&table_name)).find_index_by_name(index_name) {
+            None => return Err(DatabaseError::from(TableError::IndexNotFoundError(index_name.to_string()))),
+            Some(index_info) => index_info.clone(),
+        };
+
+        // keys which identify rows for the table itself or for foreign keys have to stay
+        let is_referenced: bool = self.referencing_foreign_keys(&table_name).iter().any(|fkey| {
+            fkey.ref_columns.len() == index_info.columns.len()
+                && fkey.ref_columns.iter().zip(index_info.columns.iter()).all(|(a, b)| a.name == b.name)
+        });
+        if index_info.is_pk_index || is_referenced {
+            return Err(DatabaseError::IndexInUseError(index_name.to_string()));
+        }
+
+        try!(self.lock(tx, LockTarget::Table(table_name.clone()), LockMode::Exclusive));
+        let meta: TableInfo = {
+            let table: Arc<RwLock<Table>> = try!(self.table(&table_name));
+            let mut mem_tbl = table.write().unwrap();
+            try!(mem_tbl.drop_index(index_name));
+            mem_tbl.meta.clone()
+        };
+        self.tables.write().unwrap().insert(table_name.clone(), meta);
+        tx.undo_log.push(UndoEntry::DropIndex(table_name, index_info));
+        Ok(())
+    }
+
+    // a failing statement reverts its own writes, the rest of the transaction is kept
+    pub fn insert_rows(
+        &self,
+        name: &str,
+        rows: Vec<Vec<Field>>,
+        tx: &mut Transaction,
+    ) -> Result<Vec<usize>, DatabaseError> {
+        let table: Arc<RwLock<Table>> = try!(self.table(name));
+        try!(self.lock(tx, LockTarget::Table(name.to_string()), LockMode::Shared));
+        let meta: TableInfo = table.read().unwrap().meta.clone();
+        for fields in rows.iter() {
+            try!(self.validate_references(&meta, fields, &rows, tx));
+        }
+
+        let record_ids: Vec<usize> = try!(table.write().unwrap().insert_all(rows, &tx.snapshot));
+        for record_id in record_ids.iter() {
+            tx.undo_log.push(UndoEntry::Insert(name.to_string(), *record_id));
+        }
+        Ok(record_ids)
+    }
+
+    pub fn update_rows(
+        &self,
+        name: &str,
+        rows: Vec<(usize, Vec<Field>)>,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        let mark: usize = tx.undo_log.len();
+        let result = self.cascade_update(name, rows, tx);
+        if result.is_err() {
+            try!(self.undo(tx, mark));
+        }
+        result
+    }
+
+    pub fn delete_rows(&self, name: &str, record_ids: Vec<usize>, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        let mark: usize = tx.undo_log.len();
+        let result = self.cascade_delete(name, record_ids, tx);
+        if result.is_err() {
+            try!(self.undo(tx, mark));
+        }
+        result
+    }
+
+    fn cascade_update(
+        &self,
+        name: &str,
+        rows: Vec<(usize, Vec<Field>)>,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        let table: Arc<RwLock<Table>> = try!(self.table(name));
+        let meta: TableInfo = table.read().unwrap().meta.clone();
+        let new_rows: Vec<Vec<Field>> = rows.iter().map(|row| row.1.clone()).collect();
+        for fields in new_rows.iter() {
+            try!(self.validate_references(&meta, fields, &new_rows, tx));
+        }
+
+        try!(self.lock_records(tx, name, rows.iter().map(|row| row.0)));
+        let mut old_rows: Vec<Tuple> = Vec::new();
+        {
+            let mut mem_tbl = table.write().unwrap();
+            for &(record_id, _) in rows.iter() {
+                match try!(mem_tbl.get(record_id)) {
+                    None => return Err(DatabaseError::from(TableError::RecordNotFoundError(record_id))),
+                    Some(tuple) => old_rows.push(tuple),
+                }
+            }
+            let new_ids: Vec<usize> = try!(mem_tbl.update_all(rows.clone(), &tx.snapshot));
+            for (&(old_id, _), new_id) in rows.iter().zip(new_ids) {
+                tx.undo_log.push(UndoEntry::Delete(name.to_string(), old_id));
+                tx.undo_log.push(UndoEntry::Insert(name.to_string(), new_id));
+            }
+        }
+
+        for fkey in self.referencing_foreign_keys(name) {
+            for (old_tuple, (_, fields)) in old_rows.iter().zip(rows.iter()) {
+                let old_key: Vec<Field> = extract_key(&old_tuple.fields, &fkey.ref_columns);
+                let new_key: Vec<Field> = extract_key(fields, &fkey.ref_columns);
+                if old_key != new_key {
+                    try!(self.apply_action(&fkey, &fkey.on_update, old_key, Some(new_key), tx));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cascade_delete(&self, name: &str, record_ids: Vec<usize>, tx: &mut Transaction) -> Result<(), DatabaseError> {
+        let table: Arc<RwLock<Table>> = try!(self.table(name));
+        try!(self.lock_records(tx, name, record_ids.iter().cloned()));
+        let removed: Vec<(usize, Tuple)> = try!(table.write().unwrap().delete_all(&record_ids, &tx.snapshot));
+        for &(record_id, _) in removed.iter() {
+            tx.undo_log.push(UndoEntry::Delete(name.to_string(), record_id));
+        }
+
+        for fkey in self.referencing_foreign_keys(name) {
+            for (_, tuple) in removed.iter() {
+                let key: Vec<Field> = extract_key(&tuple.fields, &fkey.ref_columns);
+                try!(self.apply_action(&fkey, &fkey.on_delete, key, None, tx));
+            }
+        }
+        Ok(())
+    }
+
+    // writers share the lock of the table, so that it is not altered under them
+    fn lock_records<I: Iterator<Item = usize>>(&self, tx: &Transaction, name: &str, record_ids: I) -> Result<(), DatabaseError> {
+        try!(self.lock(tx, LockTarget::Table(name.to_string()), LockMode::Shared));
+        for record_id in record_ids {
+            try!(self.lock(tx, LockTarget::Record(name.to_string(), record_id), LockMode::Exclusive));
+        }
+        Ok(())
+    }
+
+    // applies the referential action to the child rows of a parent key which is updated or deleted
+    fn apply_action(
+        &self,
+        fkey: &ForeignKeyInfo,
+        action: &ReferentialAction,
+        old_key: Vec<Field>,
+        new_key: Option<Vec<Field>>,
+        tx: &mut Transaction,
+    ) -> Result<(), DatabaseError> {
+        // the parent row is locked by now, so the children are looked up in the latest committed
+        // state. a child committed after the snapshot was taken can not be rewritten by it
+        let mut latest: Snapshot = self.snapshot();
+        latest.txid = tx.txid();
+        let child: Arc<RwLock<Table>> = try!(self.table(&fkey.table_name));
+        let child_ids: Vec<usize> = try!(child.read().unwrap().find_records(&fkey.columns, &old_key, &latest));
+        if child_ids.is_empty() {
+            return Ok(());
+        }
+
+        if *action != ReferentialAction::Restrict {
+            let hidden: Option<usize> = {
+                let child = child.read().unwrap();
+                child_ids.iter().cloned().find(|record_id| !child.is_visible(*record_id, &tx.snapshot))
+            };
+            if let Some(record_id) = hidden {
+                return Err(DatabaseError::from(TableError::from(TransactionError::WriteConflictError(
+                    fkey.table_name.clone(),
+                    record_id,
+                ))));
+            }
+        }
+
+        let replacement: Vec<Field> = match (action, new_key) {
+            (&ReferentialAction::Restrict, _) => {
+                return Err(DatabaseError::ForeignKeyViolationError(
+                    fkey.name.clone(),
+                    old_key,
+                ))
+            }
+            (&ReferentialAction::Cascade, None) => {
+                return self.cascade_delete(&fkey.table_name, child_ids, tx)
+            }
+            (&ReferentialAction::Cascade, Some(new_key)) => new_key,
+            (&ReferentialAction::SetDefault, _) => {
+                let mut defaults: Vec<Field> = Vec::new();
+                for column in fkey.columns.iter() {
+                    match column.default {
+                        None => return Err(DatabaseError::ForeignKeyViolationError(
+                            fkey.name.clone(),
+                            old_key,
+                        )),
+                        Some(ref default) => defaults.push(default.clone()),
+                    }
+                }
+                defaults
+            }
+        };
+
+        let mut child_rows: Vec<(usize, Vec<Field>)> = Vec::new();
+        {
+            let child = child.read().unwrap();
+            for record_id in child_ids {
+                let mut fields: Vec<Field> = child.get_tuple(record_id).fields;
+                for (column, field) in fkey.columns.iter().zip(replacement.iter()) {
+                    fields[column.offset] = field.clone();
+                }
+                child_rows.push((record_id, fields));
+            }
+        }
+        self.cascade_update(&fkey.table_name, child_rows, tx)
+    }
+
+    // every row has to point an existing parent key, a self referencing table may point a row
+    // written by the same statement
+    fn validate_references(
+        &self,
+        meta: &TableInfo,
+        fields: &[Field],
+        written_rows: &[Vec<Field>],
+        tx: &Transaction,
+    ) -> Result<(), DatabaseError> {
+        for fkey in meta.foreign_keys.iter() {
+            let key: Vec<Field> = extract_key(fields, &fkey.columns);
+            let parent: Arc<RwLock<Table>> = try!(self.table(&fkey.ref_table_name));
+            let parent_ids: Vec<usize> = try!(parent.read().unwrap().find_records(&fkey.ref_columns, &key, &tx.snapshot));
+
+            // the parent row is kept from being deleted until the transaction ends,
+            // it may have been deleted by the one which held the lock
+            let mut found: bool = false;
+            for parent_id in parent_ids {
+                try!(self.lock(tx, LockTarget::Record(fkey.ref_table_name.clone(), parent_id), LockMode::Shared));
+                found = found || parent.read().unwrap().is_live(parent_id);
+            }
+
+            if !found && fkey.ref_table_name == meta.name {
+                found = written_rows
+                    .iter()
+                    .any(|row| extract_key(row, &fkey.ref_columns) == key);
+            }
+
+            if !found {
+                return Err(DatabaseError::ForeignKeyViolationError(
+                    fkey.name.clone(),
+                    key,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn referencing_foreign_keys(&self, name: &str) -> Vec<ForeignKeyInfo> {
+        let mut foreign_keys: Vec<ForeignKeyInfo> = Vec::new();
+        for table_info in self.tables.read().unwrap().values() {
+            for fkey in table_info.foreign_keys.iter() {
+                if fkey.ref_table_name == name {
+                    foreign_keys.push(fkey.clone());
+                }
+            }
+        }
+        foreign_keys
+    }
+}
+
+fn extract_key(fields: &[Field], columns: &[ColumnInfo]) -> Vec<Field> {
+    columns
+        .iter()
+        .map(|column| fields[column.offset].clone())
+        .collect()
 }
 
 #[derive(Debug, PartialEq)]
 pub enum DatabaseError {
-    MemoryTableError(MemoryTableError),
+    TableError(TableError),
+    LockError(LockError),
     TableNotFoundError,
+    DuplicateTableError(String),
+    ForeignKeyViolationError(String, Vec<Field>),
+    AmbiguousIndexError(String),
+    IndexInUseError(String),
+    ViewInUseError(String),
+}
+
+impl From<LockError> for DatabaseError {
+    fn from(err: LockError) -> DatabaseError {
+        DatabaseError::LockError(err)
+    }
 }
 
-impl From<MemoryTableError> for DatabaseError {
-    fn from(err: MemoryTableError) -> DatabaseError {
-        DatabaseError::MemoryTableError(err)
+impl From<TableError> for DatabaseError {
+    fn from(err: TableError) -> DatabaseError {
+        DatabaseError::TableError(err)
     }
 }
 
@@ -57,7 +57,7 @@ where
 
     fn upsert(&mut self, keys: Vec<Field>, tuple: Tuple) {
         if !self.grouped_aggregators.contains_key(&keys) {
-            let init_aggrs: Vec<Aggregator> = self.aggregators.iter().map(|a| a.clone()).collect();
+            let init_aggrs: Vec<Aggregator> = self.aggregators.to_vec();
             self.grouped_aggregators
                 .insert(keys.clone(), init_aggrs.clone());
         }
@@ -208,6 +208,7 @@ mod tests {
 
     fn gen_target() -> Target {
         Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "price".to_owned(),
         }
@@ -221,7 +222,7 @@ mod tests {
         ];
 
         column_defs
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(i, col)| Column {
                 table_name: "shohin".to_owned(),
@@ -306,6 +307,7 @@ mod tests {
     #[test]
     fn test_update_str_count() {
         let target: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "shohin_name".to_owned(),
         };
@@ -383,6 +385,7 @@ mod tests {
     #[test]
     fn test_update_str_sum() {
         let target: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "shohin_name".to_owned(),
         };
@@ -394,10 +397,7 @@ mod tests {
             iterate_num: 0,
         };
         assert_eq!(sum.fetch_result(), Field::set_i64(0));
-        assert_eq!(
-            sum.update(&gen_int_tuple(), &gen_columns()).is_err(),
-            true
-        );
+        assert!(sum.update(&gen_int_tuple(), &gen_columns()).is_err());
     }
 
     #[test]
@@ -458,6 +458,7 @@ mod tests {
     #[test]
     fn test_update_str_avg() {
         let target: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "shohin_name".to_owned(),
         };
@@ -469,10 +470,7 @@ mod tests {
             iterate_num: 0,
         };
         assert_eq!(avg.fetch_result(), Field::set_i64(0));
-        assert_eq!(
-            avg.update(&gen_int_tuple(), &gen_columns()).is_err(),
-            true
-        );
+        assert!(avg.update(&gen_int_tuple(), &gen_columns()).is_err());
     }
 
     #[test]
@@ -543,6 +541,7 @@ mod tests {
     #[test]
     fn test_update_str_max() {
         let target: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "shohin_name".to_owned(),
         };
@@ -554,10 +553,7 @@ mod tests {
             iterate_num: 0,
         };
         assert_eq!(max.fetch_result(), Field::set_i64(0));
-        assert_eq!(
-            max.update(&gen_int_tuple(), &gen_columns()).is_err(),
-            true
-        );
+        assert!(max.update(&gen_int_tuple(), &gen_columns()).is_err());
     }
 
     #[test]
@@ -638,6 +634,7 @@ mod tests {
     #[test]
     fn test_update_str_min() {
         let target: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "shohin_name".to_owned(),
         };
@@ -649,9 +646,6 @@ mod tests {
             iterate_num: 0,
         };
         assert_eq!(min.fetch_result(), Field::set_i64(0));
-        assert_eq!(
-            min.update(&gen_int_tuple(), &gen_columns()).is_err(),
-            true
-        );
+        assert!(min.update(&gen_int_tuple(), &gen_columns()).is_err());
     }
 }
@@ -0,0 +1,77 @@
+use std::sync::{Arc, RwLock};
+
+use ScanIterator;
+use meta::table_info::TableInfo;
+use columns::column::Column;
+use columns::range::KeyRange;
+use tables::tuple::Tuple;
+use tables::table::Table;
+use tables::index::IndexError;
+use transaction::Snapshot;
+
+#[derive(Debug)]
+pub struct IndexScanExec {
+    pub table: Arc<RwLock<Table>>,
+    pub record_ids: Vec<usize>,
+    pub cursor: usize,
+    pub columns: Vec<Column>,
+    pub meta: TableInfo,
+}
+
+impl IndexScanExec {
+    // record ids are looked up front, so the scan yields tuples in the order of the index keys.
+    // the index has every version, only the ones the snapshot sees are kept
+    pub fn new(
+        table: Arc<RwLock<Table>>,
+        index_name: &str,
+        range: &KeyRange,
+        snapshot: &Snapshot,
+    ) -> Result<IndexScanExec, IndexError> {
+        let (columns, meta, record_ids) = {
+            let mem_tbl = table.read().unwrap();
+            let columns: Vec<Column> = mem_tbl.columns.to_vec();
+            let record_ids: Vec<usize> = match mem_tbl.indices.iter().find(|index| index.meta.name == index_name) {
+                None => Vec::new(),
+                Some(index) => try!(index.scan(range))
+                    .into_iter()
+                    .filter(|record_id| mem_tbl.is_visible(*record_id, snapshot))
+                    .collect(),
+            };
+            (columns, mem_tbl.meta.clone(), record_ids)
+        };
+
+        Ok(IndexScanExec {
+            table: table,
+            record_ids: record_ids,
+            cursor: 0,
+            columns: columns,
+            meta: meta,
+        })
+    }
+}
+
+impl ScanIterator for IndexScanExec {
+    fn get_meta(&self) -> TableInfo {
+        self.meta.clone()
+    }
+
+    fn get_columns(&self) -> Vec<Column> {
+        self.columns.clone()
+    }
+}
+
+impl Iterator for IndexScanExec {
+    type Item = Tuple;
+    fn next(&mut self) -> Option<Tuple> {
+        if self.cursor >= self.record_ids.len() {
+            return None;
+        }
+
+        let record_id: usize = self.record_ids[self.cursor];
+        self.cursor += 1;
+        Some(self.table.read().unwrap().get_tuple(record_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -1,10 +1,15 @@
+use std::sync::{Arc, RwLock};
+
 use ScanIterator;
 use {eval_selectors, Selectors};
 use meta::table_info::{TableInfo, TableInfoError};
 use meta::column_info::ColumnInfo;
 use columns::column::Column;
 use tables::tuple::Tuple;
+use tables::field::Field;
+use tables::table::{Table, TableError};
 use allocators::allocator::Allocator;
+use transaction::Snapshot;
 
 use parser::statement::*;
 use executors::selector::*;
@@ -14,7 +19,7 @@ pub struct NestedLoopJoinExec<'n> {
     pub cursor: usize,
     pub outer_columns: Vec<Column>,
     pub inner_columns: Vec<Column>,
-    pub next_tuple: Box<FnMut() -> Option<Tuple> + 'n>,
+    pub next_tuple: Box<dyn FnMut() -> Option<Tuple> + 'n>,
     pub selectors: Option<Selectors>,
     pub meta: TableInfo,
 }
@@ -24,34 +29,84 @@ impl<'n> NestedLoopJoinExec<'n> {
         outer_table: T1,
         inner_table: T2,
         condition: Option<Conditions>,
-    ) -> NestedLoopJoinExec<'n> {
-        let outer_column_length: usize = outer_table.get_meta().columns.len();
-        let mut column_infos: Vec<ColumnInfo> = outer_table.get_meta().columns;
-        for (i, column) in inner_table.get_meta().columns.iter().enumerate() {
-            let col: ColumnInfo = column.clone();
-            column_infos.push(ColumnInfo {
-                name: col.name,
-                dtype: col.dtype,
-                offset: outer_column_length + i,
-            });
-        }
-        let meta: TableInfo = TableInfo {
-            id: 0,
-            name: "".to_owned(),
-            columns: column_infos,
-            indices: Vec::new(),
-            next_record_id: Allocator::new(1),
+    ) -> Result<NestedLoopJoinExec<'n>, SelectorError> {
+        let meta: TableInfo = join_meta(&outer_table.get_meta(), &inner_table.get_meta());
+        let selectors: Option<Selectors> = match condition {
+            Some(c) => Some(try!(build_selectors(c))),
+            None => None,
         };
-
-        let selectors: Option<Selectors> = condition.and_then(|c| build_selectors(c).ok());
-        NestedLoopJoinExec {
+        Ok(NestedLoopJoinExec {
             cursor: 0,
             outer_columns: outer_table.get_columns(),
             inner_columns: inner_table.get_columns(),
             next_tuple: next_tuple(outer_table, inner_table),
             selectors: selectors,
             meta: meta,
-        }
+        })
+    }
+
+    // looks the inner tuples up through the index by the outer columns at the offsets,
+    // instead of pairing every outer tuple with every inner one.
+    // the lookups are done here, so that a failed one is returned instead of ending the join
+    pub fn with_index<T: ScanIterator + 'n>(
+        outer_table: T,
+        inner_table: Arc<RwLock<Table>>,
+        index_name: String,
+        outer_offsets: Vec<usize>,
+        condition: Option<Conditions>,
+        snapshot: Snapshot,
+    ) -> Result<NestedLoopJoinExec<'n>, JoinExecError> {
+        let (inner_meta, inner_columns) = {
+            let mem_tbl = inner_table.read().unwrap();
+            (mem_tbl.meta.clone(), mem_tbl.columns.clone())
+        };
+        let meta: TableInfo = join_meta(&outer_table.get_meta(), &inner_meta);
+        let selectors: Option<Selectors> = match condition {
+            Some(c) => Some(try!(build_selectors(c))),
+            None => None,
+        };
+        let outer_columns: Vec<Column> = outer_table.get_columns();
+        let joined_tuples: Vec<Tuple> = try!(index_join_tuples(
+            outer_table,
+            &inner_table,
+            &index_name,
+            &outer_offsets,
+            &snapshot
+        ));
+        let mut joined_tuples = joined_tuples.into_iter();
+        Ok(NestedLoopJoinExec {
+            cursor: 0,
+            outer_columns: outer_columns,
+            inner_columns: inner_columns,
+            next_tuple: Box::new(move || joined_tuples.next()),
+            selectors: selectors,
+            meta: meta,
+        })
+    }
+}
+
+fn join_meta(outer_meta: &TableInfo, inner_meta: &TableInfo) -> TableInfo {
+    let outer_column_length: usize = outer_meta.columns.len();
+    let mut column_infos: Vec<ColumnInfo> = outer_meta.columns.clone();
+    for (i, column) in inner_meta.columns.iter().enumerate() {
+        let col: ColumnInfo = column.clone();
+        column_infos.push(ColumnInfo {
+            name: col.name,
+            dtype: col.dtype,
+            offset: outer_column_length + i,
+            default: col.default,
+        });
+    }
+
+    TableInfo {
+        id: 0,
+        name: "".to_owned(),
+        columns: column_infos,
+        indices: Vec::new(),
+        foreign_keys: Vec::new(),
+        checks: Vec::new(),
+        engine: Engine::Memory,
+        next_record_id: Allocator::new(1),
     }
 }
 
@@ -82,43 +137,93 @@ impl<'n> ScanIterator for NestedLoopJoinExec<'n> {
 impl<'n> Iterator for NestedLoopJoinExec<'n> {
     type Item = Tuple;
     fn next(&mut self) -> Option<Tuple> {
-        (self.next_tuple)().and_then(|tuple| {
+        // a joined tuple which does not satisfy the condition is skipped, not the end of the join
+        while let Some(tuple) = (self.next_tuple)() {
             let passed: bool = match self.selectors.clone() {
                 None => true,
                 Some(s) => eval_selectors(s, &tuple, &self.get_columns()),
             };
 
             if passed {
-                Some(tuple)
-            } else {
-                None
+                return Some(tuple);
             }
-        })
+        }
+        None
     }
 }
 
+// the inner side is read once and kept, then every outer tuple is paired with each of its tuples
 fn next_tuple<'n, T1: ScanIterator + 'n, T2: ScanIterator + 'n>(
     mut outer_table: T1,
-    mut inner_table: T2,
-) -> Box<FnMut() -> Option<Tuple> + 'n> {
+    inner_table: T2,
+) -> Box<dyn FnMut() -> Option<Tuple> + 'n> {
+    let mut inner_table: Option<T2> = Some(inner_table);
+    let mut inner_tuples: Vec<Tuple> = Vec::new();
+    let mut outer_tuple: Option<Tuple> = None;
+    let mut cursor: usize = 0;
     Box::new(move || loop {
-        match outer_table.next() {
-            None => return None,
-            Some(ref outer_tuple) => match inner_table.next() {
-                None => continue,
-                Some(ref inner_tuple) => {
-                    let joined_tuple: Tuple = outer_tuple.append(inner_tuple);
-                    return Some(joined_tuple);
-                }
-            },
+        if let Some(inner_table) = inner_table.take() {
+            inner_tuples = inner_table.collect();
+        }
+
+        if outer_tuple.is_none() || cursor >= inner_tuples.len() {
+            outer_tuple = outer_table.next();
+            cursor = 0;
+            if outer_tuple.is_some() && inner_tuples.is_empty() {
+                continue;
+            }
         }
+
+        cursor += 1;
+        return outer_tuple
+            .as_ref()
+            .map(|outer_tuple| outer_tuple.append(&inner_tuples[cursor - 1]));
     })
 }
 
+fn index_join_tuples<T: ScanIterator>(
+    outer_table: T,
+    inner_table: &Arc<RwLock<Table>>,
+    index_name: &str,
+    outer_offsets: &[usize],
+    snapshot: &Snapshot,
+) -> Result<Vec<Tuple>, JoinExecError> {
+    let mut joined_tuples: Vec<Tuple> = Vec::new();
+    for outer_tuple in outer_table {
+        // the inner table is locked for one outer tuple at a time
+        let inner_table = inner_table.read().unwrap();
+        let index = match inner_table.indices.iter().find(|index| index.meta.name == index_name) {
+            None => return Err(JoinExecError::from(TableError::IndexNotFoundError(index_name.to_string()))),
+            Some(index) => index,
+        };
+
+        // keys are stored in the types of the inner columns, a value of no such type matches nothing
+        let key: Option<Vec<Field>> = index
+            .meta
+            .columns
+            .iter()
+            .zip(outer_offsets.iter())
+            .map(|(column, offset)| column.dtype.coerce(&outer_tuple.fields[*offset]))
+            .collect();
+        let record_ids: Vec<usize> = match key {
+            None => continue,
+            Some(key) => try!(index.lookup(&key).map_err(TableError::from)),
+        };
+
+        for record_id in record_ids {
+            if inner_table.is_visible(record_id, snapshot) {
+                joined_tuples.push(outer_tuple.append(&inner_table.get_tuple(record_id)));
+            }
+        }
+    }
+    Ok(joined_tuples)
+}
+
 #[derive(Debug, PartialEq)]
 pub enum JoinExecError {
     TableInfoError(TableInfoError),
     SelectorError(SelectorError),
+    TableError(TableError),
 }
 
 impl From<TableInfoError> for JoinExecError {
@@ -133,5 +238,11 @@ impl From<SelectorError> for JoinExecError {
     }
 }
 
+impl From<TableError> for JoinExecError {
+    fn from(err: TableError) -> JoinExecError {
+        JoinExecError::TableError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {}
@@ -1,9 +1,12 @@
 pub mod scan_iterator;
 pub mod table_scan;
-pub mod memory_table_scan;
+pub mod index_scan;
+pub mod tuple_scan;
 pub mod join;
 pub mod selection;
 pub mod selector;
 pub mod projection;
 pub mod aggregation;
 pub mod aggregator;
+pub mod set_operation;
+pub mod recursive_union;
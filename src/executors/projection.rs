@@ -52,8 +52,8 @@ where
                 Some(tuple) => {
                     let mut fields: Vec<Field> = Vec::new();
                     for target in &self.projectors {
-                        match target {
-                            &Projectable::Target(ref t) => for column in &self.inputs.get_columns()
+                        match *target {
+                            Projectable::Target(ref t) => for column in &self.inputs.get_columns()
                             {
                                 let t: Target = t.clone();
                                 if t.table_name.is_some() {
@@ -66,11 +66,13 @@ where
                                     fields.push(tuple.fields[column.offset].clone());
                                 }
                             },
-                            &Projectable::Lit(ref l) => fields.push(l.clone().into()),
-                            &Projectable::All => {
+                            Projectable::Lit(ref l) => fields.push(l.clone().into()),
+                            Projectable::All => {
                                 fields = tuple.fields.clone();
                             }
-                            &Projectable::Aggregate(ref _a) => (),
+                            Projectable::Aggregate(ref _a) => (),
+                            // rewritten into a column before the projection is built
+                            Projectable::SubQuery(ref _q) => (),
                         }
                     }
                     return Some(Tuple::new(fields));
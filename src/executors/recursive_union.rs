@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+use ScanIterator;
+use meta::table_info::TableInfo;
+use columns::column::Column;
+use tables::tuple::Tuple;
+use tables::field::Field;
+
+// iterates a recursive query over a working table. the tuples of the anchor are yielded first,
+// then the step is run over the tuples it yielded last, until it yields no new tuples.
+// the error of a failed step is kept, and ends the iteration
+pub struct RecursiveUnionExec<F, E> {
+    meta: TableInfo,
+    step: F,
+    // UNION drops the tuples yielded before, UNION ALL keeps them
+    is_distinct: bool,
+    working: Vec<Tuple>,
+    cursor: usize,
+    returned: HashSet<Vec<Field>>,
+    pub error: Option<E>,
+}
+
+impl<F, E> RecursiveUnionExec<F, E>
+where
+    F: FnMut(&[Tuple]) -> Result<Vec<Tuple>, E>,
+{
+    pub fn new(meta: TableInfo, anchor: Vec<Tuple>, is_distinct: bool, step: F) -> RecursiveUnionExec<F, E> {
+        let mut exec: RecursiveUnionExec<F, E> = RecursiveUnionExec {
+            meta: meta,
+            step: step,
+            is_distinct: is_distinct,
+            working: Vec::new(),
+            cursor: 0,
+            returned: HashSet::new(),
+            error: None,
+        };
+        exec.working = exec.new_tuples(anchor);
+        exec
+    }
+
+    fn new_tuples(&mut self, tuples: Vec<Tuple>) -> Vec<Tuple> {
+        if !self.is_distinct {
+            return tuples;
+        }
+        let returned: &mut HashSet<Vec<Field>> = &mut self.returned;
+        tuples
+            .into_iter()
+            .filter(|tuple| returned.insert(tuple.fields.clone()))
+            .collect()
+    }
+}
+
+impl<F, E> ScanIterator for RecursiveUnionExec<F, E>
+where
+    F: FnMut(&[Tuple]) -> Result<Vec<Tuple>, E>,
+{
+    fn get_meta(&self) -> TableInfo {
+        self.meta.clone()
+    }
+
+    fn get_columns(&self) -> Vec<Column> {
+        self.meta.columns.iter().map(|c| c.to_column(&self.meta.name)).collect()
+    }
+}
+
+impl<F, E> Iterator for RecursiveUnionExec<F, E>
+where
+    F: FnMut(&[Tuple]) -> Result<Vec<Tuple>, E>,
+{
+    type Item = Tuple;
+    fn next(&mut self) -> Option<Tuple> {
+        loop {
+            if self.cursor < self.working.len() {
+                self.cursor += 1;
+                return Some(self.working[self.cursor - 1].clone());
+            }
+
+            if self.working.is_empty() || self.error.is_some() {
+                return None;
+            }
+
+            match (self.step)(&self.working) {
+                Err(err) => {
+                    self.error = Some(err);
+                    return None;
+                }
+                Ok(tuples) => {
+                    self.working = self.new_tuples(tuples);
+                    self.cursor = 0;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterates_until_no_new_tuples() {
+        let meta: TableInfo = TableInfo::generated("nums", Vec::new());
+        let step = |working: &[Tuple]| -> Result<Vec<Tuple>, ()> {
+            Ok(working
+                .iter()
+                .map(|tuple| tuple.fields[0].get_i64())
+                .filter(|n| *n < 3)
+                .map(|n| Tuple::new(vec![Field::set_i64(n + 1)]))
+                .collect())
+        };
+        let exec = RecursiveUnionExec::new(meta.clone(), vec![Tuple::new(vec![Field::set_i64(0)])], false, step);
+        let nums: Vec<i64> = exec.map(|tuple| tuple.fields[0].get_i64()).collect();
+        assert_eq!(nums, vec![0, 1, 2, 3]);
+
+        // a cycle ends once every tuple was yielded
+        let step = |working: &[Tuple]| -> Result<Vec<Tuple>, ()> {
+            Ok(working
+                .iter()
+                .map(|tuple| Tuple::new(vec![Field::set_i64((tuple.fields[0].get_i64() + 1) % 2)]))
+                .collect())
+        };
+        let exec = RecursiveUnionExec::new(meta, vec![Tuple::new(vec![Field::set_i64(0)])], true, step);
+        assert_eq!(exec.count(), 2);
+    }
+}
@@ -7,5 +7,16 @@ pub trait ScanIterator: Iterator<Item = Tuple> {
     fn get_columns(&self) -> Vec<Column>;
 }
 
+// a scan chosen at run time, over a stored table or over generated tuples
+impl<'a> ScanIterator for Box<dyn ScanIterator + 'a> {
+    fn get_meta(&self) -> TableInfo {
+        (**self).get_meta()
+    }
+
+    fn get_columns(&self) -> Vec<Column> {
+        (**self).get_columns()
+    }
+}
+
 #[cfg(test)]
 mod tests {}
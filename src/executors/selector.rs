@@ -1,13 +1,16 @@
+use std::cmp::Ordering;
+
 use columns::column::Column;
 use tables::tuple::Tuple;
-use tables::field::Field;
+use tables::field::{Field, KIND_STR};
 use parser::statement::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Selectors {
-    Leaf(Selector),
+    Leaf(Box<Selector>),
     And(Box<Selectors>, Box<Selectors>),
     Or(Box<Selectors>, Box<Selectors>),
+    Not(Box<Selectors>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +20,8 @@ pub struct Selector {
     pub left_column: String,
     pub right_hand: Option<Target>,
     pub scholar: Option<Field>,
+    // the values of IN, or the bounds of BETWEEN
+    pub list: Vec<Field>,
 }
 
 impl Selector {
@@ -28,9 +33,63 @@ impl Selector {
             Operator::LE => self.eval_le(tuple, columns).unwrap_or(false),
             Operator::GT => self.eval_gt(tuple, columns).unwrap_or(false),
             Operator::LT => self.eval_lt(tuple, columns).unwrap_or(false),
+            Operator::In => self.eval_in(tuple, columns).unwrap_or(false),
+            Operator::Between => self.eval_between(tuple, columns).unwrap_or(false),
+            Operator::Like => self.eval_like(tuple, columns, false).unwrap_or(false),
+            Operator::ILike => self.eval_like(tuple, columns, true).unwrap_or(false),
         }
     }
 
+    fn eval_in(&self, tuple: &Tuple, columns: &[Column]) -> Result<bool, SelectorError> {
+        let left_side: Field = try!(find_field(
+            tuple,
+            columns,
+            self.left_table.clone(),
+            self.left_column.clone()
+        ));
+        Ok(self.list.iter().any(|right_side| left_side.compare(right_side) == Some(Ordering::Equal)))
+    }
+
+    fn eval_between(&self, tuple: &Tuple, columns: &[Column]) -> Result<bool, SelectorError> {
+        let (low, high): (&Field, &Field) = match (self.list.first(), self.list.get(1)) {
+            (Some(low), Some(high)) => (low, high),
+            _ => return Err(SelectorError::UnexpectedRightHandError),
+        };
+        let left_side: Field = try!(find_field(
+            tuple,
+            columns,
+            self.left_table.clone(),
+            self.left_column.clone()
+        ));
+        Ok(left_side.compare(low).is_some_and(|o| o != Ordering::Less)
+            && left_side.compare(high).is_some_and(|o| o != Ordering::Greater))
+    }
+
+    fn eval_like(&self, tuple: &Tuple, columns: &[Column], ignore_case: bool) -> Result<bool, SelectorError> {
+        let pattern: String = match self.scholar {
+            Some(ref pattern) if pattern.kind == KIND_STR => pattern.get_str(),
+            _ => return Err(SelectorError::UnexpectedRightHandError),
+        };
+        let left_side: Field = try!(find_field(
+            tuple,
+            columns,
+            self.left_table.clone(),
+            self.left_column.clone()
+        ));
+        if left_side.kind != KIND_STR {
+            return Ok(false);
+        }
+
+        let (text, pattern): (String, String) = if ignore_case {
+            (left_side.get_str().to_lowercase(), pattern.to_lowercase())
+        } else {
+            (left_side.get_str(), pattern)
+        };
+        let text: Vec<char> = text.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        Ok(like(&text, &pattern))
+    }
+
     fn eval_equ(&self, tuple: &Tuple, columns: &[Column]) -> Result<bool, SelectorError> {
         match self.right_hand {
             None => {}
@@ -47,7 +106,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side == right_side);
+                return Ok(left_side.compare(right_side) == Some(Ordering::Equal));
             }
         }
 
@@ -60,7 +119,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side == right_side)
+                Ok(left_side.compare(right_side) == Some(Ordering::Equal))
             }
         }
     }
@@ -81,7 +140,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side != right_side);
+                return Ok(left_side.compare(right_side) != Some(Ordering::Equal));
             }
         }
 
@@ -94,7 +153,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side != right_side)
+                Ok(left_side.compare(right_side) != Some(Ordering::Equal))
             }
         }
     }
@@ -115,7 +174,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side >= right_side);
+                return Ok(left_side.compare(right_side).is_some_and(|o| o != Ordering::Less));
             }
         }
 
@@ -128,7 +187,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side >= right_side)
+                Ok(left_side.compare(right_side).is_some_and(|o| o != Ordering::Less))
             }
         }
     }
@@ -149,7 +208,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side <= right_side);
+                return Ok(left_side.compare(right_side).is_some_and(|o| o != Ordering::Greater));
             }
         }
 
@@ -162,7 +221,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side <= right_side)
+                Ok(left_side.compare(right_side).is_some_and(|o| o != Ordering::Greater))
             }
         }
     }
@@ -183,7 +242,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side > right_side);
+                return Ok(left_side.compare(right_side) == Some(Ordering::Greater));
             }
         }
 
@@ -196,7 +255,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side > right_side)
+                Ok(left_side.compare(right_side) == Some(Ordering::Greater))
             }
         }
     }
@@ -217,7 +276,7 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                return Ok(left_side < right_side);
+                return Ok(left_side.compare(right_side) == Some(Ordering::Less));
             }
         }
 
@@ -230,13 +289,13 @@ impl Selector {
                     self.left_table.clone(),
                     self.left_column.clone()
                 ));
-                Ok(left_side < right_side)
+                Ok(left_side.compare(right_side) == Some(Ordering::Less))
             }
         }
     }
 }
 
-fn find_field(
+pub fn find_field(
     tuple: &Tuple,
     columns: &[Column],
     table_name: Option<String>,
@@ -259,6 +318,34 @@ fn find_field(
     Err(SelectorError::ColumnNotFoundError)
 }
 
+// % matches any characters, and _ matches a single one. on a mismatch the last % takes one more
+// character and the matching goes on after it. the earlier ones never need to take more, since
+// the last one can take those characters as well, so no more than text * pattern steps are taken
+fn like(text: &[char], pattern: &[char]) -> bool {
+    let (mut t, mut p): (usize, usize) = (0, 0);
+    // the position of the last % in the pattern, and of the text it was matched from
+    let mut last_percent: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '%' {
+            last_percent = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '_' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else {
+            match last_percent {
+                None => return false,
+                Some((percent, matched)) => {
+                    last_percent = Some((percent, matched + 1));
+                    t = matched + 1;
+                    p = percent + 1;
+                }
+            }
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
 pub fn build_selectors(condition: Conditions) -> Result<Selectors, SelectorError> {
     match condition {
         Conditions::And(c1, c2) => {
@@ -273,36 +360,55 @@ pub fn build_selectors(condition: Conditions) -> Result<Selectors, SelectorError
             Ok(Selectors::Or(Box::new(selectors1), Box::new(selectors2)))
         }
 
+        Conditions::Not(c) => Ok(Selectors::Not(Box::new(try!(build_selectors(*c))))),
+
+        // the subqueries are run by the client, which evaluates these conditions itself
+        Conditions::In(_, _) | Conditions::Exists(_) => Err(SelectorError::SubQueryError),
+
         Conditions::Leaf(condition) => {
             let kind: Operator = condition.op;
             match condition.right {
-                Comparable::Lit(l) => Ok(Selectors::Leaf(Selector {
+                Comparable::Lit(l) => Ok(Selectors::Leaf(Box::new(Selector {
                     kind: kind,
                     left_table: condition.left.table_name,
                     left_column: condition.left.name,
                     right_hand: None,
                     scholar: Some(l.into()),
-                })),
-                Comparable::Target(t) => Ok(Selectors::Leaf(Selector {
+                    list: Vec::new(),
+                }))),
+                Comparable::Target(t) => Ok(Selectors::Leaf(Box::new(Selector {
                     kind: kind,
                     left_table: condition.left.table_name,
                     left_column: condition.left.name,
                     right_hand: Some(t),
                     scholar: None,
-                })),
+                    list: Vec::new(),
+                }))),
+                Comparable::List(lits) => Ok(Selectors::Leaf(Box::new(Selector {
+                    kind: kind,
+                    left_table: condition.left.table_name,
+                    left_column: condition.left.name,
+                    right_hand: None,
+                    scholar: None,
+                    list: lits.into_iter().map(|l| l.into()).collect(),
+                }))),
+                Comparable::SubQuery(_) => Err(SelectorError::SubQueryError),
             }
         }
     }
 }
 
 pub fn eval_selectors(selectors: Selectors, tuple: &Tuple, columns: &[Column]) -> bool {
-    match selectors {
-        Selectors::Leaf(c) => c.eval(tuple, columns),
-        Selectors::And(c1, c2) => {
-            eval_selectors(*c1, tuple, columns) && eval_selectors(*c2, tuple, columns)
-        }
-        Selectors::Or(c1, c2) => {
-            eval_selectors(*c1, tuple, columns) || eval_selectors(*c2, tuple, columns)
+    selectors.eval(tuple, columns)
+}
+
+impl Selectors {
+    pub fn eval(&self, tuple: &Tuple, columns: &[Column]) -> bool {
+        match *self {
+            Selectors::Leaf(ref c) => c.eval(tuple, columns),
+            Selectors::And(ref c1, ref c2) => c1.eval(tuple, columns) && c2.eval(tuple, columns),
+            Selectors::Or(ref c1, ref c2) => c1.eval(tuple, columns) || c2.eval(tuple, columns),
+            Selectors::Not(ref c) => !c.eval(tuple, columns),
         }
     }
 }
@@ -311,6 +417,7 @@ pub fn eval_selectors(selectors: Selectors, tuple: &Tuple, columns: &[Column]) -
 pub enum SelectorError {
     ColumnNotFoundError,
     UnexpectedRightHandError,
+    SubQueryError,
 }
 
 #[cfg(test)]
@@ -329,7 +436,7 @@ mod tests {
         ];
 
         column_defs
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(i, col)| Column {
                 table_name: "shohin".to_owned(),
@@ -342,6 +449,7 @@ mod tests {
 
     fn gen_left_column() -> Target {
         Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "price".to_owned(),
         }
@@ -349,11 +457,39 @@ mod tests {
 
     fn gen_right_column() -> Target {
         Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "prev_price".to_owned(),
         }
     }
 
+    #[test]
+    fn test_like() {
+        let matches = |text: &str, pattern: &str| {
+            like(&text.chars().collect::<Vec<char>>(), &pattern.chars().collect::<Vec<char>>())
+        };
+        assert!(matches("apple", "apple"));
+        assert!(matches("apple", "app%"));
+        assert!(matches("apple", "%pl%"));
+        assert!(matches("apple", "a_p_e"));
+        assert!(matches("", "%"));
+        assert!(!matches("apple", "app"));
+        assert!(!matches("apple", "_pple_"));
+        assert!(!matches("apple", "%x%"));
+        assert!(matches("banana", "%an%a"));
+        assert!(!matches("banana", "%an%b"));
+    }
+
+    #[test]
+    fn test_like_pathological_pattern() {
+        // every % could take any of the characters, which a backtracking matcher tries one by one
+        let text: String = "a".repeat(255);
+        let pattern: String = "%a".repeat(20) + "%b";
+        let start = ::std::time::Instant::now();
+        assert!(!like(&text.chars().collect::<Vec<char>>(), &pattern.chars().collect::<Vec<char>>()));
+        assert!(start.elapsed() < ::std::time::Duration::from_secs(1));
+    }
+
     #[test]
     fn test_find_field() {
         let tuple: Tuple = Tuple::new(vec![
@@ -396,13 +532,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let equ = Selectors::Leaf(Selector {
+        let equ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::Equ,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, equ);
     }
@@ -419,13 +556,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let equ = Selectors::Leaf(Selector {
+        let equ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::Equ,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, equ);
     }
@@ -434,13 +572,14 @@ mod tests {
     fn test_eval_column_equal_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let equ = Selectors::Leaf(Selector {
+        let equ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::Equ,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -449,10 +588,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -461,22 +597,20 @@ mod tests {
             Field::set_i64(100),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_equal_leaf() {
         let left_hand: Target = gen_left_column();
-        let equ = Selectors::Leaf(Selector {
+        let equ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::Equ,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -485,10 +619,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -497,25 +628,24 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_bool_equal_leaf() {
         let left_hand: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "discounted".to_owned(),
         };
-        let equ = Selectors::Leaf(Selector {
+        let equ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::Equ,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_bool(true)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -524,10 +654,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(equ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -536,10 +663,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(false),
         ]);
-        assert_eq!(
-            eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(equ.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -554,13 +678,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let nequ = Selectors::Leaf(Selector {
+        let nequ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::NEqu,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, nequ);
     }
@@ -577,13 +702,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let nequ = Selectors::Leaf(Selector {
+        let nequ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::NEqu,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, nequ);
     }
@@ -592,13 +718,14 @@ mod tests {
     fn test_eval_column_not_equal_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let nequ = Selectors::Leaf(Selector {
+        let nequ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::NEqu,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -607,10 +734,7 @@ mod tests {
             Field::set_i64(200),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -619,22 +743,20 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_not_equal_leaf() {
         let left_hand: Target = gen_left_column();
-        let nequ = Selectors::Leaf(Selector {
+        let nequ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::NEqu,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -643,10 +765,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -655,25 +774,24 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()));
     }
 
    #[test]
     fn test_eval_bool_not_equal_leaf() {
         let left_hand: Target = Target {
+            database_name: None,
             table_name: Some("shohin".to_owned()),
             name: "discounted".to_owned(),
         };
-        let nequ = Selectors::Leaf(Selector {
+        let nequ = Selectors::Leaf(Box::new(Selector {
             kind: Operator::NEqu,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_bool(true)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -682,10 +800,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(false),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(nequ.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -694,10 +809,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(nequ.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -712,13 +824,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let gt = Selectors::Leaf(Selector {
+        let gt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, gt);
     }
@@ -735,13 +848,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let gt = Selectors::Leaf(Selector {
+        let gt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, gt);
     }
@@ -750,13 +864,14 @@ mod tests {
     fn test_eval_column_gt_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let gt = Selectors::Leaf(Selector {
+        let gt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -765,10 +880,7 @@ mod tests {
             Field::set_i64(200),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(gt.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -777,10 +889,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -789,22 +898,20 @@ mod tests {
             Field::set_i64(400),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_gt_leaf() {
         let left_hand: Target = gen_left_column();
-        let gt = Selectors::Leaf(Selector {
+        let gt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -813,10 +920,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(gt.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -825,10 +929,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -837,10 +938,7 @@ mod tests {
             Field::set_i64(400),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(gt.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -855,13 +953,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let lt = Selectors::Leaf(Selector {
+        let lt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, lt);
     }
@@ -878,13 +977,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let lt = Selectors::Leaf(Selector {
+        let lt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, lt);
     }
@@ -893,13 +993,14 @@ mod tests {
     fn test_eval_column_lt_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let lt = Selectors::Leaf(Selector {
+        let lt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -908,10 +1009,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(lt.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -920,10 +1018,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -932,22 +1027,20 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_lt_leaf() {
         let left_hand: Target = gen_left_column();
-        let lt = Selectors::Leaf(Selector {
+        let lt = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LT,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -956,10 +1049,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(lt.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -968,10 +1058,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -980,10 +1067,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(lt.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -998,13 +1082,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let ge = Selectors::Leaf(Selector {
+        let ge = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, ge);
     }
@@ -1021,13 +1106,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let ge = Selectors::Leaf(Selector {
+        let ge = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, ge);
     }
@@ -1036,13 +1122,14 @@ mod tests {
     fn test_eval_column_ge_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let ge = Selectors::Leaf(Selector {
+        let ge = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1051,10 +1138,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1063,10 +1147,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1075,22 +1156,20 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(ge.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_ge_leaf() {
         let left_hand: Target = gen_left_column();
-        let ge = Selectors::Leaf(Selector {
+        let ge = Selectors::Leaf(Box::new(Selector {
             kind: Operator::GE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1099,10 +1178,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1111,10 +1187,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(ge.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1123,10 +1196,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(ge.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(ge.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -1141,13 +1211,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let le = Selectors::Leaf(Selector {
+        let le = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, le);
     }
@@ -1164,13 +1235,14 @@ mod tests {
         });
         let selectors = build_selectors(condition).unwrap();
 
-        let le = Selectors::Leaf(Selector {
+        let le = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         assert_eq!(selectors, le);
     }
@@ -1179,13 +1251,14 @@ mod tests {
     fn test_eval_column_le_leaf() {
         let left_hand: Target = gen_left_column();
         let right_column: Target = gen_right_column();
-        let le = Selectors::Leaf(Selector {
+        let le = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: Some(right_column),
             scholar: None,
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1194,10 +1267,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(le.clone(), &truthy_tuple, &gen_columns()));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1206,10 +1276,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(le.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1218,22 +1285,20 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(le.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
     fn test_eval_lit_le_leaf() {
         let left_hand: Target = gen_left_column();
-        let le = Selectors::Leaf(Selector {
+        let le = Selectors::Leaf(Box::new(Selector {
             kind: Operator::LE,
             left_table: left_hand.clone().table_name,
             left_column: left_hand.clone().name,
             right_hand: None,
             scholar: Some(Field::set_i64(300)),
-        });
+            list: Vec::new(),
+        }));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1242,10 +1307,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(le.clone(), &truthy_tuple, &gen_columns()));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1254,10 +1316,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(le.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1266,10 +1325,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(le.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(le.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -1292,20 +1348,22 @@ mod tests {
         let built_selectors = build_selectors(condition).unwrap();
 
         let and = Selectors::And(
-            Box::new(Selectors::Leaf(Selector {
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::NEqu,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: Some(right_column),
                 scholar: None,
-            })),
-            Box::new(Selectors::Leaf(Selector {
+                list: Vec::new(),
+            }))),
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::GT,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(200)),
-            })),
+                list: Vec::new(),
+            }))),
         );
         assert_eq!(built_selectors, and);
     }
@@ -1314,20 +1372,22 @@ mod tests {
     fn test_eval_and_node() {
         let left_hand: Target = gen_left_column();
         let and = Selectors::And(
-            Box::new(Selectors::Leaf(Selector {
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::NEqu,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(300)),
-            })),
-            Box::new(Selectors::Leaf(Selector {
+                list: Vec::new(),
+            }))),
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::GT,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(200)),
-            })),
+                list: Vec::new(),
+            }))),
         );
 
         let truthy_tuple: Tuple = Tuple::new(vec![
@@ -1337,10 +1397,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(and.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(and.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1349,10 +1406,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(and.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(and.clone(), &falsey_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1361,10 +1415,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(and.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(and.clone(), &falsey_tuple, &gen_columns()));
     }
 
     #[test]
@@ -1387,20 +1438,22 @@ mod tests {
         let built_selectors = build_selectors(condition).unwrap();
 
         let or = Selectors::Or(
-            Box::new(Selectors::Leaf(Selector {
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::Equ,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: Some(right_column),
                 scholar: None,
-            })),
-            Box::new(Selectors::Leaf(Selector {
+                list: Vec::new(),
+            }))),
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::GT,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(200)),
-            })),
+                list: Vec::new(),
+            }))),
         );
         assert_eq!(built_selectors, or);
     }
@@ -1409,20 +1462,22 @@ mod tests {
     fn test_eval_or_node() {
         let left_hand: Target = gen_left_column();
         let or = Selectors::Or(
-            Box::new(Selectors::Leaf(Selector {
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::Equ,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(300)),
-            })),
-            Box::new(Selectors::Leaf(Selector {
+                list: Vec::new(),
+            }))),
+            Box::new(Selectors::Leaf(Box::new(Selector {
                 kind: Operator::GT,
                 left_table: left_hand.clone().table_name,
                 left_column: left_hand.clone().name,
                 right_hand: None,
                 scholar: Some(Field::set_i64(200)),
-            })),
+                list: Vec::new(),
+            }))),
         );
 
         let truthy_tuple: Tuple = Tuple::new(vec![
@@ -1432,10 +1487,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(or.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(or.clone(), &truthy_tuple, &gen_columns()));
 
         let truthy_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1444,10 +1496,7 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(or.clone(), &truthy_tuple, &gen_columns()),
-            true
-        );
+        assert!(eval_selectors(or.clone(), &truthy_tuple, &gen_columns()));
 
         let falsey_tuple: Tuple = Tuple::new(vec![
             Field::set_i64(1),
@@ -1456,9 +1505,6 @@ mod tests {
             Field::set_i64(300),
             Field::set_bool(true),
         ]);
-        assert_eq!(
-            eval_selectors(or.clone(), &falsey_tuple, &gen_columns()),
-            false
-        );
+        assert!(!eval_selectors(or.clone(), &falsey_tuple, &gen_columns()));
     }
 }
@@ -0,0 +1,105 @@
+use std::marker::PhantomData;
+use std::collections::HashSet;
+
+use ScanIterator;
+use meta::table_info::TableInfo;
+use columns::column::Column;
+use tables::tuple::Tuple;
+use tables::field::Field;
+use parser::statement::SetOperator;
+
+// combines the tuples of two inputs of the same columns, the duplicated tuples are told apart by
+// hashing their fields
+#[derive(Debug)]
+pub struct SetOperationExec<'s, 't: 's, L: 't, R: 't> {
+    op: SetOperator,
+    left: &'s mut L,
+    right: &'s mut R,
+    // the tuples of the right input, read at first for INTERSECT and EXCEPT
+    right_tuples: Option<HashSet<Vec<Field>>>,
+    returned: HashSet<Vec<Field>>,
+    _marker: PhantomData<&'t L>,
+}
+
+impl<'s, 't, L, R> SetOperationExec<'s, 't, L, R>
+where
+    L: ScanIterator,
+    R: ScanIterator,
+{
+    pub fn new(op: SetOperator, left: &'s mut L, right: &'s mut R) -> SetOperationExec<'s, 't, L, R> {
+        SetOperationExec {
+            op: op,
+            left: left,
+            right: right,
+            right_tuples: None,
+            returned: HashSet::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn next_union(&mut self) -> Option<Tuple> {
+        while let Some(tuple) = self.left.next().or_else(|| self.right.next()) {
+            if self.op == SetOperator::UnionAll || self.returned.insert(tuple.fields.clone()) {
+                return Some(tuple);
+            }
+        }
+        None
+    }
+
+    fn next_intersect_or_except(&mut self) -> Option<Tuple> {
+        if self.right_tuples.is_none() {
+            let mut right_tuples: HashSet<Vec<Field>> = HashSet::new();
+            loop {
+                match self.right.next() {
+                    None => break,
+                    Some(tuple) => right_tuples.insert(tuple.fields),
+                };
+            }
+            self.right_tuples = Some(right_tuples);
+        }
+
+        for tuple in self.left.by_ref() {
+            let in_right: bool = self.right_tuples
+                .as_ref()
+                .is_some_and(|right_tuples| right_tuples.contains(&tuple.fields));
+            if in_right != (self.op == SetOperator::Intersect) {
+                continue;
+            }
+            if self.returned.insert(tuple.fields.clone()) {
+                return Some(tuple);
+            }
+        }
+        None
+    }
+}
+
+impl<'s, 't, L, R> ScanIterator for SetOperationExec<'s, 't, L, R>
+where
+    L: ScanIterator,
+    R: ScanIterator,
+{
+    fn get_meta(&self) -> TableInfo {
+        self.left.get_meta()
+    }
+
+    fn get_columns(&self) -> Vec<Column> {
+        self.left.get_columns()
+    }
+}
+
+impl<'s, 't, L, R> Iterator for SetOperationExec<'s, 't, L, R>
+where
+    L: ScanIterator,
+    R: ScanIterator,
+{
+    type Item = Tuple;
+    fn next(&mut self) -> Option<Tuple> {
+        match self.op {
+            SetOperator::Union | SetOperator::UnionAll => self.next_union(),
+            SetOperator::Intersect | SetOperator::Except => self.next_intersect_or_except(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -1,45 +1,45 @@
+use std::sync::{Arc, RwLock};
+
 use ScanIterator;
 use meta::table_info::TableInfo;
 use columns::column::Column;
 use columns::range::Range;
 use tables::tuple::Tuple;
 use tables::table::Table;
+use transaction::Snapshot;
 
 #[derive(Debug)]
-pub struct TableScanExec<'ts, 't: 'ts> {
-    pub table: &'ts Table<'t>,
+pub struct TableScanExec {
+    pub table: Arc<RwLock<Table>>,
     pub ranges: Vec<Range>,
     pub cursor: usize,
     pub seek_handle: usize,
     pub columns: Vec<Column>,
     pub meta: TableInfo,
+    pub snapshot: Snapshot,
 }
 
-impl<'ts, 't> TableScanExec<'ts, 't> {
-    pub fn new(
-        table: &'ts Table<'t>,
-        meta: TableInfo,
-        ranges: Vec<Range>,
-    ) -> TableScanExec<'ts, 't> {
+impl TableScanExec {
+    pub fn new(table: Arc<RwLock<Table>>, meta: TableInfo, ranges: Vec<Range>, snapshot: Snapshot) -> TableScanExec {
+        let columns: Vec<Column> = table.read().unwrap().columns.to_vec();
         TableScanExec {
             table: table,
             ranges: ranges,
             cursor: 0,
             seek_handle: 0,
-            columns: table.columns.iter().map(|c| c.clone()).collect(),
+            columns: columns,
             meta: meta,
+            snapshot: snapshot,
         }
     }
 
-    fn get_tuple(&mut self, handle: usize) -> Tuple {
-        self.table.get_tuple(handle)
-    }
-
     fn set_next_handle(&mut self, next_handle: usize) {
         self.seek_handle = next_handle;
     }
 
-    fn next_handle(&mut self) -> Option<usize> {
+    // the cursor points at the current range and the seek handle at the next record id to look for,
+    // every step is a single seek into the store
+    fn next_handle(&mut self, table: &Table) -> Option<usize> {
         loop {
             if self.cursor >= self.ranges.len() {
                 return None;
@@ -47,26 +47,33 @@ impl<'ts, 't> TableScanExec<'ts, 't> {
 
             let range: &Range = &self.ranges[self.cursor];
             if self.seek_handle < range.low {
-                *&mut self.seek_handle = range.low.clone();
+                self.seek_handle = range.low;
             }
 
             if self.seek_handle > range.high {
-                *&mut self.cursor += 1;
+                self.cursor += 1;
                 continue;
             }
 
-            match self.table.seek(self.seek_handle) {
+            match table.seek(self.seek_handle) {
                 None => {
-                    *&mut self.seek_handle = 0;
+                    self.cursor = self.ranges.len();
                     return None;
                 }
-                Some(handle) => return Some(handle),
+                Some(handle) => {
+                    if handle > range.high {
+                        self.seek_handle = handle;
+                        self.cursor += 1;
+                        continue;
+                    }
+                    return Some(handle);
+                }
             }
         }
     }
 }
 
-impl<'ts, 't> ScanIterator for TableScanExec<'ts, 't> {
+impl ScanIterator for TableScanExec {
     fn get_meta(&self) -> TableInfo {
         self.meta.clone()
     }
@@ -76,18 +83,81 @@ impl<'ts, 't> ScanIterator for TableScanExec<'ts, 't> {
     }
 }
 
-impl<'ts, 't> Iterator for TableScanExec<'ts, 't> {
+impl Iterator for TableScanExec {
     type Item = Tuple;
+    // skips the versions the snapshot does not see. the table is locked only while a tuple is
+    // looked for, so writers of the other sessions are not blocked by the whole scan
     fn next(&mut self) -> Option<Tuple> {
-        match self.next_handle() {
-            None => None,
-            Some(handle) => {
-                *&mut self.seek_handle = handle + 1;
-                Some(self.get_tuple(handle))
+        let table: Arc<RwLock<Table>> = self.table.clone();
+        let table = table.read().unwrap();
+        loop {
+            match self.next_handle(&table) {
+                None => return None,
+                Some(handle) => {
+                    self.set_next_handle(handle + 1);
+                    if table.is_visible(handle, &self.snapshot) {
+                        return Some(table.get_tuple(handle));
+                    }
+                }
             }
         }
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use data_type::DataType;
+    use meta::column_info::ColumnInfo;
+    use allocators::allocator::Allocator;
+    use parser::statement::Engine;
+    use tables::field::Field;
+    use tables::memory_store::MemoryStore;
+    use transaction::TransactionManager;
+
+    fn gen_table() -> Table {
+        let meta: TableInfo = TableInfo {
+            id: 1,
+            name: "shohin".to_string(),
+            columns: vec![ColumnInfo {
+                name: "shohin_id".to_string(),
+                dtype: DataType::Int,
+                offset: 0,
+                default: None,
+            }],
+            indices: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            engine: Engine::Memory,
+            next_record_id: Allocator::new(1),
+        };
+        let mut table: Table = Table::new(meta, Box::new(MemoryStore::new()));
+        let snapshot: Snapshot = TransactionManager::new().begin().snapshot;
+        for i in 1..101 {
+            table.insert(vec![Field::set_i64(i)], &snapshot).unwrap();
+        }
+        table
+    }
+
+    fn scan(table: &Table, ranges: Vec<Range>) -> Vec<usize> {
+        let snapshot: Snapshot = TransactionManager::new().begin().snapshot;
+        let table: Arc<RwLock<Table>> = Arc::new(RwLock::new(table.clone()));
+        let meta: TableInfo = table.read().unwrap().meta.clone();
+        let scan_exec: TableScanExec = TableScanExec::new(table, meta, ranges, snapshot);
+        scan_exec.map(|tuple| tuple.fields[0].get_i64() as usize).collect()
+    }
+
+    #[test]
+    fn test_scan_sparse_record_ids() {
+        let mut table: Table = gen_table();
+        let snapshot: Snapshot = TransactionManager::new().begin().snapshot;
+        let deleted: Vec<usize> = (1..101).filter(|i| i % 10 != 0).collect();
+        table.delete_all(&deleted, &snapshot).unwrap();
+        table.vacuum(2, &HashSet::new()).unwrap();
+
+        assert_eq!(scan(&table, vec![Range::new(0, usize::MAX)]), (1..11).map(|i| i * 10).collect::<Vec<usize>>());
+        assert_eq!(scan(&table, vec![Range::new(15, 35), Range::new(38, 41), Range::new(90, 95)]), vec![20, 30, 40, 90]);
+        assert_eq!(scan(&table, vec![Range::new(101, 200)]), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,55 @@
+use ScanIterator;
+use meta::table_info::TableInfo;
+use columns::column::Column;
+use tables::tuple::Tuple;
+
+// scans tuples held in memory, for the tables which are generated instead of stored
+#[derive(Debug, Clone)]
+pub struct TupleScanExec {
+    pub tuples: Vec<Tuple>,
+    pub cursor: usize,
+    pub columns: Vec<Column>,
+    pub meta: TableInfo,
+}
+
+impl TupleScanExec {
+    pub fn new(meta: TableInfo, tuples: Vec<Tuple>) -> TupleScanExec {
+        let columns: Vec<Column> = meta.columns.iter().map(|c| c.to_column(&meta.name)).collect();
+        TupleScanExec::with_columns(meta, columns, tuples)
+    }
+
+    // for tuples whose columns come from several tables
+    pub fn with_columns(meta: TableInfo, columns: Vec<Column>, tuples: Vec<Tuple>) -> TupleScanExec {
+        TupleScanExec {
+            tuples: tuples,
+            cursor: 0,
+            columns: columns,
+            meta: meta,
+        }
+    }
+}
+
+impl ScanIterator for TupleScanExec {
+    fn get_meta(&self) -> TableInfo {
+        self.meta.clone()
+    }
+
+    fn get_columns(&self) -> Vec<Column> {
+        self.columns.clone()
+    }
+}
+
+impl Iterator for TupleScanExec {
+    type Item = Tuple;
+    fn next(&mut self) -> Option<Tuple> {
+        if self.cursor >= self.tuples.len() {
+            return None;
+        }
+
+        self.cursor += 1;
+        Some(self.tuples[self.cursor - 1].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use catalog::Catalog;
+use database::Database;
+use data_type::DataType;
+use meta::table_info::{column_list, TableInfo};
+use meta::column_info::ColumnInfo;
+use tables::field::Field;
+use tables::tuple::Tuple;
+use executors::tuple_scan::TupleScanExec;
+
+// the catalog is exposed as the read only tables of this database,
+// which are generated from the catalog each time they are scanned
+pub const DATABASE_NAME: &str = "information_schema";
+
+// the kinds of the tables
+pub const BASE_TABLE: &str = "base table";
+pub const VIEW: &str = "view";
+pub const MATERIALIZED_VIEW: &str = "materialized view";
+
+// the kind of a stored table
+pub fn table_type(db: &Database, table_name: &str) -> &'static str {
+    match db.materialized_view(table_name) {
+        None => BASE_TABLE,
+        Some(_) => MATERIALIZED_VIEW,
+    }
+}
+
+pub fn table_info(table_name: &str) -> Option<TableInfo> {
+    let columns: Vec<(&str, DataType)> = match table_name {
+        "tables" => vec![
+            ("table_schema", DataType::Char(64)),
+            ("table_name", DataType::Char(64)),
+            ("table_type", DataType::Char(16)),
+            ("engine", DataType::Char(16)),
+        ],
+        "columns" => vec![
+            ("table_schema", DataType::Char(64)),
+            ("table_name", DataType::Char(64)),
+            ("column_name", DataType::Char(64)),
+            ("ordinal_position", DataType::Int),
+            ("column_default", DataType::Char(255)),
+            ("data_type", DataType::Char(16)),
+        ],
+        "indexes" => vec![
+            ("table_schema", DataType::Char(64)),
+            ("table_name", DataType::Char(64)),
+            ("index_name", DataType::Char(64)),
+            ("column_names", DataType::Char(255)),
+            ("is_unique", DataType::Bool),
+            ("is_primary", DataType::Bool),
+            ("index_type", DataType::Char(16)),
+        ],
+        _ => return None,
+    };
+
+    Some(TableInfo::generated(
+        table_name,
+        columns
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, dtype))| ColumnInfo {
+                name: name.to_string(),
+                dtype: dtype,
+                offset: i,
+                default: None,
+            })
+            .collect(),
+    ))
+}
+
+// the rows are ordered by the database and the table they describe, the views after the tables
+pub fn scan(catalog: &Catalog, table_name: &str) -> Option<TupleScanExec> {
+    table_info(table_name).map(|meta| TupleScanExec::new(meta, rows(catalog, table_name)))
+}
+
+fn rows(catalog: &Catalog, table_name: &str) -> Vec<Tuple> {
+    let mut tuples: Vec<Tuple> = Vec::new();
+    for db in databases(catalog) {
+        let schema: Field = Field::set_str(&db.name);
+        for table_info in table_infos(&db) {
+            let name: Field = Field::set_str(&table_info.name);
+            match table_name {
+                "tables" => tuples.push(Tuple::new(vec![
+                    schema.clone(),
+                    name.clone(),
+                    Field::set_str(table_type(&db, &table_info.name)),
+                    Field::set_str(table_info.engine.to_sql()),
+                ])),
+                "columns" => for column in table_info.columns.iter() {
+                    let default: String = column.default.as_ref().map_or("".to_string(), |d| d.to_sql());
+                    tuples.push(Tuple::new(vec![
+                        schema.clone(),
+                        name.clone(),
+                        Field::set_str(&column.name),
+                        Field::set_i64(column.offset as i64 + 1),
+                        Field::set_str(&default),
+                        Field::set_str(&column.dtype.to_sql()),
+                    ]));
+                },
+                _ => for index_info in table_info.indices.iter() {
+                    tuples.push(Tuple::new(vec![
+                        schema.clone(),
+                        name.clone(),
+                        Field::set_str(&index_info.name),
+                        Field::set_str(&column_list(&index_info.columns)),
+                        Field::set_bool(index_info.is_unique),
+                        Field::set_bool(index_info.is_pk_index),
+                        Field::set_str(index_info.index_type.to_sql()),
+                    ]));
+                },
+            }
+        }
+        // views are stored without an engine
+        if table_name == "tables" {
+            for view_name in view_names(&db) {
+                tuples.push(Tuple::new(vec![
+                    schema.clone(),
+                    Field::set_str(&view_name),
+                    Field::set_str(VIEW),
+                    Field::set_str(""),
+                ]));
+            }
+        }
+    }
+    tuples
+}
+
+fn databases(catalog: &Catalog) -> Vec<Arc<Database>> {
+    let mut databases: Vec<Arc<Database>> = catalog.databases.read().unwrap().values().cloned().collect();
+    databases.sort_by(|a, b| a.name.cmp(&b.name));
+    databases
+}
+
+fn table_infos(db: &Database) -> Vec<TableInfo> {
+    let mut table_infos: Vec<TableInfo> = db.tables.read().unwrap().values().cloned().collect();
+    table_infos.sort_by(|a, b| a.name.cmp(&b.name));
+    table_infos
+}
+
+fn view_names(db: &Database) -> Vec<String> {
+    let mut view_names: Vec<String> = db.views.read().unwrap().keys().cloned().collect();
+    view_names.sort();
+    view_names
+}
+
+#[cfg(test)]
+mod tests {}
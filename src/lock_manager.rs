@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use transaction::TxId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+impl LockMode {
+    fn is_compatible(&self, held: &LockMode) -> bool {
+        *self == LockMode::Shared && *held == LockMode::Shared
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LockTarget {
+    Table(String),
+    Record(String, usize),
+}
+
+#[derive(Debug)]
+struct LockTable {
+    // the holders of each lock and the mode they hold it in
+    granted: HashMap<LockTarget, HashMap<TxId, LockMode>>,
+    // the wait-for graph, the transactions each waiting transaction waits for
+    waits_for: HashMap<TxId, HashSet<TxId>>,
+}
+
+impl LockTable {
+    fn blockers(&self, txid: TxId, target: &LockTarget, mode: LockMode) -> HashSet<TxId> {
+        match self.granted.get(target) {
+            None => HashSet::new(),
+            Some(holders) => holders
+                .iter()
+                .filter(|&(holder, held)| *holder != txid && !mode.is_compatible(held))
+                .map(|(holder, _)| *holder)
+                .collect(),
+        }
+    }
+
+    // looks for a path in the wait-for graph leading back to the transaction
+    fn is_deadlocked(&self, txid: TxId) -> bool {
+        let mut visited: HashSet<TxId> = HashSet::new();
+        let mut waiters: Vec<TxId> = vec![txid];
+        while let Some(waiter) = waiters.pop() {
+            if let Some(holders) = self.waits_for.get(&waiter) {
+                for holder in holders.iter() {
+                    if *holder == txid {
+                        return true;
+                    }
+                    if visited.insert(*holder) {
+                        waiters.push(*holder);
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+// locks are held until the transaction ends. a shared lock is upgraded by asking for it
+// in the exclusive mode
+#[derive(Debug)]
+pub struct LockManager {
+    table: Mutex<LockTable>,
+    released: Condvar,
+}
+
+impl LockManager {
+    pub fn new() -> LockManager {
+        LockManager {
+            table: Mutex::new(LockTable {
+                granted: HashMap::new(),
+                waits_for: HashMap::new(),
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    // blocks until the lock is granted, or until the timeout passes when one is given.
+    // the waiter which would close a cycle in the wait-for graph is refused as the victim
+    pub fn acquire(
+        &self,
+        txid: TxId,
+        target: LockTarget,
+        mode: LockMode,
+        timeout: Option<Duration>,
+    ) -> Result<(), LockError> {
+        let deadline: Option<Instant> = timeout.map(|timeout| Instant::now() + timeout);
+        let mut table = self.table.lock().unwrap();
+        loop {
+            let blockers: HashSet<TxId> = table.blockers(txid, &target, mode);
+            if blockers.is_empty() {
+                table.waits_for.remove(&txid);
+                let held: &mut LockMode = table
+                    .granted
+                    .entry(target)
+                    .or_default()
+                    .entry(txid)
+                    .or_insert(mode);
+                if mode == LockMode::Exclusive {
+                    *held = mode;
+                }
+                return Ok(());
+            }
+
+            table.waits_for.insert(txid, blockers);
+            if table.is_deadlocked(txid) {
+                table.waits_for.remove(&txid);
+                return Err(LockError::DeadlockError);
+            }
+
+            table = match deadline {
+                None => self.released.wait(table).unwrap(),
+                Some(deadline) => {
+                    let now: Instant = Instant::now();
+                    if now >= deadline {
+                        table.waits_for.remove(&txid);
+                        return Err(LockError::LockTimeoutError);
+                    }
+                    self.released.wait_timeout(table, deadline - now).unwrap().0
+                }
+            };
+        }
+    }
+
+    // wakes the waiters up to look at the locks again
+    pub fn release_all(&self, txid: TxId) {
+        let mut table = self.table.lock().unwrap();
+        for holders in table.granted.values_mut() {
+            holders.remove(&txid);
+        }
+        table.granted.retain(|_, holders| !holders.is_empty());
+        table.waits_for.remove(&txid);
+        for holders in table.waits_for.values_mut() {
+            holders.remove(&txid);
+        }
+        self.released.notify_all();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LockError {
+    DeadlockError,
+    LockTimeoutError,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn record(record_id: usize) -> LockTarget {
+        LockTarget::Record("shohin".to_string(), record_id)
+    }
+
+    #[test]
+    fn test_lock_modes() {
+        let locks: LockManager = LockManager::new();
+        let timeout: Option<Duration> = Some(Duration::from_millis(10));
+        locks.acquire(1, record(1), LockMode::Shared, None).unwrap();
+        locks.acquire(2, record(1), LockMode::Shared, None).unwrap();
+        assert_eq!(
+            locks.acquire(2, record(1), LockMode::Exclusive, timeout),
+            Err(LockError::LockTimeoutError)
+        );
+
+        locks.release_all(1);
+        locks.acquire(2, record(1), LockMode::Exclusive, timeout).unwrap();
+        assert_eq!(
+            locks.acquire(1, record(1), LockMode::Shared, timeout),
+            Err(LockError::LockTimeoutError)
+        );
+        locks.acquire(1, LockTarget::Table("shohin".to_string()), LockMode::Exclusive, timeout).unwrap();
+    }
+
+    #[test]
+    fn test_deadlock_victim() {
+        let locks: Arc<LockManager> = Arc::new(LockManager::new());
+        locks.acquire(1, record(1), LockMode::Exclusive, None).unwrap();
+        locks.acquire(2, record(2), LockMode::Exclusive, None).unwrap();
+
+        let waiter = {
+            let locks: Arc<LockManager> = locks.clone();
+            thread::spawn(move || locks.acquire(1, record(2), LockMode::Exclusive, None))
+        };
+        // the other side has to be waiting before the cycle can be closed
+        while !locks.table.lock().unwrap().waits_for.contains_key(&1) {
+            thread::yield_now();
+        }
+
+        assert_eq!(
+            locks.acquire(2, record(1), LockMode::Exclusive, None),
+            Err(LockError::DeadlockError)
+        );
+        locks.release_all(2);
+        assert_eq!(waiter.join().unwrap(), Ok(()));
+    }
+}
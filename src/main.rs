@@ -1,7 +1,14 @@
 use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 mod client;
+mod catalog;
+mod information_schema;
 mod database;
+mod transaction;
+mod lock_manager;
 mod context;
 mod columns;
 mod tables;
@@ -9,17 +16,19 @@ mod data_type;
 mod meta;
 mod allocators;
 mod executors;
+mod planner;
 mod parser;
 
 pub use client::Client;
+pub use catalog::Catalog;
 pub use database::Database;
 pub use context::Context;
+pub use transaction::TransactionManager;
 pub use columns::column::Column;
 pub use columns::range::Range;
 pub use tables::field::Field;
 pub use tables::tuple::Tuple;
 pub use tables::table::Table;
-pub use tables::memory_table::MemoryTable;
 pub use tables::index::Index;
 pub use data_type::DataType;
 pub use meta::table_info::TableInfo;
@@ -28,27 +37,34 @@ pub use meta::index_info::IndexInfo;
 pub use allocators::allocator::Allocator;
 pub use executors::scan_iterator::ScanIterator;
 pub use executors::table_scan::TableScanExec;
-pub use executors::memory_table_scan::MemoryTableScanExec;
+pub use executors::index_scan::IndexScanExec;
+pub use executors::tuple_scan::TupleScanExec;
 pub use executors::join::NestedLoopJoinExec;
 pub use executors::selection::SelectionExec;
 pub use executors::selector::{eval_selectors, Selectors};
 pub use executors::projection::ProjectionExec;
 pub use executors::aggregation::AggregationExec;
+pub use executors::set_operation::SetOperationExec;
+pub use executors::recursive_union::RecursiveUnionExec;
 pub use executors::aggregator::{Aggregator, Aggregators};
 pub use parser::statement::*;
 pub use parser::parser::Parser;
 
+// the files of the databases are put under the directory given as the first argument,
+// or under the temporary directory without one
 fn main() {
-    let db: Database = Database {
-        id: 1,
-        name: "test".to_string(),
-        tables: HashMap::new(),
-        real_tables: HashMap::new(),
+    let data_dir: PathBuf = match env::args().nth(1) {
+        Some(data_dir) => PathBuf::from(data_dir),
+        None => env::temp_dir().join("puresql"),
     };
+    let catalog: Arc<Catalog> = Arc::new(Catalog::new(data_dir));
+    let db: Arc<Database> = catalog.create_database("test").unwrap();
 
     let ctx: Context = Context {
+        catalog: catalog,
         db: Some(db),
-        table_id_alloc: Allocator::new(1),
+        lock_timeout: None,
+        ctes: HashMap::new(),
     };
 
     let mut client: Client = Client::new(ctx);
@@ -0,0 +1,64 @@
+use columns::column::Column;
+use tables::tuple::Tuple;
+use parser::statement::{Comparable, Conditions};
+use executors::selector::{build_selectors, SelectorError, Selectors};
+
+#[derive(Debug, Clone)]
+pub struct CheckInfo {
+    pub name: String,
+    pub condition: Conditions,
+    // built once from the condition, every written row is evaluated with them
+    pub selectors: Selectors,
+}
+
+impl CheckInfo {
+    // a condition which can not be evaluated on a single row, such as a subquery, is refused here
+    pub fn new(name: String, condition: Conditions) -> Result<CheckInfo, SelectorError> {
+        let selectors: Selectors = try!(build_selectors(condition.clone()));
+        Ok(CheckInfo {
+            name: name,
+            condition: condition,
+            selectors: selectors,
+        })
+    }
+
+    pub fn eval(&self, tuple: &Tuple, columns: &[Column]) -> bool {
+        self.selectors.eval(tuple, columns)
+    }
+
+    // names of every column the condition refers
+    pub fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        collect_column_names(&self.condition, &mut names);
+        names
+    }
+
+    // nth is the position of the check in the table, so that checks of the same column differ
+    pub fn generate_name(table_name: &str, column_name: Option<&str>, nth: usize) -> String {
+        match column_name {
+            Some(column_name) => format!("{}_{}_check{}", table_name, column_name, nth),
+            None => format!("{}_check{}", table_name, nth),
+        }
+    }
+}
+
+fn collect_column_names(condition: &Conditions, names: &mut Vec<String>) {
+    match *condition {
+        Conditions::And(ref c1, ref c2) | Conditions::Or(ref c1, ref c2) => {
+            collect_column_names(c1, names);
+            collect_column_names(c2, names);
+        }
+        Conditions::Leaf(ref c) => {
+            names.push(c.left.name.clone());
+            if let Comparable::Target(ref t) = c.right {
+                names.push(t.name.clone());
+            }
+        }
+        Conditions::In(ref t, _) => names.push(t.name.clone()),
+        Conditions::Not(ref c) => collect_column_names(c, names),
+        Conditions::Exists(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {}
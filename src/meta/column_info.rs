@@ -1,11 +1,13 @@
 use data_type::DataType;
 use columns::column::Column;
+use tables::field::Field;
 
 #[derive(Debug, Clone)]
 pub struct ColumnInfo {
     pub name: String,
     pub dtype: DataType,
     pub offset: usize,
+    pub default: Option<Field>,
 }
 
 impl ColumnInfo {
@@ -0,0 +1,28 @@
+use meta::column_info::ColumnInfo;
+use parser::statement::ReferentialAction;
+
+#[derive(Debug, Clone)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub table_name: String,
+    pub columns: Vec<ColumnInfo>,
+    pub ref_table_name: String,
+    pub ref_columns: Vec<ColumnInfo>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+impl ForeignKeyInfo {
+    pub fn generate_name(table_name: &str, column_names: &Vec<&str>) -> String {
+        let mut name: String = table_name.to_string();
+        for column_name in column_names {
+            name.push('_');
+            name.push_str(column_name);
+        }
+        name.push_str("_fkey");
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -1,5 +1,6 @@
-use meta::table_info::TableInfo;
+use meta::table_info::{TableInfo, TableInfoError};
 use meta::column_info::ColumnInfo;
+use parser::statement::IndexType;
 
 #[derive(Debug, Clone)]
 pub struct IndexInfo {
@@ -8,27 +9,47 @@ pub struct IndexInfo {
     pub table_name: String,
     pub columns: Vec<ColumnInfo>,
     pub is_pk_index: bool,
+    pub is_unique: bool,
+    pub index_type: IndexType,
 }
 
 impl IndexInfo {
-    pub fn new(table_info: &mut TableInfo, column_names: Vec<&str>, is_pk: bool) -> IndexInfo {
+    // the name is generated from the columns when it is not given
+    pub fn new(
+        table_info: &mut TableInfo,
+        name: Option<&str>,
+        column_names: Vec<&str>,
+        is_pk: bool,
+        is_unique: bool,
+        index_type: IndexType,
+    ) -> Result<IndexInfo, TableInfoError> {
+        let name: String = match name {
+            None => Self::generate_name(&column_names),
+            Some(name) => name.to_string(),
+        };
+        if table_info.find_index_by_name(&name).is_some() {
+            return Err(TableInfoError::DuplicateIndexError(name));
+        }
+
         let index_info = IndexInfo {
-            id: 0,
-            name: Self::generate_name(&column_names),
+            id: table_info.indices.iter().map(|i| i.id).max().unwrap_or(0) + 1,
+            name: name,
             table_name: table_info.name.clone(),
-            columns: table_info.find_column_infos_by_names(&column_names),
+            columns: try!(table_info.find_column_infos_by_names(&column_names)),
             is_pk_index: is_pk,
+            is_unique: is_pk || is_unique,
+            index_type: index_type,
         };
 
         table_info.indices.push(index_info.clone());
-        index_info
+        Ok(index_info)
     }
 
     pub fn generate_name(column_names: &Vec<&str>) -> String {
         let mut name: String = "".to_string();
         for column_name in column_names {
             name.push_str(column_name);
-            name.push_str("_");
+            name.push('_');
         }
         name.pop();
         name
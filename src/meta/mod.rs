@@ -1,3 +1,6 @@
 pub mod table_info;
 pub mod column_info;
 pub mod index_info;
+pub mod foreign_key_info;
+pub mod check_info;
+pub mod view_info;
@@ -1,6 +1,9 @@
 use meta::column_info::ColumnInfo;
 use meta::index_info::IndexInfo;
+use meta::foreign_key_info::ForeignKeyInfo;
+use meta::check_info::CheckInfo;
 use allocators::allocator::Allocator;
+use parser::statement::Engine;
 
 #[derive(Debug, Clone)]
 pub struct TableInfo {
@@ -8,10 +11,27 @@ pub struct TableInfo {
     pub name: String,
     pub columns: Vec<ColumnInfo>,
     pub indices: Vec<IndexInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    pub checks: Vec<CheckInfo>,
+    pub engine: Engine,
     pub next_record_id: Box<Allocator>,
 }
 
 impl TableInfo {
+    // for the tables which are built when they are scanned, and are not stored
+    pub fn generated(name: &str, columns: Vec<ColumnInfo>) -> TableInfo {
+        TableInfo {
+            id: 0,
+            name: name.to_string(),
+            columns: columns,
+            indices: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            engine: Engine::Memory,
+            next_record_id: Allocator::new(0),
+        }
+    }
+
     pub fn get_bin_path(&self) -> String {
         self.name.to_string()
     }
@@ -25,22 +45,75 @@ impl TableInfo {
         Err(TableInfoError::ColumnNotFoundError)
     }
 
-    pub fn find_column_infos_by_names(&self, column_names: &Vec<&str>) -> Vec<ColumnInfo> {
+    // keeps the order of the given names, which matters for composite keys
+    pub fn find_column_infos_by_names(
+        &self,
+        column_names: &Vec<&str>,
+    ) -> Result<Vec<ColumnInfo>, TableInfoError> {
         let mut columns: Vec<ColumnInfo> = Vec::new();
-        for column_info in &self.columns {
-            for column_name in column_names {
-                if column_info.name == column_name.to_string() {
-                    columns.push(column_info.clone());
-                }
+        for column_name in column_names {
+            columns.push(try!(self.column_info_from_str(column_name)));
+        }
+        Ok(columns)
+    }
+
+    pub fn primary_key(&self) -> Option<&IndexInfo> {
+        self.indices.iter().find(|index_info| index_info.is_pk_index)
+    }
+
+    pub fn find_index_by_name(&self, name: &str) -> Option<&IndexInfo> {
+        self.indices.iter().find(|index_info| index_info.name == name)
+    }
+
+    // rebuilds the statement which creates the table. indexes without an unique key are left to
+    // their own statements
+    pub fn create_table_sql(&self) -> String {
+        let mut definitions: Vec<String> = Vec::new();
+        for column in self.columns.iter() {
+            let mut definition: String = format!("{} {}", column.name, column.dtype.to_sql());
+            if let Some(ref default) = column.default {
+                definition.push_str(&format!(" default {}", default.to_sql()));
             }
+            definitions.push(definition);
         }
-        columns
+
+        for index_info in self.indices.iter().filter(|index_info| index_info.is_unique) {
+            let constraint: &str = if index_info.is_pk_index { "primary key" } else { "unique" };
+            definitions.push(format!("{} ( {} )", constraint, column_list(&index_info.columns)));
+        }
+
+        for fkey in self.foreign_keys.iter() {
+            definitions.push(format!(
+                "foreign key ( {} ) references {} ( {} ) on delete {} on update {}",
+                column_list(&fkey.columns),
+                fkey.ref_table_name,
+                column_list(&fkey.ref_columns),
+                fkey.on_delete.to_sql(),
+                fkey.on_update.to_sql()
+            ));
+        }
+
+        for check in self.checks.iter() {
+            definitions.push(format!("constraint {} check ( {} )", check.name, check.condition.to_sql()));
+        }
+
+        format!(
+            "create table {} ( {} ) engine = {}",
+            self.name,
+            definitions.join(", "),
+            self.engine.to_sql()
+        )
     }
 }
 
+pub fn column_list(columns: &[ColumnInfo]) -> String {
+    columns.iter().map(|c| &c.name[..]).collect::<Vec<&str>>().join(", ")
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TableInfoError {
     ColumnNotFoundError,
+    DuplicateIndexError(String),
 }
 
 #[cfg(test)]
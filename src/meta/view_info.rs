@@ -0,0 +1,11 @@
+use parser::statement::SelectStmt;
+
+// a view keeps only its query, which is run again as a subquery each time the view is read
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewInfo {
+    pub name: String,
+    pub query: Box<SelectStmt>,
+}
+
+#[cfg(test)]
+mod tests {}
@@ -1,18 +1,24 @@
 #[derive(Debug, PartialEq)]
 pub enum Keyword {
     Create,
-    //Drop,
+    Drop,
     //Alter,
-    //Use,
-    //Describe,
+    Use,
+    Describe,
+    Show,
     Select,
     Update,
     Insert,
     Delete,
     Set,
     Table,
-    //Database,
-    //View,
+    Index,
+    Tables,
+    Indexes,
+    Database,
+    View,
+    Materialized,
+    Refresh,
     Column,
     Count,
     Sum,
@@ -32,6 +38,19 @@ pub enum Keyword {
     Values,
     And,
     Or,
+    Not,
+    Null,
+    In,
+    Between,
+    Like,
+    ILike,
+    Exists,
+    Union,
+    All,
+    Intersect,
+    Except,
+    With,
+    Recursive,
     As,
     On,
     By,
@@ -39,6 +58,25 @@ pub enum Keyword {
     Desc,
     Primary,
     Key,
+    Unique,
+    Default,
+    Foreign,
+    References,
+    Restrict,
+    Cascade,
+    Constraint,
+    Check,
+    Using,
+    Btree,
+    Hash,
+    Engine,
+    Begin,
+    Commit,
+    Rollback,
+    Savepoint,
+    Release,
+    Transaction,
+    To,
     //Replace,
     //AutoIncrement,
     //Comment,
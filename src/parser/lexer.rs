@@ -81,17 +81,23 @@ impl<'c> Lexer<'c> {
         s
     }
 
+    // the literal ends at the quotation mark it starts with, which stands for itself when doubled
     pub fn scan_literal(&mut self) -> Result<String, LexError> {
         let mut l: String = String::new();
+        let quotation_mark: Option<char> = self.curr_char;
         self.bump();
 
         loop {
             match self.curr_char {
                 None => return Err(LexError::UnclosedQuationmark),
-                Some(c) => match c {
-                    '\'' | '"' => break,
-                    _ => l.push(c),
-                },
+                Some(c) if Some(c) == quotation_mark => {
+                    if self.next_char != quotation_mark {
+                        break;
+                    }
+                    l.push(c);
+                    self.bump();
+                }
+                Some(c) => l.push(c),
             }
             self.bump();
         }
@@ -6,6 +6,7 @@ use parser::token_pos::TokenPos;
 use parser::lexer::{LexError, Lexer};
 use parser::keyword::Keyword;
 use parser::statement::*;
+use meta::view_info::ViewInfo;
 
 #[derive(Debug)]
 pub struct Parser<'c> {
@@ -50,6 +51,25 @@ impl<'c> Parser<'c> {
         }
     }
 
+    pub fn validate_engine(&self) -> Result<Engine, ParseError> {
+        let token_pos: &TokenPos = match self.curr_token {
+            None => return Err(ParseError::UnexepectedEoq),
+            Some(ref ts) => ts,
+        };
+
+        let engine: String = match token_pos.token {
+            Token::Word(ref s) => s.to_lowercase(),
+            _ => return Err(ParseError::UndefinedEngine(token_pos.clone())),
+        };
+
+        match &engine[..] {
+            "memory" => Ok(Engine::Memory),
+            "heap" => Ok(Engine::Heap),
+            "columnar" => Ok(Engine::Columnar),
+            _ => Err(ParseError::UndefinedEngine(token_pos.clone())),
+        }
+    }
+
     pub fn validate_datatype(&mut self) -> Result<DataType, ParseError> {
         let debug_token_pos: TokenPos;
         let datatype: String;
@@ -70,6 +90,7 @@ impl<'c> Parser<'c> {
 
         let found_datatype: DataType = match &datatype[..] {
             "int" => DataType::Int,
+            "float" => DataType::Float,
             "bool" => DataType::Bool,
             "char" => {
                 try!(self.bump());
@@ -79,7 +100,7 @@ impl<'c> Parser<'c> {
                 try!(self.bump());
                 try!(self.validate_token(&[Token::ClPar]));
 
-                if 0 <= length && length <= (u8::max_value() as i64) {
+                if 0 <= length && length <= (u8::MAX as i64) {
                     DataType::Char(length as u8)
                 } else {
                     return Err(ParseError::UnexpectedDatatype(debug_token_pos));
@@ -142,14 +163,46 @@ impl<'c> Parser<'c> {
         Ok(found_lit)
     }
 
+    // there are no NULL values to store, so every column is NOT NULL. the constraint is
+    // accepted as it is written, while NULL alone is not
     pub fn validate_column_def(&mut self) -> Result<ColumnDef, ParseError> {
         let name: String = try!(self.validate_word(true));
         try!(self.bump());
 
         let dtype: DataType = try!(self.validate_datatype());
+
+        let mut default: Option<Literal> = None;
+        let mut constraints: Vec<ColumnConstraint> = Vec::new();
+        loop {
+            if self.check_next_keyword(&[Keyword::Default]) {
+                try!(self.double_bump());
+                default = Some(try!(self.validate_literal()));
+            } else if self.check_next_keyword(&[Keyword::Primary]) {
+                try!(self.double_bump());
+                try!(self.validate_keyword(&[Keyword::Key]));
+                constraints.push(ColumnConstraint::PrimaryKey);
+            } else if self.check_next_keyword(&[Keyword::Not]) {
+                try!(self.double_bump());
+                try!(self.validate_keyword(&[Keyword::Null]));
+            } else if self.check_next_keyword(&[Keyword::Unique]) {
+                try!(self.bump());
+                constraints.push(ColumnConstraint::Unique);
+            } else if self.check_next_keyword(&[Keyword::Check]) {
+                try!(self.bump());
+                constraints.push(ColumnConstraint::Check(try!(self.parse_check())));
+            } else if self.check_next_keyword(&[Keyword::References]) {
+                try!(self.bump());
+                constraints.push(ColumnConstraint::References(try!(self.parse_references())));
+            } else {
+                break;
+            }
+        }
+
         Ok(ColumnDef {
             name: name,
             datatype: dtype,
+            default: default,
+            constraints: constraints,
         })
     }
 
@@ -220,67 +273,277 @@ impl<'c> Parser<'c> {
                 let stmt: Statement = Statement::DDL(DDL::Create(try!(self.parse_create_stmt())));
                 Ok(try!(self.build_ast(stmt)))
             }
-            /*
             Keyword::Drop => {
                 let stmt: Statement = Statement::DDL(DDL::Drop(try!(self.parse_drop_stmt())));
                 Ok(try!(self.build_ast(stmt)))
-            },
-            */
+            }
             /*
             Keyword::Alter => {
                 let stmt: Statement = Statement::DDL(DDL::Alter(try!(self.parse_alter_stmt())));
                 Ok(try!(self.build_ast(stmt)))
             },
             */
-            /*
             Keyword::Use => {
                 let stmt: Statement = Statement::DML(DML::Use(try!(self.parse_use_stmt())));
                 Ok(try!(self.build_ast(stmt)))
-            },
-            */
-            /*
+            }
             Keyword::Describe => {
-                let stmt: Statement = Statement::DML(DML::Use(try!(self.parse_describe_stmt())));
+                let stmt: Statement = Statement::DML(DML::Describe(try!(self.parse_describe_stmt())));
                 Ok(try!(self.build_ast(stmt)))
-            },
-            */
+            }
+            Keyword::Show => {
+                let stmt: Statement = Statement::DML(DML::Show(try!(self.parse_show_stmt())));
+                Ok(try!(self.build_ast(stmt)))
+            }
             Keyword::Select => {
-                let stmt: Statement = Statement::DML(DML::Select(try!(self.parse_select_stmt())));
+                let stmt: Statement = match try!(self.parse_query()) {
+                    Query::Select(select) => Statement::DML(DML::Select(select)),
+                    Query::SetOperation(set_operation) => Statement::DML(DML::SetOperation(*set_operation)),
+                };
+                Ok(try!(self.build_ast(stmt)))
+            }
+            Keyword::With => {
+                let stmt: Statement = Statement::DML(DML::With(try!(self.parse_with_stmt())));
                 Ok(try!(self.build_ast(stmt)))
             }
-            /*
             Keyword::Update => {
                 let stmt: Statement = Statement::DML(DML::Update(try!(self.parse_update_stmt())));
                 Ok(try!(self.build_ast(stmt)))
-            },
-            */
+            }
             Keyword::Insert => {
                 let stmt: Statement = Statement::DML(DML::Insert(try!(self.parse_insert_stmt())));
                 Ok(try!(self.build_ast(stmt)))
             }
-            /*
             Keyword::Delete => {
                 let stmt: Statement = Statement::DML(DML::Delete(try!(self.parse_delete_stmt())));
                 Ok(try!(self.build_ast(stmt)))
-            },
-            */
+            }
+            Keyword::Refresh => {
+                try!(self.bump());
+                let stmt: Statement = Statement::DML(DML::Refresh(try!(self.parse_materialized_view_name())));
+                Ok(try!(self.build_ast(stmt)))
+            }
+            Keyword::Set => {
+                let stmt: Statement = Statement::DML(DML::Set(try!(self.parse_set_stmt())));
+                Ok(try!(self.build_ast(stmt)))
+            }
+            Keyword::Begin => {
+                // BEGIN [TRANSACTION]
+                if self.check_next_keyword(&[Keyword::Transaction]) {
+                    try!(self.bump());
+                }
+                Ok(try!(self.build_ast(Statement::TCL(TCL::Begin))))
+            }
+            Keyword::Commit => Ok(try!(self.build_ast(Statement::TCL(TCL::Commit)))),
+            Keyword::Rollback => {
+                let stmt: Statement = Statement::TCL(TCL::Rollback(try!(self.parse_rollback_stmt())));
+                Ok(try!(self.build_ast(stmt)))
+            }
+            Keyword::Savepoint => {
+                let stmt: Statement = Statement::TCL(TCL::Savepoint(try!(self.parse_savepoint_name())));
+                Ok(try!(self.build_ast(stmt)))
+            }
+            Keyword::Release => {
+                let stmt: Statement = Statement::TCL(TCL::Release(try!(self.parse_savepoint_name())));
+                Ok(try!(self.build_ast(stmt)))
+            }
             _ => Err(ParseError::UndefinedStatementError),
         }
     }
 
+    // ROLLBACK [TO [SAVEPOINT] xx]
+    pub fn parse_rollback_stmt(&mut self) -> Result<Option<String>, ParseError> {
+        if !self.check_next_keyword(&[Keyword::To]) {
+            return Ok(None);
+        }
+        try!(self.bump());
+        Ok(Some(try!(self.parse_savepoint_name())))
+    }
+
+    // [SAVEPOINT] xx
+    pub fn parse_savepoint_name(&mut self) -> Result<String, ParseError> {
+        if self.check_next_keyword(&[Keyword::Savepoint]) {
+            try!(self.bump());
+        }
+        try!(self.bump());
+        self.validate_word(false)
+    }
+
+    // SET xx = yy | SET xx TO yy
+    pub fn parse_set_stmt(&mut self) -> Result<SetStmt, ParseError> {
+        try!(self.bump());
+        let name: String = try!(self.validate_word(false));
+        try!(self.bump());
+        if self.validate_keyword(&[Keyword::To]).is_err() {
+            try!(self.validate_token(&[Token::Equ]));
+        }
+        try!(self.bump());
+        Ok(SetStmt {
+            name: name,
+            value: try!(self.validate_literal()),
+        })
+    }
+
+    // USE xx
+    pub fn parse_use_stmt(&mut self) -> Result<UseStmt, ParseError> {
+        Ok(UseStmt {
+            database_name: try!(self.parse_database_name()),
+        })
+    }
+
+    // DATABASE xx
+    pub fn parse_database_name(&mut self) -> Result<String, ParseError> {
+        try!(self.bump());
+        self.validate_word(false)
+    }
+
+    // DESCRIBE xx
+    pub fn parse_describe_stmt(&mut self) -> Result<String, ParseError> {
+        try!(self.bump());
+        self.validate_word(false)
+    }
+
+    // SHOW TABLES | SHOW INDEXES FROM xx | SHOW CREATE TABLE xx
+    pub fn parse_show_stmt(&mut self) -> Result<ShowStmt, ParseError> {
+        try!(self.bump());
+        match try!(self.validate_keyword(&[Keyword::Tables, Keyword::Indexes, Keyword::Index, Keyword::Create])) {
+            Keyword::Tables => Ok(ShowStmt::Tables),
+            Keyword::Create => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::Table]));
+                try!(self.bump());
+                Ok(ShowStmt::CreateTable(try!(self.validate_word(false))))
+            }
+            _ => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::From]));
+                try!(self.bump());
+                Ok(ShowStmt::Indexes(try!(self.validate_word(false))))
+            }
+        }
+    }
+
     pub fn parse_create_stmt(&mut self) -> Result<CreateStmt, ParseError> {
         try!(self.bump());
-        match try!(self.validate_keyword(&[Keyword::Table])) {
+        match try!(self.validate_keyword(&[
+            Keyword::Table,
+            Keyword::Unique,
+            Keyword::Index,
+            Keyword::View,
+            Keyword::Materialized,
+            Keyword::Database,
+        ])) {
             Keyword::Table => Ok(CreateStmt::Table(try!(self.parse_create_table_stmt()))),
+            Keyword::View => Ok(CreateStmt::View(try!(self.parse_create_view_stmt()))),
+            Keyword::Materialized => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::View]));
+                Ok(CreateStmt::MaterializedView(try!(self.parse_create_view_stmt())))
+            }
+            Keyword::Database => Ok(CreateStmt::Database(try!(self.parse_database_name()))),
+            Keyword::Unique => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::Index]));
+                Ok(CreateStmt::Index(try!(self.parse_create_index_stmt(true))))
+            }
+            Keyword::Index => Ok(CreateStmt::Index(try!(self.parse_create_index_stmt(false)))),
+            _ => Err(ParseError::UndefinedStatementError),
+        }
+    }
+
+    // VIEW xx AS SELECT ...
+    pub fn parse_create_view_stmt(&mut self) -> Result<ViewInfo, ParseError> {
+        try!(self.bump());
+        let view_name: String = try!(self.validate_word(false));
+
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::As]));
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::Select]));
+        Ok(ViewInfo {
+            name: view_name,
+            query: Box::new(try!(self.parse_select_stmt())),
+        })
+    }
+
+    // MATERIALIZED VIEW xx
+    pub fn parse_materialized_view_name(&mut self) -> Result<String, ParseError> {
+        try!(self.validate_keyword(&[Keyword::Materialized]));
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::View]));
+        try!(self.bump());
+        self.validate_word(false)
+    }
+
+    // INDEX xx ON yy [USING zz] ( ww )
+    pub fn parse_create_index_stmt(&mut self, is_unique: bool) -> Result<CreateIndexStmt, ParseError> {
+        try!(self.bump());
+        let index_name: String = try!(self.validate_word(false));
+
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::On]));
+        try!(self.bump());
+        let table_name: String = try!(self.validate_word(false));
+
+        // USING BTREE | HASH
+        let mut index_type: IndexType = IndexType::BTree;
+        try!(self.bump());
+        if self.validate_keyword(&[Keyword::Using]).is_ok() {
+            try!(self.bump());
+            index_type = match try!(self.validate_keyword(&[Keyword::Btree, Keyword::Hash])) {
+                Keyword::Hash => IndexType::Hash,
+                _ => IndexType::BTree,
+            };
+            try!(self.bump());
+        }
+
+        Ok(CreateIndexStmt {
+            index_name: index_name,
+            table_name: table_name,
+            column_names: try!(self.parse_column_names()),
+            is_unique: is_unique,
+            index_type: index_type,
+        })
+    }
+
+    pub fn parse_drop_stmt(&mut self) -> Result<DropStmt, ParseError> {
+        try!(self.bump());
+        match try!(self.validate_keyword(&[Keyword::Index, Keyword::View, Keyword::Materialized, Keyword::Database])) {
+            Keyword::Index => Ok(DropStmt::Index(try!(self.parse_drop_index_stmt()))),
+            Keyword::View => {
+                try!(self.bump());
+                Ok(DropStmt::View(try!(self.validate_word(false))))
+            }
+            Keyword::Materialized => Ok(DropStmt::MaterializedView(try!(self.parse_materialized_view_name()))),
+            Keyword::Database => Ok(DropStmt::Database(try!(self.parse_database_name()))),
             _ => Err(ParseError::UndefinedStatementError),
         }
     }
 
+    // INDEX xx [ON yy]
+    pub fn parse_drop_index_stmt(&mut self) -> Result<DropIndexStmt, ParseError> {
+        try!(self.bump());
+        let index_name: String = try!(self.validate_word(false));
+
+        let mut table_name: Option<String> = None;
+        if self.check_next_keyword(&[Keyword::On]) {
+            try!(self.double_bump());
+            table_name = Some(try!(self.validate_word(false)));
+        }
+
+        Ok(DropIndexStmt {
+            index_name: index_name,
+            table_name: table_name,
+        })
+    }
+
     pub fn parse_create_table_stmt(&mut self) -> Result<CreateTableStmt, ParseError> {
         try!(self.bump());
         let mut stmt: CreateTableStmt = CreateTableStmt {
             table_name: try!(self.validate_word(false)),
             columns: Vec::new(),
+            constraints: Vec::new(),
+            engine: Engine::Memory,
         };
 
         try!(self.bump());
@@ -289,44 +552,126 @@ impl<'c> Parser<'c> {
         }
 
         try!(self.validate_token(&[Token::OpPar]));
-        stmt.columns = try!(self.parse_create_columns());
+        try!(self.parse_create_columns(&mut stmt));
+
+        // ENGINE = MEMORY | HEAP | COLUMNAR
+        if self.check_next_keyword(&[Keyword::Engine]) {
+            try!(self.double_bump());
+            try!(self.validate_token(&[Token::Equ]));
+            try!(self.bump());
+            stmt.engine = try!(self.validate_engine());
+        }
         Ok(stmt)
     }
 
-    pub fn parse_create_columns(&mut self) -> Result<Vec<ColumnDef>, ParseError> {
+    pub fn parse_create_columns(&mut self, stmt: &mut CreateTableStmt) -> Result<(), ParseError> {
         try!(self.bump());
-        let mut columns: Vec<ColumnDef> = Vec::new();
-
         while !self.validate_token(&[Token::ClPar]).is_ok() {
-            columns.push(try!(self.validate_column_def()));
+            if self.validate_keyword(table_constraint_keywords()).is_ok() {
+                stmt.constraints.push(try!(self.parse_table_constraint()));
+            } else {
+                stmt.columns.push(try!(self.validate_column_def()));
+            }
+
             try!(self.bump());
             match try!(self.validate_token(&[Token::Comma, Token::ClPar])) {
                 Token::Comma => try!(self.bump()),
                 _ => (),
             }
         }
-        Ok(columns)
+        Ok(())
     }
 
-    pub fn parse_insert_stmt(&mut self) -> Result<InsertStmt, ParseError> {
+    pub fn parse_table_constraint(&mut self) -> Result<TableConstraint, ParseError> {
+        match try!(self.validate_keyword(table_constraint_keywords())) {
+            Keyword::Primary => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::Key]));
+                try!(self.bump());
+                Ok(TableConstraint::PrimaryKey(try!(self.parse_column_names())))
+            }
+            Keyword::Unique => {
+                try!(self.bump());
+                Ok(TableConstraint::Unique(try!(self.parse_column_names())))
+            }
+            Keyword::Foreign => {
+                try!(self.bump());
+                try!(self.validate_keyword(&[Keyword::Key]));
+                try!(self.bump());
+                let column_names: Vec<String> = try!(self.parse_column_names());
+                try!(self.bump());
+                Ok(TableConstraint::ForeignKey(
+                    column_names,
+                    try!(self.parse_references()),
+                ))
+            }
+            Keyword::Constraint => {
+                try!(self.bump());
+                let name: String = try!(self.validate_word(false));
+                try!(self.bump());
+                Ok(TableConstraint::Check(Some(name), try!(self.parse_check())))
+            }
+            Keyword::Check => Ok(TableConstraint::Check(None, try!(self.parse_check()))),
+            _ => Err(ParseError::UndefinedStatementError),
+        }
+    }
+
+    // CHECK ( xx and yy ), leaving the closing parenthesis as the current token
+    pub fn parse_check(&mut self) -> Result<Conditions, ParseError> {
+        try!(self.validate_keyword(&[Keyword::Check]));
         try!(self.bump());
-        try!(self.validate_keyword(&[Keyword::Into]));
+        try!(self.validate_token(&[Token::OpPar]));
+        try!(self.bump());
+        let cond: Conditions = try!(self.parse_or_conditions());
+        try!(self.validate_token(&[Token::ClPar]));
+        Ok(cond)
+    }
 
+    // REFERENCES xx ( yy ) ON DELETE CASCADE ON UPDATE RESTRICT
+    pub fn parse_references(&mut self) -> Result<References, ParseError> {
+        try!(self.validate_keyword(&[Keyword::References]));
         try!(self.bump());
-        let stmt: InsertStmt = InsertStmt {
+        let mut references: References = References {
             table_name: try!(self.validate_word(false)),
-            column_names: try!(self.parse_insert_columns()),
-            values: try!(self.parse_insert_values()),
+            column_names: Vec::new(),
+            on_delete: ReferentialAction::Restrict,
+            on_update: ReferentialAction::Restrict,
         };
 
-        if stmt.column_names.len() != stmt.values.len() {
-            return Err(ParseError::MissmatchColumnNumber);
+        if self.check_next_token(&[Token::OpPar]) {
+            try!(self.bump());
+            references.column_names = try!(self.parse_column_names());
         }
-        Ok(stmt)
+
+        while self.check_next_keyword(&[Keyword::On]) {
+            try!(self.double_bump());
+            let event: Keyword = try!(self.validate_keyword(&[Keyword::Delete, Keyword::Update]));
+
+            try!(self.bump());
+            let action: ReferentialAction = match try!(self.validate_keyword(&[
+                Keyword::Restrict,
+                Keyword::Cascade,
+                Keyword::Set,
+            ])) {
+                Keyword::Restrict => ReferentialAction::Restrict,
+                Keyword::Cascade => ReferentialAction::Cascade,
+                _ => {
+                    try!(self.bump());
+                    try!(self.validate_keyword(&[Keyword::Default]));
+                    ReferentialAction::SetDefault
+                }
+            };
+
+            match event {
+                Keyword::Delete => references.on_delete = action,
+                _ => references.on_update = action,
+            }
+        }
+        Ok(references)
     }
 
-    pub fn parse_insert_columns(&mut self) -> Result<Vec<String>, ParseError> {
-        try!(self.bump());
+    // parses "( col1, col2, ... )" leaving the closing parenthesis as the current token
+    pub fn parse_column_names(&mut self) -> Result<Vec<String>, ParseError> {
         try!(self.validate_token(&[Token::OpPar]));
 
         let mut column_names: Vec<String> = Vec::new();
@@ -346,11 +691,64 @@ impl<'c> Parser<'c> {
         }
     }
 
-    pub fn parse_insert_values(&mut self) -> Result<Vec<Literal>, ParseError> {
+    pub fn parse_insert_stmt(&mut self) -> Result<InsertStmt, ParseError> {
         try!(self.bump());
-        try!(self.validate_keyword(&[Keyword::Values]));
+        try!(self.validate_keyword(&[Keyword::Into]));
 
         try!(self.bump());
+        let stmt: InsertStmt = InsertStmt {
+            table_name: try!(self.validate_word(false)),
+            column_names: try!(self.parse_insert_columns()),
+            source: try!(self.parse_insert_source()),
+        };
+
+        match stmt.source {
+            InsertSource::Values(ref rows) => for values in rows {
+                if !stmt.column_names.is_empty() && stmt.column_names.len() != values.len() {
+                    return Err(ParseError::MissmatchColumnNumber);
+                }
+            },
+            InsertSource::Select(_) => (),
+        }
+        Ok(stmt)
+    }
+
+    // column list is optional, values are taken in table column order without it
+    pub fn parse_insert_columns(&mut self) -> Result<Vec<String>, ParseError> {
+        if !self.check_next_token(&[Token::OpPar]) {
+            return Ok(Vec::new());
+        }
+
+        try!(self.bump());
+        self.parse_column_names()
+    }
+
+    pub fn parse_insert_source(&mut self) -> Result<InsertSource, ParseError> {
+        try!(self.bump());
+        match try!(self.validate_keyword(&[Keyword::Values, Keyword::Select])) {
+            Keyword::Select => Ok(InsertSource::Select(Box::new(try!(self.parse_select_stmt())))),
+            _ => {
+                let mut rows: Vec<Vec<Literal>> = Vec::new();
+                loop {
+                    rows.push(try!(self.parse_insert_values()));
+                    if self.check_next_token(&[Token::Comma]) {
+                        try!(self.bump());
+                    } else {
+                        break;
+                    }
+                }
+                Ok(InsertSource::Values(rows))
+            }
+        }
+    }
+
+    pub fn parse_insert_values(&mut self) -> Result<Vec<Literal>, ParseError> {
+        try!(self.bump());
+        self.parse_literal_list()
+    }
+
+    // ( xx, yy ), leaves the closing parenthesis as the current token
+    pub fn parse_literal_list(&mut self) -> Result<Vec<Literal>, ParseError> {
         try!(self.validate_token(&[Token::OpPar]));
 
         let mut values: Vec<Literal> = Vec::new();
@@ -370,6 +768,66 @@ impl<'c> Parser<'c> {
         }
     }
 
+    pub fn parse_update_stmt(&mut self) -> Result<UpdateStmt, ParseError> {
+        try!(self.bump());
+        let table_name: String = try!(self.validate_word(false));
+
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::Set]));
+
+        let mut assignments: Vec<Assignment> = Vec::new();
+        loop {
+            try!(self.bump());
+            let column_name: String = try!(self.validate_word(true));
+            try!(self.bump());
+            try!(self.validate_token(&[Token::Equ]));
+            try!(self.bump());
+            assignments.push(Assignment {
+                column_name: column_name,
+                value: try!(self.validate_literal()),
+            });
+
+            if self.check_next_token(&[Token::Comma]) {
+                try!(self.bump());
+            } else {
+                break;
+            }
+        }
+
+        // WHERE xx and yy
+        try!(self.bump());
+        let mut condition: Option<Conditions> = None;
+        if self.validate_keyword(&[Keyword::Where]).is_ok() {
+            condition = Some(try!(self.parse_conditions()));
+        }
+
+        Ok(UpdateStmt {
+            table_name: table_name,
+            assignments: assignments,
+            condition: condition,
+        })
+    }
+
+    pub fn parse_delete_stmt(&mut self) -> Result<DeleteStmt, ParseError> {
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::From]));
+
+        try!(self.bump());
+        let table_name: String = try!(self.validate_word(false));
+
+        // WHERE xx and yy
+        try!(self.bump());
+        let mut condition: Option<Conditions> = None;
+        if self.validate_keyword(&[Keyword::Where]).is_ok() {
+            condition = Some(try!(self.parse_conditions()));
+        }
+
+        Ok(DeleteStmt {
+            table_name: table_name,
+            condition: condition,
+        })
+    }
+
     // TODO: impl alias
     pub fn parse_select_stmt(&mut self) -> Result<SelectStmt, ParseError> {
         // SELECT xx, yy
@@ -406,6 +864,7 @@ impl<'c> Parser<'c> {
         if self.validate_keyword(&[Keyword::Limit]).is_ok() {
             try!(self.bump());
             limit = Some(try!(self.parse_limit()));
+            try!(self.bump());
         }
 
         Ok(SelectStmt {
@@ -418,6 +877,99 @@ impl<'c> Parser<'c> {
         })
     }
 
+    // SELECT ... UNION [ALL] | INTERSECT | EXCEPT SELECT ..., where INTERSECT binds tighter than
+    // the others, which are applied from left to right
+    pub fn parse_query(&mut self) -> Result<Query, ParseError> {
+        let mut query: Query = try!(self.parse_intersect_query());
+        loop {
+            let op: SetOperator = match self.validate_keyword(&[Keyword::Union, Keyword::Except]) {
+                Ok(Keyword::Union) if self.check_next_keyword(&[Keyword::All]) => {
+                    try!(self.bump());
+                    SetOperator::UnionAll
+                }
+                Ok(Keyword::Union) => SetOperator::Union,
+                Ok(_) => SetOperator::Except,
+                Err(_) => break,
+            };
+
+            try!(self.bump());
+            let right: Query = try!(self.parse_intersect_query());
+            query = Query::SetOperation(Box::new(SetOperationStmt {
+                op: op,
+                left: query,
+                right: right,
+            }));
+        }
+        Ok(query)
+    }
+
+    // WITH [RECURSIVE] xx [( yy, zz )] AS ( SELECT ... ) [, ...] SELECT ...
+    pub fn parse_with_stmt(&mut self) -> Result<WithStmt, ParseError> {
+        try!(self.bump());
+        let is_recursive: bool = self.validate_keyword(&[Keyword::Recursive]).is_ok();
+        if is_recursive {
+            try!(self.bump());
+        }
+
+        let mut ctes: Vec<CommonTableExpr> = Vec::new();
+        loop {
+            ctes.push(try!(self.parse_common_table_expr()));
+            try!(self.bump());
+            if self.validate_token(&[Token::Comma]).is_ok() {
+                try!(self.bump());
+            } else {
+                break;
+            }
+        }
+
+        Ok(WithStmt {
+            is_recursive: is_recursive,
+            ctes: ctes,
+            query: try!(self.parse_query()),
+        })
+    }
+
+    // leaves the closing parenthesis of the query as the current token
+    pub fn parse_common_table_expr(&mut self) -> Result<CommonTableExpr, ParseError> {
+        let name: String = try!(self.validate_word(false));
+        try!(self.bump());
+
+        let mut column_names: Option<Vec<String>> = None;
+        if self.validate_token(&[Token::OpPar]).is_ok() {
+            column_names = Some(try!(self.parse_column_names()));
+            try!(self.bump());
+        }
+
+        try!(self.validate_keyword(&[Keyword::As]));
+        try!(self.bump());
+        try!(self.validate_token(&[Token::OpPar]));
+        try!(self.bump());
+        let query: Query = try!(self.parse_query());
+        try!(self.validate_token(&[Token::ClPar]));
+
+        Ok(CommonTableExpr {
+            name: name,
+            column_names: column_names,
+            query: query,
+        })
+    }
+
+    pub fn parse_intersect_query(&mut self) -> Result<Query, ParseError> {
+        try!(self.validate_keyword(&[Keyword::Select]));
+        let mut query: Query = Query::Select(Box::new(try!(self.parse_select_stmt())));
+        while self.validate_keyword(&[Keyword::Intersect]).is_ok() {
+            try!(self.bump());
+            try!(self.validate_keyword(&[Keyword::Select]));
+            let right: Query = Query::Select(Box::new(try!(self.parse_select_stmt())));
+            query = Query::SetOperation(Box::new(SetOperationStmt {
+                op: SetOperator::Intersect,
+                left: query,
+                right: right,
+            }));
+        }
+        Ok(query)
+    }
+
     pub fn parse_projectable(&mut self) -> Result<Vec<Projectable>, ParseError> {
         let mut targets: Vec<Projectable> = Vec::new();
         while !self.validate_keyword(&[Keyword::From]).is_ok() {
@@ -452,6 +1004,12 @@ impl<'c> Parser<'c> {
                 Err(_e) => (),
             };
 
+            if self.validate_token(&[Token::OpPar]).is_ok() {
+                targets.push(Projectable::SubQuery(Box::new(try!(self.parse_subquery()))));
+                try!(self.bump());
+                continue;
+            }
+
             match self.validate_literal() {
                 Ok(l) => {
                     targets.push(Projectable::Lit(l));
@@ -461,10 +1019,7 @@ impl<'c> Parser<'c> {
                 Err(_e) => (),
             };
 
-            match self.parse_target() {
-                Ok(target) => targets.push(Projectable::Target(target)),
-                Err(_e) => (),
-            };
+            targets.push(Projectable::Target(try!(self.parse_target())));
         }
         Ok(targets)
     }
@@ -564,22 +1119,27 @@ impl<'c> Parser<'c> {
     }
 
     pub fn parse_target(&mut self) -> Result<Target, ParseError> {
-        match try!(self.validate_word(false)) {
-            _ => {
-                let mut table_name: Option<String> = None;
-                if self.check_next_token(&[Token::Dot]) {
-                    table_name = Some(try!(self.validate_word(false)));
-                    try!(self.double_bump());
-                };
+        let target: Target = try!(self.validate_target());
+        try!(self.bump());
+        Ok(target)
+    }
 
-                let column_name: String = try!(self.validate_word(true));
-                try!(self.bump());
-                Ok(Target {
-                    table_name: table_name,
-                    name: column_name,
-                })
-            }
+    // [[xx.]yy.]zz, the column may be qualified by its table and the database of the table.
+    // the names may be keywords, as the columns of information_schema.tables
+    pub fn validate_target(&mut self) -> Result<Target, ParseError> {
+        let mut qualifiers: Vec<String> = Vec::new();
+        while qualifiers.len() < 2 && self.check_next_token(&[Token::Dot]) {
+            qualifiers.push(try!(self.validate_word(true)));
+            try!(self.double_bump());
         }
+
+        let column_name: String = try!(self.validate_word(true));
+        let table_name: Option<String> = qualifiers.pop();
+        Ok(Target {
+            database_name: qualifiers.pop(),
+            table_name: table_name,
+            name: column_name,
+        })
     }
 
     pub fn parse_from(&mut self) -> Result<DataSource, ParseError> {
@@ -608,81 +1168,172 @@ impl<'c> Parser<'c> {
         Ok(source)
     }
 
+    // [xx.]yy or ( SELECT ... ) [AS] zz
     pub fn parse_data_source(&mut self) -> Result<Source, ParseError> {
+        if self.validate_token(&[Token::OpPar]).is_ok() {
+            let query: SelectStmt = try!(self.parse_subquery());
+            try!(self.bump());
+            if self.validate_keyword(&[Keyword::As]).is_ok() {
+                try!(self.bump());
+            }
+            let alias: String = try!(self.validate_word(false));
+            try!(self.bump());
+            return Ok(Source::SubQuery(SubQuery {
+                query: Box::new(query),
+                alias: alias,
+            }));
+        }
+
+        let mut database_name: Option<String> = None;
+        if self.check_next_token(&[Token::Dot]) {
+            database_name = Some(try!(self.validate_word(false)));
+            try!(self.double_bump());
+        }
+
         let table = Source::Table(Table {
+            database_name: database_name,
             name: try!(self.validate_word(true)),
         });
         try!(self.bump());
         Ok(table)
     }
 
+    // ( SELECT ... ), leaves the closing parenthesis as the current token
+    pub fn parse_subquery(&mut self) -> Result<SelectStmt, ParseError> {
+        try!(self.validate_token(&[Token::OpPar]));
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::Select]));
+        let query: SelectStmt = try!(self.parse_select_stmt());
+        try!(self.validate_token(&[Token::ClPar]));
+        Ok(query)
+    }
+
+    // expects the keyword preceding the conditions as the current token,
+    // and leaves the token following them as the current one
     pub fn parse_conditions(&mut self) -> Result<Conditions, ParseError> {
-        let mut cond;
-        if self.check_next_token(&[Token::OpPar]) {
+        try!(self.bump());
+        self.parse_or_conditions()
+    }
+
+    // OR binds looser than AND
+    pub fn parse_or_conditions(&mut self) -> Result<Conditions, ParseError> {
+        let mut cond: Conditions = try!(self.parse_and_conditions());
+        while self.validate_keyword(&[Keyword::Or]).is_ok() {
             try!(self.bump());
-            cond = try!(self.parse_conditions());
+            cond = Conditions::Or(Box::new(cond), Box::new(try!(self.parse_and_conditions())));
+        }
+        Ok(cond)
+    }
+
+    pub fn parse_and_conditions(&mut self) -> Result<Conditions, ParseError> {
+        let mut cond: Conditions = try!(self.parse_primary_conditions());
+        while self.validate_keyword(&[Keyword::And]).is_ok() {
+            try!(self.bump());
+            cond = Conditions::And(
+                Box::new(cond),
+                Box::new(try!(self.parse_primary_conditions())),
+            );
+        }
+        Ok(cond)
+    }
+
+    pub fn parse_primary_conditions(&mut self) -> Result<Conditions, ParseError> {
+        if self.validate_token(&[Token::OpPar]).is_ok() {
+            try!(self.bump());
+            let cond: Conditions = try!(self.parse_or_conditions());
             try!(self.validate_token(&[Token::ClPar]));
-            if self.check_next_keyword(&[Keyword::And, Keyword::Or]) {
-                try!(self.bump());
-                match try!(self.validate_keyword(&[Keyword::And, Keyword::Or])) {
-                    Keyword::And => {
-                        cond = Conditions::And(
-                            Box::new(cond),
-                            Box::new(try!(self.parse_conditions())),
-                        );
-                    }
-                    Keyword::Or => {
-                        cond =
-                            Conditions::Or(Box::new(cond), Box::new(try!(self.parse_conditions())));
-                    }
-                    _ => {}
-                };
-            };
+            try!(self.bump());
+            Ok(cond)
+        } else if self.validate_keyword(&[Keyword::Not]).is_ok() {
+            // NOT binds tighter than AND
+            try!(self.bump());
+            Ok(Conditions::Not(Box::new(try!(self.parse_primary_conditions()))))
+        } else if self.validate_keyword(&[Keyword::Exists]).is_ok() {
+            // EXISTS ( SELECT ... )
+            try!(self.bump());
+            let cond: Conditions = Conditions::Exists(Box::new(try!(self.parse_subquery())));
+            try!(self.bump());
+            Ok(cond)
         } else {
-            cond = Conditions::Leaf(try!(self.parse_condition()));
-            while self.validate_keyword(&[Keyword::And, Keyword::Or]).is_ok() {
-                match try!(self.validate_keyword(&[Keyword::And, Keyword::Or])) {
-                    Keyword::And => {
-                        if self.check_next_token(&[Token::OpPar]) {
-                            cond = Conditions::And(
-                                Box::new(cond),
-                                Box::new(try!(self.parse_conditions())),
-                            );
-                        } else {
-                            cond = Conditions::And(
-                                Box::new(cond),
-                                Box::new(Conditions::Leaf(try!(self.parse_condition()))),
-                            );
-                            try!(self.bump());
-                        };
-                    }
-                    Keyword::Or => {
-                        cond =
-                            Conditions::Or(Box::new(cond), Box::new(try!(self.parse_conditions())));
-                    }
-                    _ => return Err(ParseError::UndefinedStatementError),
-                };
+            let left_side: Target = try!(self.validate_target());
+            try!(self.bump());
+
+            // xx NOT IN, NOT BETWEEN and NOT LIKE
+            let is_negated: bool = self.validate_keyword(&[Keyword::Not]).is_ok();
+            if is_negated {
+                try!(self.bump());
+            }
+
+            let cond: Conditions = match self.validate_keyword(&[Keyword::In, Keyword::Between, Keyword::Like, Keyword::ILike]) {
+                Ok(Keyword::In) => try!(self.parse_in(left_side)),
+                Ok(Keyword::Between) => Conditions::Leaf(try!(self.parse_between(left_side))),
+                Ok(Keyword::Like) => Conditions::Leaf(try!(self.parse_like(left_side, Operator::Like))),
+                Ok(Keyword::ILike) => Conditions::Leaf(try!(self.parse_like(left_side, Operator::ILike))),
+                _ if is_negated => return Err(self.unexpected_token()),
+                _ => Conditions::Leaf(try!(self.parse_condition(left_side))),
+            };
+            try!(self.bump());
+
+            if is_negated {
+                Ok(Conditions::Not(Box::new(cond)))
+            } else {
+                Ok(cond)
             }
         }
+    }
+
+    // IN ( SELECT ... ) or IN ( xx, yy )
+    pub fn parse_in(&mut self, left_side: Target) -> Result<Conditions, ParseError> {
         try!(self.bump());
-        Ok(cond)
+        if self.check_next_keyword(&[Keyword::Select]) {
+            return Ok(Conditions::In(left_side, Box::new(try!(self.parse_subquery()))));
+        }
+
+        Ok(Conditions::Leaf(Condition {
+            left: left_side,
+            op: Operator::In,
+            right: Comparable::List(try!(self.parse_literal_list())),
+        }))
     }
 
-    pub fn parse_condition(&mut self) -> Result<Condition, ParseError> {
+    // BETWEEN xx AND yy
+    pub fn parse_between(&mut self, left_side: Target) -> Result<Condition, ParseError> {
         try!(self.bump());
-        let mut left_table_name: Option<String> = None;
-        if self.check_next_token(&[Token::Dot]) {
-            left_table_name = Some(try!(self.validate_word(false)));
-            try!(self.double_bump());
-        };
+        let low: Literal = try!(self.validate_literal());
+        try!(self.bump());
+        try!(self.validate_keyword(&[Keyword::And]));
+        try!(self.bump());
+        let high: Literal = try!(self.validate_literal());
 
-        let left_column_name: String = try!(self.validate_word(true));
-        let left_side: Target = Target {
-            table_name: left_table_name,
-            name: left_column_name,
-        };
+        Ok(Condition {
+            left: left_side,
+            op: Operator::Between,
+            right: Comparable::List(vec![low, high]),
+        })
+    }
 
+    // LIKE 'xx%' or ILIKE 'xx%'
+    pub fn parse_like(&mut self, left_side: Target, op: Operator) -> Result<Condition, ParseError> {
         try!(self.bump());
+        match try!(self.validate_literal()) {
+            Literal::String(pattern) => Ok(Condition {
+                left: left_side,
+                op: op,
+                right: Comparable::Lit(Literal::String(pattern)),
+            }),
+            _ => Err(self.unexpected_token()),
+        }
+    }
+
+    fn unexpected_token(&self) -> ParseError {
+        match self.curr_token {
+            None => ParseError::UnexepectedEoq,
+            Some(ref ts) => ParseError::UnexpectedToken(ts.clone()),
+        }
+    }
+
+    // expects the operator following the left side as the current token
+    pub fn parse_condition(&mut self, left_side: Target) -> Result<Condition, ParseError> {
         let op: Operator = match try!(self.validate_token(condition_tokens())) {
             Token::Equ => Operator::Equ,
             Token::NEqu => Operator::NEqu,
@@ -697,21 +1348,21 @@ impl<'c> Parser<'c> {
         };
 
         try!(self.bump());
-        let right_side: Comparable = match self.validate_word(false) {
-            Ok(_right) => {
-                let mut right_table_name: Option<String> = None;
-                if self.check_next_token(&[Token::Dot]) {
-                    right_table_name = Some(try!(self.validate_word(false)));
-                    try!(self.double_bump());
-                };
+        if self.validate_token(&[Token::OpPar]).is_ok() {
+            return Ok(Condition {
+                left: left_side,
+                op: op,
+                right: Comparable::SubQuery(Box::new(try!(self.parse_subquery()))),
+            });
+        }
 
-                let right_column_name: String = try!(self.validate_word(true));
-                Comparable::Target(Target {
-                    table_name: right_table_name,
-                    name: right_column_name,
-                })
+        // TRUE and FALSE are words, but no columns
+        let right_side: Comparable = match self.validate_literal() {
+            Ok(lit) => Comparable::Lit(lit),
+            Err(_) => {
+                try!(self.validate_word(false));
+                Comparable::Target(try!(self.validate_target()))
             }
-            _ => Comparable::Lit(try!(self.validate_literal())),
         };
 
         Ok(Condition {
@@ -760,17 +1411,36 @@ fn condition_tokens() -> &'static [Token] {
     ]
 }
 
+fn table_constraint_keywords() -> &'static [Keyword] {
+    &[
+        Keyword::Primary,
+        Keyword::Unique,
+        Keyword::Foreign,
+        Keyword::Constraint,
+        Keyword::Check,
+    ]
+}
+
 fn starting_keywords() -> &'static [Keyword] {
     &[
         Keyword::Create,
-        //Keyword::Drop,
+        Keyword::Drop,
         //Keyword::Alter,
-        //Keyword::Use,
-        //Keyword::Describe,
+        Keyword::Use,
+        Keyword::Describe,
+        Keyword::Show,
         Keyword::Select,
+        Keyword::With,
         Keyword::Update,
         Keyword::Insert,
         Keyword::Delete,
+        Keyword::Refresh,
+        Keyword::Set,
+        Keyword::Begin,
+        Keyword::Commit,
+        Keyword::Rollback,
+        Keyword::Savepoint,
+        Keyword::Release,
     ]
 }
 
@@ -778,18 +1448,35 @@ fn keyword_from_str(string: &str) -> Option<Keyword> {
     let lower_string: String = string.to_lowercase();
     match &lower_string[..] {
         "create" => Some(Keyword::Create),
-        //"drop" => Some(Keyword::Drop),
+        "drop" => Some(Keyword::Drop),
         //"alter" => Some(Keyword::Alter),
-        //"use" => Some(Keyword::Use),
-        //"describe" => Some(Keyword::Describe),
+        "use" => Some(Keyword::Use),
+        "describe" => Some(Keyword::Describe),
+        "show" => Some(Keyword::Show),
+        "tables" => Some(Keyword::Tables),
+        "indexes" => Some(Keyword::Indexes),
         "select" => Some(Keyword::Select),
         "update" => Some(Keyword::Update),
         "insert" => Some(Keyword::Insert),
         "delete" => Some(Keyword::Delete),
         "set" => Some(Keyword::Set),
         "table" => Some(Keyword::Table),
-        //"database" => Some(Keyword::Database),
-        //"view" => Some(Keyword::View),
+        "index" => Some(Keyword::Index),
+        "using" => Some(Keyword::Using),
+        "btree" => Some(Keyword::Btree),
+        "hash" => Some(Keyword::Hash),
+        "engine" => Some(Keyword::Engine),
+        "begin" => Some(Keyword::Begin),
+        "commit" => Some(Keyword::Commit),
+        "rollback" => Some(Keyword::Rollback),
+        "savepoint" => Some(Keyword::Savepoint),
+        "release" => Some(Keyword::Release),
+        "transaction" => Some(Keyword::Transaction),
+        "to" => Some(Keyword::To),
+        "database" => Some(Keyword::Database),
+        "view" => Some(Keyword::View),
+        "materialized" => Some(Keyword::Materialized),
+        "refresh" => Some(Keyword::Refresh),
         "column" => Some(Keyword::Column),
         "count" => Some(Keyword::Count),
         "sum" => Some(Keyword::Sum),
@@ -809,13 +1496,34 @@ fn keyword_from_str(string: &str) -> Option<Keyword> {
         "values" => Some(Keyword::Values),
         "and" => Some(Keyword::And),
         "or" => Some(Keyword::Or),
+        "not" => Some(Keyword::Not),
+        "null" => Some(Keyword::Null),
+        "in" => Some(Keyword::In),
+        "between" => Some(Keyword::Between),
+        "like" => Some(Keyword::Like),
+        "ilike" => Some(Keyword::ILike),
+        "exists" => Some(Keyword::Exists),
+        "union" => Some(Keyword::Union),
+        "all" => Some(Keyword::All),
+        "intersect" => Some(Keyword::Intersect),
+        "except" => Some(Keyword::Except),
+        "with" => Some(Keyword::With),
+        "recursive" => Some(Keyword::Recursive),
         "as" => Some(Keyword::As),
         "on" => Some(Keyword::On),
         "by" => Some(Keyword::By),
         "asc" => Some(Keyword::Asc),
         "desc" => Some(Keyword::Desc),
-        //"primary" => Some(Keyword::Primary),
-        //"key" => Some(Keyword::Key),
+        "primary" => Some(Keyword::Primary),
+        "key" => Some(Keyword::Key),
+        "unique" => Some(Keyword::Unique),
+        "foreign" => Some(Keyword::Foreign),
+        "references" => Some(Keyword::References),
+        "restrict" => Some(Keyword::Restrict),
+        "cascade" => Some(Keyword::Cascade),
+        "constraint" => Some(Keyword::Constraint),
+        "check" => Some(Keyword::Check),
+        "default" => Some(Keyword::Default),
         //"replace" => Some(Keyword::Replace),
         //"auto_increment" => Some(Keyword::AutoIncrement),
         //"comment" => Some(Keyword::Comment),
@@ -832,6 +1540,7 @@ pub enum ParseError {
     UndefinedStatementError,
     UndefinedKeyword(TokenPos),
     UndefinedDatatype(TokenPos),
+    UndefinedEngine(TokenPos),
     UndefinedNumber(TokenPos),
     UnexepectedEoq,
     UnexpectedKeyword(TokenPos),
@@ -847,4 +1556,23 @@ impl From<LexError> for ParseError {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set_stmt() {
+        let stmt = SetStmt {
+            name: "lock_timeout".to_string(),
+            value: Literal::Int(100),
+        };
+        assert_eq!(
+            Parser::new("set lock_timeout = 100").parse(),
+            Ok(Statement::DML(DML::Set(stmt.clone())))
+        );
+        assert_eq!(
+            Parser::new("SET lock_timeout TO 100").parse(),
+            Ok(Statement::DML(DML::Set(stmt)))
+        );
+        assert!(Parser::new("set lock_timeout 100").parse().is_err());
+    }
+}
@@ -1,38 +1,62 @@
 use data_type::DataType;
 use parser::token::Literal;
+use meta::view_info::ViewInfo;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     DDL(DDL),
     DML(DML),
+    TCL(TCL),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DDL {
     Create(CreateStmt),
-    //Drop(DropStmt),
+    Drop(DropStmt),
     //Alter(AlterStmt),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DML {
-    //Use(UseStmt),
-    //Describe(String),
-    Select(SelectStmt),
+    Use(UseStmt),
+    Describe(String),
+    Show(ShowStmt),
+    Select(Box<SelectStmt>),
+    SetOperation(SetOperationStmt),
+    With(WithStmt),
     Update(UpdateStmt),
     Insert(InsertStmt),
     Delete(DeleteStmt),
+    // runs the query of the materialized view again
+    Refresh(String),
+    Set(SetStmt),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TCL {
+    Begin,
+    Commit,
+    // rolls back the whole transaction, or only to the named savepoint
+    Rollback(Option<String>),
+    Savepoint(String),
+    Release(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CreateStmt {
     Table(CreateTableStmt),
-    //View(ViewInfo),
-    //Database(DatabaseInfo),
+    Index(CreateIndexStmt),
+    View(ViewInfo),
+    MaterializedView(ViewInfo),
+    Database(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DropStmt {
+    Index(DropIndexStmt),
+    View(String),
+    MaterializedView(String),
+    Database(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,7 +64,24 @@ pub enum AlterStmt {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum UseStmt {
+pub struct UseStmt {
+    pub database_name: String,
+}
+
+// a setting of the session, as lock_timeout
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetStmt {
+    pub name: String,
+    pub value: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShowStmt {
+    Tables,
+    // DESCRIBE lists the columns
+    Columns(String),
+    Indexes(String),
+    CreateTable(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -53,20 +94,151 @@ pub struct SelectStmt {
     pub limit: Option<isize>,
 }
 
+impl SelectStmt {
+    pub fn to_sql(&self) -> String {
+        let targets: Vec<String> = self.targets.iter().map(|target| target.to_sql()).collect();
+        let mut sql: String = format!("select {} from {}", targets.join(", "), self.source.to_sql());
+        if let Some(ref condition) = self.condition {
+            sql.push_str(&format!(" where {}", condition.to_sql()));
+        }
+        if let Some(ref group_by) = self.group_by {
+            let keys: Vec<String> = group_by.iter().map(|key| key.to_sql()).collect();
+            sql.push_str(&format!(" group by {}", keys.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" limit {}", limit));
+        }
+        sql
+    }
+
+    pub fn has_subqueries(&self) -> bool {
+        let in_targets: bool = self.targets.iter().any(|target| matches!(*target, Projectable::SubQuery(_)));
+        in_targets || self.condition.as_ref().is_some_and(|condition| condition.has_subqueries())
+    }
+}
+
+// a query is a SELECT, or SELECTs combined by set operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Select(Box<SelectStmt>),
+    SetOperation(Box<SetOperationStmt>),
+}
+
+impl Query {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            Query::Select(ref stmt) => stmt.to_sql(),
+            Query::SetOperation(ref stmt) => stmt.to_sql(),
+        }
+    }
+
+    // the tables the SELECTs read from
+    pub fn tables(&self) -> Vec<&Table> {
+        match *self {
+            Query::Select(ref stmt) => stmt.source.tables(),
+            Query::SetOperation(ref stmt) => {
+                let mut tables: Vec<&Table> = stmt.left.tables();
+                tables.extend(stmt.right.tables());
+                tables
+            }
+        }
+    }
+}
+
+// the common table expressions are read as tables by the query, and by the expressions following
+// them. a recursive one reads the rows it yielded last
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithStmt {
+    pub is_recursive: bool,
+    pub ctes: Vec<CommonTableExpr>,
+    pub query: Query,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommonTableExpr {
+    pub name: String,
+    // renames the columns of the query
+    pub column_names: Option<Vec<String>>,
+    pub query: Query,
+}
+
+// the columns of the result are named after the left query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetOperationStmt {
+    pub op: SetOperator,
+    pub left: Query,
+    pub right: Query,
+}
+
+impl SetOperationStmt {
+    pub fn to_sql(&self) -> String {
+        format!("{} {} {}", self.left.to_sql(), self.op.to_sql(), self.right.to_sql())
+    }
+}
+
+// every operator but UNION ALL drops the duplicated rows
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetOperator {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+impl SetOperator {
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            SetOperator::Union => "union",
+            SetOperator::UnionAll => "union all",
+            SetOperator::Intersect => "intersect",
+            SetOperator::Except => "except",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Projectable {
     Lit(Literal),
     Target(Target),
     Aggregate(Aggregate),
+    // yields the first field of the first row, for each projected tuple
+    SubQuery(Box<SelectStmt>),
     All,
 }
 
+impl Projectable {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            Projectable::Lit(ref lit) => lit.to_sql(),
+            Projectable::Target(ref target) => target.to_sql(),
+            Projectable::Aggregate(ref aggregate) => aggregate.to_sql(),
+            Projectable::SubQuery(ref query) => format!("( {} )", query.to_sql()),
+            Projectable::All => "*".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Target {
+    pub database_name: Option<String>,
     pub table_name: Option<String>,
     pub name: String,
 }
 
+impl Target {
+    pub fn to_sql(&self) -> String {
+        let mut names: Vec<&str> = Vec::new();
+        if let Some(ref database_name) = self.database_name {
+            names.push(database_name);
+        }
+        if let Some(ref table_name) = self.table_name {
+            names.push(table_name);
+        }
+        names.push(&self.name);
+        names.join(".")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Aggregate {
     Count(Aggregatable),
@@ -76,6 +248,22 @@ pub enum Aggregate {
     Min(Aggregatable),
 }
 
+impl Aggregate {
+    pub fn to_sql(&self) -> String {
+        let (function, aggregatable): (&str, &Aggregatable) = match *self {
+            Aggregate::Count(ref a) => ("count", a),
+            Aggregate::Sum(ref a) => ("sum", a),
+            Aggregate::Average(ref a) => ("avg", a),
+            Aggregate::Max(ref a) => ("max", a),
+            Aggregate::Min(ref a) => ("min", a),
+        };
+        match *aggregatable {
+            Aggregatable::Target(ref target) => format!("{}({})", function, target.to_sql()),
+            Aggregatable::All => format!("{}(*)", function),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Aggregatable {
     Target(Target),
@@ -88,24 +276,97 @@ pub enum DataSource {
     Join(Box<DataSource>, Box<DataSource>, Option<Conditions>),
 }
 
+impl DataSource {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            DataSource::Leaf(Source::Table(ref t)) => match t.database_name {
+                None => t.name.clone(),
+                Some(ref database_name) => format!("{}.{}", database_name, t.name),
+            },
+            DataSource::Leaf(Source::SubQuery(ref s)) => format!("( {} ) as {}", s.query.to_sql(), s.alias),
+            DataSource::Join(ref left, ref right, None) => format!("{}, {}", left.to_sql(), right.to_sql()),
+            DataSource::Join(ref left, ref right, Some(ref condition)) => {
+                format!("{} join {} on {}", left.to_sql(), right.to_sql(), condition.to_sql())
+            }
+        }
+    }
+
+    // the tables and the views read, from the left
+    pub fn tables(&self) -> Vec<&Table> {
+        match *self {
+            DataSource::Leaf(Source::Table(ref t)) => vec![t],
+            DataSource::Leaf(Source::SubQuery(ref s)) => s.query.source.tables(),
+            DataSource::Join(ref left, ref right, _) => {
+                let mut tables: Vec<&Table> = left.tables();
+                tables.extend(right.tables());
+                tables
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Source {
     Table(Table),
+    SubQuery(SubQuery),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Table {
+    pub database_name: Option<String>,
     pub name: String,
 }
 
+// a derived table, the result of the query is read under the alias
 #[derive(Debug, Clone, PartialEq)]
-pub struct SubQuery {}
+pub struct SubQuery {
+    pub query: Box<SelectStmt>,
+    pub alias: String,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Conditions {
     Leaf(Condition),
     And(Box<Conditions>, Box<Conditions>),
     Or(Box<Conditions>, Box<Conditions>),
+    Not(Box<Conditions>),
+    // the subquery yields a single column to look the value up in
+    In(Target, Box<SelectStmt>),
+    Exists(Box<SelectStmt>),
+}
+
+// AND binds tighter than OR, so only an OR under an AND needs parentheses
+impl Conditions {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            Conditions::Leaf(ref condition) => condition.to_sql(),
+            Conditions::And(ref left, ref right) => {
+                let sides: Vec<String> = [left, right]
+                    .iter()
+                    .map(|side| match ***side {
+                        Conditions::Or(_, _) => format!("( {} )", side.to_sql()),
+                        _ => side.to_sql(),
+                    })
+                    .collect();
+                sides.join(" and ")
+            }
+            Conditions::Or(ref left, ref right) => format!("{} or {}", left.to_sql(), right.to_sql()),
+            Conditions::Not(ref condition) => format!("not ( {} )", condition.to_sql()),
+            Conditions::In(ref target, ref query) => format!("{} in ( {} )", target.to_sql(), query.to_sql()),
+            Conditions::Exists(ref query) => format!("exists ( {} )", query.to_sql()),
+        }
+    }
+
+    pub fn has_subqueries(&self) -> bool {
+        match *self {
+            Conditions::Leaf(ref condition) => matches!(condition.right, Comparable::SubQuery(_)),
+            Conditions::And(ref left, ref right) | Conditions::Or(ref left, ref right) => {
+                left.has_subqueries() || right.has_subqueries()
+            }
+            Conditions::Not(ref condition) => condition.has_subqueries(),
+            Conditions::In(_, _) | Conditions::Exists(_) => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -115,10 +376,32 @@ pub struct Condition {
     pub right: Comparable,
 }
 
+impl Condition {
+    pub fn to_sql(&self) -> String {
+        let right: String = match self.right {
+            Comparable::Lit(ref lit) => lit.to_sql(),
+            Comparable::Target(ref target) => target.to_sql(),
+            Comparable::SubQuery(ref query) => format!("( {} )", query.to_sql()),
+            Comparable::List(ref lits) => {
+                let lits: Vec<String> = lits.iter().map(|lit| lit.to_sql()).collect();
+                match self.op {
+                    Operator::Between => lits.join(" and "),
+                    _ => format!("( {} )", lits.join(", ")),
+                }
+            }
+        };
+        format!("{} {} {}", self.left.to_sql(), self.op.to_sql(), right)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Comparable {
     Lit(Literal),
     Target(Target),
+    // compared with the first field of the first row
+    SubQuery(Box<SelectStmt>),
+    // the values of IN, or the lower and the upper bound of BETWEEN
+    List(Vec<Literal>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -129,6 +412,27 @@ pub enum Operator {
     LT,
     GE,
     LE,
+    In,
+    Between,
+    Like,
+    ILike,
+}
+
+impl Operator {
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            Operator::Equ => "=",
+            Operator::NEqu => "<>",
+            Operator::GT => ">",
+            Operator::LT => "<",
+            Operator::GE => ">=",
+            Operator::LE => "<=",
+            Operator::In => "in",
+            Operator::Between => "between",
+            Operator::Like => "like",
+            Operator::ILike => "ilike",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -141,30 +445,141 @@ pub struct OrderBy {}
 pub struct Limit {}
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum UpdateStmt {
+pub struct UpdateStmt {
+    pub table_name: String,
+    pub assignments: Vec<Assignment>,
+    pub condition: Option<Conditions>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub column_name: String,
+    pub value: Literal,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InsertStmt {
     pub table_name: String,
     pub column_names: Vec<String>,
-    pub values: Vec<Literal>,
+    pub source: InsertSource,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum DeleteStmt {
+pub enum InsertSource {
+    Values(Vec<Vec<Literal>>),
+    Select(Box<SelectStmt>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteStmt {
+    pub table_name: String,
+    pub condition: Option<Conditions>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateIndexStmt {
+    pub index_name: String,
+    pub table_name: String,
+    pub column_names: Vec<String>,
+    pub is_unique: bool,
+    pub index_type: IndexType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexType {
+    BTree,
+    Hash,
+}
+
+impl IndexType {
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            IndexType::BTree => "btree",
+            IndexType::Hash => "hash",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropIndexStmt {
+    pub index_name: String,
+    pub table_name: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CreateTableStmt {
     pub table_name: String,
     pub columns: Vec<ColumnDef>,
+    pub constraints: Vec<TableConstraint>,
+    pub engine: Engine,
+}
+
+// where the rows of a table are stored
+#[derive(Debug, Clone, PartialEq)]
+pub enum Engine {
+    Memory,
+    Heap,
+    Columnar,
+}
+
+impl Engine {
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            Engine::Memory => "memory",
+            Engine::Heap => "heap",
+            Engine::Columnar => "columnar",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ColumnDef {
     pub name: String,
     pub datatype: DataType,
+    pub default: Option<Literal>,
+    pub constraints: Vec<ColumnConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnConstraint {
+    PrimaryKey,
+    Unique,
+    References(References),
+    Check(Conditions),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableConstraint {
+    PrimaryKey(Vec<String>),
+    Unique(Vec<String>),
+    ForeignKey(Vec<String>, References),
+    Check(Option<String>, Conditions),
+}
+
+// referenced columns are left empty to point the primary key of the table
+#[derive(Debug, Clone, PartialEq)]
+pub struct References {
+    pub table_name: String,
+    pub column_names: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferentialAction {
+    Restrict,
+    Cascade,
+    SetDefault,
+}
+
+impl ReferentialAction {
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            ReferentialAction::Restrict => "restrict",
+            ReferentialAction::Cascade => "cascade",
+            ReferentialAction::SetDefault => "set default",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -31,5 +31,24 @@ pub enum Literal {
     Bool(u8),
 }
 
+impl Literal {
+    pub fn to_sql(&self) -> String {
+        match *self {
+            Literal::String(ref s) => format!("'{}'", s.replace('\'', "''")),
+            Literal::Int(i) => i.to_string(),
+            // always with a decimal point, so that it is read back as a float
+            Literal::Float(f) => {
+                let s: String = f.to_string();
+                if s.contains('.') {
+                    s
+                } else {
+                    s + ".0"
+                }
+            }
+            Literal::Bool(b) => (b != 0).to_string(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,457 @@
+use std::collections::Bound;
+
+use columns::range::KeyRange;
+use meta::table_info::TableInfo;
+use meta::index_info::IndexInfo;
+use tables::field::Field;
+use columns::column::Column;
+use parser::statement::{Comparable, Condition, Conditions, IndexType, Operator, Target};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessPath {
+    FullScan,
+    IndexScan(String, Box<KeyRange>),
+}
+
+// bounds which the AND-ed conditions put on a single column
+#[derive(Debug, Clone)]
+struct ColumnBounds {
+    equal: Option<Field>,
+    low: Bound<Field>,
+    high: Bound<Field>,
+}
+
+impl ColumnBounds {
+    fn new() -> ColumnBounds {
+        ColumnBounds {
+            equal: None,
+            low: Bound::Unbounded,
+            high: Bound::Unbounded,
+        }
+    }
+
+    fn is_ranged(&self) -> bool {
+        self.low != Bound::Unbounded || self.high != Bound::Unbounded
+    }
+
+    fn add(&mut self, op: &Operator, field: Field) {
+        match *op {
+            Operator::Equ => self.equal = Some(field),
+            Operator::GT => self.low = tighter_low(self.low.clone(), Bound::Excluded(field)),
+            Operator::GE => self.low = tighter_low(self.low.clone(), Bound::Included(field)),
+            Operator::LT => self.high = tighter_high(self.high.clone(), Bound::Excluded(field)),
+            Operator::LE => self.high = tighter_high(self.high.clone(), Bound::Included(field)),
+            Operator::NEqu | Operator::In | Operator::Between | Operator::Like | Operator::ILike => {}
+        }
+    }
+
+    // the values of IN are covered by a single range, from the least to the greatest of them
+    fn add_list(&mut self, op: &Operator, mut fields: Vec<Field>) {
+        match *op {
+            Operator::In if fields.len() == 1 => self.add(&Operator::Equ, fields.remove(0)),
+            Operator::In if !fields.is_empty() => {
+                let mut least: Field = fields[0].clone();
+                let mut greatest: Field = fields[0].clone();
+                for field in fields {
+                    if field < least {
+                        least = field;
+                    } else if field > greatest {
+                        greatest = field;
+                    }
+                }
+                self.add(&Operator::GE, least);
+                self.add(&Operator::LE, greatest);
+            }
+            Operator::Between if fields.len() == 2 => {
+                let high: Field = fields.remove(1);
+                self.add(&Operator::GE, fields.remove(0));
+                self.add(&Operator::LE, high);
+            }
+            _ => {}
+        }
+    }
+}
+
+// chooses an index which narrows the rows down by the WHERE conditions,
+// the conditions still have to be evaluated on every fetched tuple
+pub fn plan_access_path(meta: &TableInfo, condition: &Option<Conditions>) -> AccessPath {
+    let condition: &Conditions = match *condition {
+        None => return AccessPath::FullScan,
+        Some(ref condition) => condition,
+    };
+
+    let mut leaves: Vec<&Condition> = Vec::new();
+    collect_and_leaves(condition, &mut leaves);
+
+    let mut best: Option<(usize, AccessPath)> = None;
+    for index_info in &meta.indices {
+        match key_range(meta, index_info, &leaves) {
+            None => {}
+            Some((score, range)) => {
+                let is_better: bool = match best {
+                    None => true,
+                    Some((best_score, _)) => score > best_score,
+                };
+                if is_better {
+                    best = Some((score, AccessPath::IndexScan(index_info.name.clone(), Box::new(range))));
+                }
+            }
+        }
+    }
+
+    match best {
+        None => AccessPath::FullScan,
+        Some((_, path)) => path,
+    }
+}
+
+// chooses an index of the inner table whose every column is equated with an outer column,
+// returns the index name and the offsets of the outer columns in the order of the index
+pub fn plan_index_join(
+    outer_columns: &[Column],
+    inner_meta: &TableInfo,
+    condition: &Option<Conditions>,
+) -> Option<(String, Vec<usize>)> {
+    let condition: &Conditions = match *condition {
+        None => return None,
+        Some(ref condition) => condition,
+    };
+
+    let mut leaves: Vec<&Condition> = Vec::new();
+    collect_and_leaves(condition, &mut leaves);
+
+    let mut best: Option<(usize, String, Vec<usize>)> = None;
+    for index_info in &inner_meta.indices {
+        let offsets: Vec<usize> = index_info
+            .columns
+            .iter()
+            .filter_map(|column| join_key_offset(outer_columns, inner_meta, &column.name, &leaves))
+            .collect();
+        if offsets.len() != index_info.columns.len() {
+            continue;
+        }
+
+        let score: usize = if index_info.is_unique { 2 } else { 0 }
+            + if index_info.index_type == IndexType::Hash { 1 } else { 0 };
+        let is_better: bool = match best {
+            None => true,
+            Some((best_score, _, _)) => score > best_score,
+        };
+        if is_better {
+            best = Some((score, index_info.name.clone(), offsets));
+        }
+    }
+    best.map(|(_, name, offsets)| (name, offsets))
+}
+
+// the offset of the outer column equated with the inner column,
+// the inner column has to be qualified by its table name to be told apart from the outer one
+fn join_key_offset(
+    outer_columns: &[Column],
+    inner_meta: &TableInfo,
+    column_name: &str,
+    leaves: &[&Condition],
+) -> Option<usize> {
+    let is_inner = |target: &Target| {
+        target.name == column_name && target.table_name.as_ref() == Some(&inner_meta.name)
+    };
+    let outer_offset = |target: &Target| {
+        outer_columns
+            .iter()
+            .find(|column| {
+                column.name == target.name
+                    && target.table_name.as_ref().is_none_or(|t| *t == column.table_name)
+            })
+            .map(|column| column.offset)
+    };
+
+    for leaf in leaves {
+        if leaf.op != Operator::Equ {
+            continue;
+        }
+        let right: &Target = match leaf.right {
+            Comparable::Target(ref right) => right,
+            Comparable::Lit(_) | Comparable::SubQuery(_) | Comparable::List(_) => continue,
+        };
+
+        let offset: Option<usize> = if is_inner(&leaf.left) {
+            outer_offset(right)
+        } else if is_inner(right) {
+            outer_offset(&leaf.left)
+        } else {
+            None
+        };
+        if offset.is_some() {
+            return offset;
+        }
+    }
+    None
+}
+
+// conditions under an OR can not narrow the scan down
+fn collect_and_leaves<'c>(condition: &'c Conditions, leaves: &mut Vec<&'c Condition>) {
+    match *condition {
+        Conditions::Leaf(ref c) => leaves.push(c),
+        Conditions::And(ref c1, ref c2) => {
+            collect_and_leaves(c1, leaves);
+            collect_and_leaves(c2, leaves);
+        }
+        // a subquery is evaluated for each tuple, it is no bound of a key
+        Conditions::Or(_, _) | Conditions::Not(_) | Conditions::In(_, _) | Conditions::Exists(_) => {}
+    }
+}
+
+// equalities on the leading columns of the index followed by a range on the next one,
+// scored by how many columns they cover
+fn key_range(
+    meta: &TableInfo,
+    index_info: &IndexInfo,
+    leaves: &[&Condition],
+) -> Option<(usize, KeyRange)> {
+    let mut prefix: Vec<Field> = Vec::new();
+    for column in &index_info.columns {
+        let bounds: ColumnBounds = column_bounds(meta, &column.name, leaves);
+        match bounds.equal {
+            Some(field) => prefix.push(field),
+            None => {
+                // a hash index has no order, so it is looked up by the whole key
+                if index_info.index_type == IndexType::Hash {
+                    return None;
+                }
+                if prefix.is_empty() && !bounds.is_ranged() {
+                    return None;
+                }
+                let score: usize = prefix.len() * 2 + if bounds.is_ranged() { 1 } else { 0 };
+                return Some((score, KeyRange::new(prefix, bounds.low, bounds.high)));
+            }
+        }
+    }
+
+    // an unique index looked up by the whole key beats any other index,
+    // and hashing beats walking down a tree
+    let score: usize = prefix.len() * 2 + if index_info.is_unique { 2 } else { 0 }
+        + if index_info.index_type == IndexType::Hash { 1 } else { 0 };
+    Some((score, KeyRange::new(prefix, Bound::Unbounded, Bound::Unbounded)))
+}
+
+fn column_bounds(meta: &TableInfo, column_name: &str, leaves: &[&Condition]) -> ColumnBounds {
+    let mut bounds: ColumnBounds = ColumnBounds::new();
+    let column = match meta.column_info_from_str(column_name) {
+        Err(_) => return bounds,
+        Ok(column) => column,
+    };
+
+    for leaf in leaves {
+        let is_target: bool = leaf.left.name == column_name
+            && leaf.left.table_name.as_ref().is_none_or(|t| *t == meta.name);
+        if !is_target {
+            continue;
+        }
+
+        match leaf.right {
+            Comparable::Lit(ref lit) => {
+                // keys are stored in the column type, a literal which can not be converted stays a filter
+                let field: Field = lit.clone().into();
+                match column.dtype.coerce(&field) {
+                    None => {}
+                    Some(field) => bounds.add(&leaf.op, field),
+                }
+            }
+            Comparable::List(ref lits) => {
+                let fields: Option<Vec<Field>> = lits
+                    .iter()
+                    .map(|lit| column.dtype.coerce(&lit.clone().into()))
+                    .collect();
+                match fields {
+                    None => {}
+                    Some(fields) => bounds.add_list(&leaf.op, fields),
+                }
+            }
+            Comparable::Target(_) | Comparable::SubQuery(_) => {}
+        }
+    }
+    bounds
+}
+
+fn tighter_low(current: Bound<Field>, other: Bound<Field>) -> Bound<Field> {
+    match (bound_value(&current), bound_value(&other)) {
+        (None, _) => other,
+        (Some(a), Some(b)) if b > a => other,
+        (Some(a), Some(b)) if b == a => match other {
+            Bound::Excluded(_) => other,
+            _ => current,
+        },
+        _ => current,
+    }
+}
+
+fn tighter_high(current: Bound<Field>, other: Bound<Field>) -> Bound<Field> {
+    match (bound_value(&current), bound_value(&other)) {
+        (None, _) => other,
+        (Some(a), Some(b)) if b < a => other,
+        (Some(a), Some(b)) if b == a => match other {
+            Bound::Excluded(_) => other,
+            _ => current,
+        },
+        _ => current,
+    }
+}
+
+fn bound_value(bound: &Bound<Field>) -> Option<Field> {
+    match *bound {
+        Bound::Included(ref field) | Bound::Excluded(ref field) => Some(field.clone()),
+        Bound::Unbounded => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_type::DataType;
+    use meta::column_info::ColumnInfo;
+    use allocators::allocator::Allocator;
+    use parser::parser::Parser;
+    use parser::statement::{Engine, Statement, DML};
+
+    fn gen_table_info() -> TableInfo {
+        let column_defs = &[("id", DataType::Int), ("kubun_id", DataType::Int), ("price", DataType::Float)];
+        let mut table_info: TableInfo = TableInfo {
+            id: 1,
+            name: "shohin".to_string(),
+            columns: column_defs
+                .iter()
+                .enumerate()
+                .map(|(i, &(name, ref dtype))| ColumnInfo {
+                    name: name.to_string(),
+                    dtype: dtype.clone(),
+                    offset: i,
+                    default: None,
+                })
+                .collect(),
+            indices: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            engine: Engine::Memory,
+            next_record_id: Allocator::new(1),
+        };
+        IndexInfo::new(&mut table_info, None, vec!["id"], true, true, IndexType::BTree).unwrap();
+        IndexInfo::new(&mut table_info, Some("kubun_price"), vec!["kubun_id", "price"], false, false, IndexType::BTree).unwrap();
+        IndexInfo::new(&mut table_info, Some("price_hash"), vec!["price"], false, false, IndexType::Hash).unwrap();
+        table_info
+    }
+
+    fn plan(query: &str) -> AccessPath {
+        match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::Select(stmt))) => plan_access_path(&gen_table_info(), &stmt.condition),
+            _ => panic!("unexpected statement"),
+        }
+    }
+
+    #[test]
+    fn test_plan_full_scan() {
+        assert_eq!(plan("select * from shohin"), AccessPath::FullScan);
+        assert_eq!(plan("select * from shohin where price > 100"), AccessPath::FullScan);
+        assert_eq!(plan("select * from shohin where id = 1 or id = 2"), AccessPath::FullScan);
+    }
+
+    #[test]
+    fn test_plan_unique_lookup() {
+        assert_eq!(
+            plan("select * from shohin where kubun_id = 1 and id = 3"),
+            AccessPath::IndexScan(
+                "id".to_string(),
+                Box::new(KeyRange::new(vec![Field::set_i64(3)], Bound::Unbounded, Bound::Unbounded))
+            )
+        );
+    }
+
+    #[test]
+    fn test_plan_range_scan() {
+        assert_eq!(
+            plan("select * from shohin where id > 3 and id >= 2 and id < 10"),
+            AccessPath::IndexScan(
+                "id".to_string(),
+                Box::new(KeyRange::new(vec![], Bound::Excluded(Field::set_i64(3)), Bound::Excluded(Field::set_i64(10))))
+            )
+        );
+
+        // the literal is converted into the type of the indexed column
+        assert_eq!(
+            plan("select * from shohin where kubun_id = 1 and price <= 300"),
+            AccessPath::IndexScan(
+                "kubun_price".to_string(),
+                Box::new(KeyRange::new(vec![Field::set_i64(1)], Bound::Unbounded, Bound::Included(Field::set_f64(300.0))))
+            )
+        );
+    }
+
+    #[test]
+    fn test_plan_in_and_between() {
+        // the values of IN are scanned through the range covering them
+        assert_eq!(
+            plan("select * from shohin where id in ( 5, 2, 8 )"),
+            AccessPath::IndexScan(
+                "id".to_string(),
+                Box::new(KeyRange::new(vec![], Bound::Included(Field::set_i64(2)), Bound::Included(Field::set_i64(8))))
+            )
+        );
+        assert_eq!(
+            plan("select * from shohin where kubun_id in ( 1 ) and price between 100 and 300"),
+            AccessPath::IndexScan(
+                "kubun_price".to_string(),
+                Box::new(KeyRange::new(
+                    vec![Field::set_i64(1)],
+                    Bound::Included(Field::set_f64(100.0)),
+                    Bound::Included(Field::set_f64(300.0))
+                ))
+            )
+        );
+        assert_eq!(plan("select * from shohin where not id in ( 1, 2 )"), AccessPath::FullScan);
+    }
+
+    #[test]
+    fn test_plan_hash_lookup() {
+        assert_eq!(
+            plan("select * from shohin where price = 100"),
+            AccessPath::IndexScan(
+                "price_hash".to_string(),
+                Box::new(KeyRange::new(vec![Field::set_f64(100.0)], Bound::Unbounded, Bound::Unbounded))
+            )
+        );
+    }
+
+    #[test]
+    fn test_plan_index_join() {
+        let outer_columns: Vec<Column> = vec![
+            Column {
+                table_name: "kubun".to_string(),
+                name: "id".to_string(),
+                dtype: DataType::Int,
+                offset: 0,
+            },
+            Column {
+                table_name: "kubun".to_string(),
+                name: "price".to_string(),
+                dtype: DataType::Int,
+                offset: 1,
+            },
+        ];
+        let condition = |query: &str| match Parser::new(query).parse() {
+            Ok(Statement::DML(DML::Select(stmt))) => stmt.condition,
+            _ => panic!("unexpected statement"),
+        };
+
+        assert_eq!(
+            plan_index_join(&outer_columns, &gen_table_info(), &condition("select * from shohin where shohin.kubun_id = kubun.id")),
+            None
+        );
+        assert_eq!(
+            plan_index_join(&outer_columns, &gen_table_info(), &condition("select * from shohin where kubun.price = shohin.price and id = 1")),
+            Some(("price_hash".to_string(), vec![1]))
+        );
+        assert_eq!(
+            plan_index_join(&outer_columns, &gen_table_info(), &condition("select * from shohin where shohin.price = price and shohin.kubun_id = kubun.id")),
+            Some(("price_hash".to_string(), vec![1]))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use data_type::DataType;
+use columns::column::Column;
+use meta::column_info::ColumnInfo;
+use tables::field::{Field, FieldError};
+use tables::tuple::Tuple;
+use parser::token::Literal;
+use parser::statement::*;
+
+// the columns of a select result, in the order of its fields, so that the result can be scanned
+// as a table. an aggregated result has the group keys followed by the aggregates
+pub fn output_columns(inputs: &[Column], stmt: &SelectStmt) -> Vec<ColumnInfo> {
+    let is_aggregated: bool = stmt.targets.iter().any(|target| matches!(*target, Projectable::Aggregate(_)));
+
+    let mut columns: Vec<(String, DataType)> = Vec::new();
+    if is_aggregated {
+        for key in stmt.group_by.iter().flat_map(|keys| keys.iter()) {
+            columns.extend(matching_columns(inputs, key));
+        }
+        for target in stmt.targets.iter() {
+            if let Projectable::Aggregate(ref aggregate) = *target {
+                columns.push(aggregate_column(inputs, aggregate));
+            }
+        }
+    } else {
+        for target in stmt.targets.iter() {
+            match *target {
+                Projectable::Target(ref t) => columns.extend(matching_columns(inputs, t)),
+                Projectable::Lit(ref lit) => columns.push((lit.to_sql(), literal_type(lit))),
+                // the whole tuple replaces the fields projected before it
+                Projectable::All => {
+                    columns = inputs.iter().map(|c| (c.name.clone(), c.dtype.clone())).collect()
+                }
+                // replaced by the columns of extract_scalar_subqueries beforehand
+                Projectable::Aggregate(_) | Projectable::SubQuery(_) => (),
+            }
+        }
+    }
+
+    columns
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, dtype))| ColumnInfo {
+            name: name,
+            dtype: dtype,
+            offset: i,
+            default: None,
+        })
+        .collect()
+}
+
+// the column a scalar subquery of the projection is read from, counted from 1
+pub fn scalar_column_name(nth: usize) -> String {
+    format!("subquery_{}", nth)
+}
+
+// replaces the scalar subqueries of the projection with the columns they are read from,
+// the subqueries are returned in the order of the columns
+pub fn extract_scalar_subqueries(stmt: &SelectStmt) -> (SelectStmt, Vec<SelectStmt>) {
+    let mut stmt: SelectStmt = stmt.clone();
+    let mut subqueries: Vec<SelectStmt> = Vec::new();
+    for target in stmt.targets.iter_mut() {
+        let query: SelectStmt = match *target {
+            Projectable::SubQuery(ref query) => *query.clone(),
+            _ => continue,
+        };
+        subqueries.push(query);
+        *target = Projectable::Target(Target {
+            database_name: None,
+            table_name: None,
+            name: scalar_column_name(subqueries.len()),
+        });
+    }
+    (stmt, subqueries)
+}
+
+// a correlated subquery refers to the columns of the outer query. a column it does not find among
+// its own is looked for in the outer tuple, and replaced by the value there, so that the subquery
+// can be run as any other query. subqueries nested in the conditions are bound too, a column there
+// is the nested one's own or the subquery's before it is the outer query's.
+// an outer field without a value can not be bound, and fails the subquery
+pub fn bind_outer<F, E>(query: &SelectStmt, outer_tuple: &Tuple, outer: &[Column], columns_of: &mut F) -> Result<SelectStmt, E>
+where
+    F: FnMut(&SelectStmt) -> Result<Vec<Column>, E>,
+    E: From<FieldError>,
+{
+    bind_query(query, &[], outer_tuple, outer, columns_of)
+}
+
+// scopes are the columns of the queries between the outer query and this one, innermost first
+fn bind_query<F, E>(
+    query: &SelectStmt,
+    scopes: &[&[Column]],
+    outer_tuple: &Tuple,
+    outer: &[Column],
+    columns_of: &mut F,
+) -> Result<SelectStmt, E>
+where
+    F: FnMut(&SelectStmt) -> Result<Vec<Column>, E>,
+    E: From<FieldError>,
+{
+    let inner: Vec<Column> = try!(columns_of(query));
+    let mut inner_scopes: Vec<&[Column]> = vec![&inner];
+    inner_scopes.extend_from_slice(scopes);
+
+    let mut query: SelectStmt = query.clone();
+    query.condition = match query.condition {
+        Some(condition) => Some(try!(bind_conditions(condition, &inner_scopes, outer_tuple, outer, columns_of))),
+        None => None,
+    };
+    Ok(query)
+}
+
+fn bind_conditions<F, E>(
+    condition: Conditions,
+    scopes: &[&[Column]],
+    outer_tuple: &Tuple,
+    outer: &[Column],
+    columns_of: &mut F,
+) -> Result<Conditions, E>
+where
+    F: FnMut(&SelectStmt) -> Result<Vec<Column>, E>,
+    E: From<FieldError>,
+{
+    Ok(match condition {
+        Conditions::And(c1, c2) => Conditions::And(
+            Box::new(try!(bind_conditions(*c1, scopes, outer_tuple, outer, columns_of))),
+            Box::new(try!(bind_conditions(*c2, scopes, outer_tuple, outer, columns_of))),
+        ),
+        Conditions::Or(c1, c2) => Conditions::Or(
+            Box::new(try!(bind_conditions(*c1, scopes, outer_tuple, outer, columns_of))),
+            Box::new(try!(bind_conditions(*c2, scopes, outer_tuple, outer, columns_of))),
+        ),
+        Conditions::Not(c) => Conditions::Not(Box::new(try!(bind_conditions(*c, scopes, outer_tuple, outer, columns_of)))),
+        Conditions::In(target, query) => Conditions::In(
+            target,
+            Box::new(try!(bind_query(&query, scopes, outer_tuple, outer, columns_of))),
+        ),
+        Conditions::Exists(query) => {
+            Conditions::Exists(Box::new(try!(bind_query(&query, scopes, outer_tuple, outer, columns_of))))
+        }
+        Conditions::Leaf(c) => {
+            let right: Comparable = match c.right {
+                Comparable::Target(t) => match outer_value(&t, scopes, outer_tuple, outer) {
+                    None => Comparable::Target(t),
+                    Some(field) => Comparable::Lit(try!(field.to_literal())),
+                },
+                Comparable::SubQuery(query) => {
+                    Comparable::SubQuery(Box::new(try!(bind_query(&query, scopes, outer_tuple, outer, columns_of))))
+                }
+                right => right,
+            };
+
+            // the sides are swapped to keep the column of the subquery on the left
+            match (outer_value(&c.left, scopes, outer_tuple, outer), right) {
+                (Some(field), Comparable::Target(t)) => Conditions::Leaf(Condition {
+                    left: t,
+                    op: flip(c.op),
+                    right: Comparable::Lit(try!(field.to_literal())),
+                }),
+                (_, right) => Conditions::Leaf(Condition {
+                    left: c.left,
+                    op: c.op,
+                    right: right,
+                }),
+            }
+        }
+    })
+}
+
+fn outer_value(target: &Target, scopes: &[&[Column]], outer_tuple: &Tuple, outer: &[Column]) -> Option<Field> {
+    if scopes.iter().any(|columns| find_column(target, columns).is_some()) {
+        return None;
+    }
+    find_column(target, outer).map(|column| outer_tuple.fields[column.offset].clone())
+}
+
+fn find_column<'c>(target: &Target, columns: &'c [Column]) -> Option<&'c Column> {
+    columns.iter().find(|column| {
+        column.name == target.name
+            && target.table_name.as_ref().is_none_or(|table_name| *table_name == column.table_name)
+    })
+}
+
+fn flip(op: Operator) -> Operator {
+    match op {
+        Operator::GT => Operator::LT,
+        Operator::LT => Operator::GT,
+        Operator::GE => Operator::LE,
+        Operator::LE => Operator::GE,
+        op => op,
+    }
+}
+
+// every column of the inputs which the target names is projected
+fn matching_columns(inputs: &[Column], target: &Target) -> Vec<(String, DataType)> {
+    inputs
+        .iter()
+        .filter(|column| target.table_name.as_ref().is_none_or(|table_name| *table_name == column.table_name))
+        .filter(|column| column.name == target.name)
+        .map(|column| (column.name.clone(), column.dtype.clone()))
+        .collect()
+}
+
+// the aggregate is named after its function and its column, as sum_amount or count_all,
+// since the function names are keywords
+fn aggregate_column(inputs: &[Column], aggregate: &Aggregate) -> (String, DataType) {
+    let (function, aggregatable): (&str, &Aggregatable) = match *aggregate {
+        Aggregate::Count(ref a) => ("count", a),
+        Aggregate::Sum(ref a) => ("sum", a),
+        Aggregate::Average(ref a) => ("avg", a),
+        Aggregate::Max(ref a) => ("max", a),
+        Aggregate::Min(ref a) => ("min", a),
+    };
+    match *aggregatable {
+        Aggregatable::All => (format!("{}_all", function), DataType::Int),
+        Aggregatable::Target(ref t) => {
+            let dtype: DataType = match *aggregate {
+                Aggregate::Count(_) => DataType::Int,
+                _ => matching_columns(inputs, t)
+                    .into_iter()
+                    .next()
+                    .map_or(DataType::Int, |(_, dtype)| dtype),
+            };
+            (format!("{}_{}", function, t.name), dtype)
+        }
+    }
+}
+
+fn literal_type(lit: &Literal) -> DataType {
+    match *lit {
+        Literal::Int(_) => DataType::Int,
+        Literal::Float(_) => DataType::Float,
+        Literal::Bool(_) => DataType::Bool,
+        Literal::String(ref s) => DataType::Char(s.chars().count().min(u8::MAX as usize) as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {}
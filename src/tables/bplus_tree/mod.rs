@@ -0,0 +1,3 @@
+pub mod pager;
+pub mod node;
+pub mod tree;
@@ -0,0 +1,197 @@
+use std::io;
+
+use tables::bplus_tree::pager::{PageId, PAGE_SIZE};
+
+const LEAF: u8 = 1;
+const INTERNAL: u8 = 2;
+
+// node type, number of keys, and the next leaf or the first child
+pub const HEADER_SIZE: usize = 7;
+
+// any two entries have to fit in half of a page, so that a split always succeeds
+pub const MAX_ENTRY_SIZE: usize = (PAGE_SIZE - HEADER_SIZE) / 4 - 8;
+
+// a node which is not the root is merged or refilled below this size
+pub const MIN_NODE_SIZE: usize = PAGE_SIZE / 4;
+
+// a leaf holds the keys with their values, and links to the next leaf for range scans.
+// an internal node holds one more child than keys, the keys of children[i + 1] are >= keys[i]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub is_leaf: bool,
+    pub keys: Vec<Vec<u8>>,
+    pub values: Vec<Vec<u8>>,
+    pub children: Vec<PageId>,
+    pub next: PageId,
+}
+
+impl Node {
+    pub fn new_leaf() -> Node {
+        Node {
+            is_leaf: true,
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+            next: 0,
+        }
+    }
+
+    pub fn new_internal(keys: Vec<Vec<u8>>, children: Vec<PageId>) -> Node {
+        Node {
+            is_leaf: false,
+            keys: keys,
+            values: Vec::new(),
+            children: children,
+            next: 0,
+        }
+    }
+
+    pub fn entry_size(&self, i: usize) -> usize {
+        if self.is_leaf {
+            4 + self.keys[i].len() + self.values[i].len()
+        } else {
+            6 + self.keys[i].len()
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        HEADER_SIZE + (0..self.keys.len()).map(|i| self.entry_size(i)).sum::<usize>()
+    }
+
+    // position of the first key >= the given key, and whether it equals
+    pub fn search(&self, key: &[u8]) -> (usize, bool) {
+        match self.keys.binary_search_by(|k| k[..].cmp(key)) {
+            Ok(i) => (i, true),
+            Err(i) => (i, false),
+        }
+    }
+
+    // index of the child whose keys may contain the given key
+    pub fn child_index(&self, key: &[u8]) -> usize {
+        match self.search(key) {
+            (i, true) => i + 1,
+            (i, false) => i,
+        }
+    }
+
+    // the number of entries moved to the left half when the node is split by bytes
+    pub fn split_point(&self) -> usize {
+        let half: usize = self.size() / 2;
+        let mut filled: usize = HEADER_SIZE;
+        for i in 0..self.keys.len() {
+            filled += self.entry_size(i);
+            if filled >= half {
+                return (i + 1).min(self.keys.len() - 1).max(1);
+            }
+        }
+        self.keys.len() / 2
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut page: Vec<u8> = Vec::with_capacity(PAGE_SIZE);
+        page.push(if self.is_leaf { LEAF } else { INTERNAL });
+        push_u16(&mut page, self.keys.len() as u16);
+        if self.is_leaf {
+            push_u32(&mut page, self.next);
+            for (key, value) in self.keys.iter().zip(self.values.iter()) {
+                push_u16(&mut page, key.len() as u16);
+                page.extend_from_slice(key);
+                push_u16(&mut page, value.len() as u16);
+                page.extend_from_slice(value);
+            }
+        } else {
+            push_u32(&mut page, self.children[0]);
+            for (key, child) in self.keys.iter().zip(self.children[1..].iter()) {
+                push_u16(&mut page, key.len() as u16);
+                page.extend_from_slice(key);
+                push_u32(&mut page, *child);
+            }
+        }
+        page
+    }
+
+    pub fn decode(page: &[u8]) -> io::Result<Node> {
+        let mut pos: usize = 3;
+        let count: usize = read_u16(page, 1) as usize;
+        let mut node: Node = match page[0] {
+            LEAF => Node::new_leaf(),
+            INTERNAL => Node::new_internal(Vec::new(), Vec::new()),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "broken b+tree page")),
+        };
+
+        if node.is_leaf {
+            node.next = read_u32(page, pos);
+            pos += 4;
+            for _ in 0..count {
+                let key: Vec<u8> = try!(read_slice(page, &mut pos));
+                let value: Vec<u8> = try!(read_slice(page, &mut pos));
+                node.keys.push(key);
+                node.values.push(value);
+            }
+        } else {
+            node.children.push(read_u32(page, pos));
+            pos += 4;
+            for _ in 0..count {
+                node.keys.push(try!(read_slice(page, &mut pos)));
+                if pos + 4 > page.len() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "broken b+tree page"));
+                }
+                node.children.push(read_u32(page, pos));
+                pos += 4;
+            }
+        }
+        Ok(node)
+    }
+}
+
+pub fn push_u16(page: &mut Vec<u8>, value: u16) {
+    page.push((value >> 8) as u8);
+    page.push(value as u8);
+}
+
+pub fn push_u32(page: &mut Vec<u8>, value: u32) {
+    for i in 0..4 {
+        page.push((value >> (24 - i * 8)) as u8);
+    }
+}
+
+pub fn read_u16(page: &[u8], pos: usize) -> u16 {
+    ((page[pos] as u16) << 8) | (page[pos + 1] as u16)
+}
+
+pub fn read_u32(page: &[u8], pos: usize) -> u32 {
+    page[pos..pos + 4].iter().fold(0, |value, b| (value << 8) | (*b as u32))
+}
+
+fn read_slice(page: &[u8], pos: &mut usize) -> io::Result<Vec<u8>> {
+    if *pos + 2 > page.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "broken b+tree page"));
+    }
+    let length: usize = read_u16(page, *pos) as usize;
+    *pos += 2;
+    if *pos + length > page.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "broken b+tree page"));
+    }
+    let slice: Vec<u8> = page[*pos..*pos + length].to_vec();
+    *pos += length;
+    Ok(slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_and_decode() {
+        let mut leaf: Node = Node::new_leaf();
+        leaf.keys = vec![vec![1, 2], vec![3]];
+        leaf.values = vec![vec![], vec![9, 9, 9]];
+        leaf.next = 42;
+        assert_eq!(Node::decode(&leaf.encode()).unwrap(), leaf);
+        assert_eq!(leaf.encode().len(), leaf.size());
+
+        let internal: Node = Node::new_internal(vec![vec![5], vec![7, 7]], vec![1, 2, 3]);
+        assert_eq!(Node::decode(&internal.encode()).unwrap(), internal);
+        assert_eq!(internal.encode().len(), internal.size());
+    }
+}
@@ -0,0 +1,58 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+pub const PAGE_SIZE: usize = 4096;
+
+pub type PageId = u32;
+
+// reads and writes fixed-size pages of a single file
+#[derive(Debug)]
+pub struct Pager {
+    file: File,
+    pub page_count: u32,
+}
+
+impl Pager {
+    pub fn open(path: &Path) -> io::Result<Pager> {
+        let file: File = try!(OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path));
+        let length: u64 = try!(file.metadata()).len();
+        Ok(Pager {
+            file: file,
+            page_count: (length / PAGE_SIZE as u64) as u32,
+        })
+    }
+
+    pub fn read(&mut self, page_id: PageId) -> io::Result<Vec<u8>> {
+        let mut page: Vec<u8> = vec![0; PAGE_SIZE];
+        try!(self.file.seek(SeekFrom::Start(page_id as u64 * PAGE_SIZE as u64)));
+        try!(self.file.read_exact(&mut page));
+        Ok(page)
+    }
+
+    pub fn write(&mut self, page_id: PageId, page: &[u8]) -> io::Result<()> {
+        let mut buffer: Vec<u8> = page.to_vec();
+        buffer.resize(PAGE_SIZE, 0);
+        try!(self.file.seek(SeekFrom::Start(page_id as u64 * PAGE_SIZE as u64)));
+        try!(self.file.write_all(&buffer));
+        if page_id >= self.page_count {
+            self.page_count = page_id + 1;
+        }
+        Ok(())
+    }
+
+    // appends a zeroed page to the end of the file
+    pub fn allocate(&mut self) -> io::Result<PageId> {
+        let page_id: PageId = self.page_count;
+        try!(self.write(page_id, &[]));
+        Ok(page_id)
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -0,0 +1,376 @@
+use std::io;
+use std::path::Path;
+
+use tables::bplus_tree::pager::{PageId, Pager, PAGE_SIZE};
+use tables::bplus_tree::node::*;
+
+const MAGIC: &[u8] = b"PSQLBPT1";
+
+// an ordered map of byte keys to byte values stored in the pages of a file.
+// the first page holds the root and the head of the freed pages
+#[derive(Debug)]
+pub struct BPlusTree {
+    pager: Pager,
+    root: PageId,
+    free_head: PageId,
+}
+
+impl BPlusTree {
+    pub fn open(path: &Path) -> io::Result<BPlusTree> {
+        let mut pager: Pager = try!(Pager::open(path));
+        if pager.page_count == 0 {
+            let mut tree: BPlusTree = BPlusTree {
+                pager: pager,
+                root: 1,
+                free_head: 0,
+            };
+            try!(tree.pager.allocate());
+            try!(tree.pager.allocate());
+            try!(tree.write_node(1, &Node::new_leaf()));
+            try!(tree.write_meta());
+            return Ok(tree);
+        }
+
+        let meta: Vec<u8> = try!(pager.read(0));
+        if &meta[..MAGIC.len()] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a b+tree file"));
+        }
+        Ok(BPlusTree {
+            pager: pager,
+            root: read_u32(&meta, MAGIC.len()),
+            free_head: read_u32(&meta, MAGIC.len() + 4),
+        })
+    }
+
+    pub fn get(&mut self, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let leaf: Node = try!(self.find_leaf(key)).1;
+        match leaf.search(key) {
+            (i, true) => Ok(Some(leaf.values[i].clone())),
+            _ => Ok(None),
+        }
+    }
+
+    // replaces the value when the key already exists
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> io::Result<()> {
+        if key.len() + value.len() > MAX_ENTRY_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "b+tree entry is too large"));
+        }
+
+        let root: PageId = self.root;
+        match try!(self.insert_into(root, key, value)) {
+            None => Ok(()),
+            Some((separator, right)) => {
+                let new_root: PageId = try!(self.allocate());
+                try!(self.write_node(new_root, &Node::new_internal(vec![separator], vec![root, right])));
+                self.root = new_root;
+                self.write_meta()
+            }
+        }
+    }
+
+    // returns whether the key existed
+    pub fn remove(&mut self, key: &[u8]) -> io::Result<bool> {
+        let root: PageId = self.root;
+        let removed: bool = try!(self.remove_from(root, key)).0;
+
+        // the root is replaced by its only child once every separator is gone
+        let node: Node = try!(self.read_node(root));
+        if !node.is_leaf && node.keys.is_empty() {
+            self.root = node.children[0];
+            try!(self.free(root));
+            try!(self.write_meta());
+        }
+        Ok(removed)
+    }
+
+    // visits the entries in the order of the keys from the first key >= start,
+    // until the visitor returns false
+    pub fn scan_from<F>(&mut self, start: &[u8], mut visit: F) -> io::Result<()>
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        let (_, mut leaf) = try!(self.find_leaf(start));
+        let mut pos: usize = leaf.search(start).0;
+        loop {
+            while pos < leaf.keys.len() {
+                if !visit(&leaf.keys[pos], &leaf.values[pos]) {
+                    return Ok(());
+                }
+                pos += 1;
+            }
+
+            if leaf.next == 0 {
+                return Ok(());
+            }
+            leaf = try!(self.read_node(leaf.next));
+            pos = 0;
+        }
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.pager.sync()
+    }
+
+    fn find_leaf(&mut self, key: &[u8]) -> io::Result<(PageId, Node)> {
+        let mut page_id: PageId = self.root;
+        loop {
+            let node: Node = try!(self.read_node(page_id));
+            if node.is_leaf {
+                return Ok((page_id, node));
+            }
+            page_id = node.children[node.child_index(key)];
+        }
+    }
+
+    // returns the separator and the page of the new right sibling when the node is split
+    fn insert_into(
+        &mut self,
+        page_id: PageId,
+        key: &[u8],
+        value: &[u8],
+    ) -> io::Result<Option<(Vec<u8>, PageId)>> {
+        let mut node: Node = try!(self.read_node(page_id));
+        if node.is_leaf {
+            match node.search(key) {
+                (i, true) => node.values[i] = value.to_vec(),
+                (i, false) => {
+                    node.keys.insert(i, key.to_vec());
+                    node.values.insert(i, value.to_vec());
+                }
+            }
+        } else {
+            let i: usize = node.child_index(key);
+            match try!(self.insert_into(node.children[i], key, value)) {
+                None => return Ok(None),
+                Some((separator, right)) => {
+                    node.keys.insert(i, separator);
+                    node.children.insert(i + 1, right);
+                }
+            }
+        }
+
+        if node.size() <= PAGE_SIZE {
+            try!(self.write_node(page_id, &node));
+            return Ok(None);
+        }
+
+        let right_id: PageId = try!(self.allocate());
+        let (separator, right): (Vec<u8>, Node) = split(&mut node);
+        if node.is_leaf {
+            node.next = right_id;
+        }
+        try!(self.write_node(page_id, &node));
+        try!(self.write_node(right_id, &right));
+        Ok(Some((separator, right_id)))
+    }
+
+    // returns whether the key existed and whether the node became too small
+    fn remove_from(&mut self, page_id: PageId, key: &[u8]) -> io::Result<(bool, bool)> {
+        let mut node: Node = try!(self.read_node(page_id));
+        let removed: bool = if node.is_leaf {
+            match node.search(key) {
+                (i, true) => {
+                    node.keys.remove(i);
+                    node.values.remove(i);
+                    true
+                }
+                _ => return Ok((false, false)),
+            }
+        } else {
+            let i: usize = node.child_index(key);
+            let (removed, underflow) = try!(self.remove_from(node.children[i], key));
+            if !underflow {
+                return Ok((removed, false));
+            }
+            try!(self.rebalance(&mut node, i));
+            removed
+        };
+
+        try!(self.write_node(page_id, &node));
+        Ok((removed, node.size() < MIN_NODE_SIZE))
+    }
+
+    // merges the small child with a sibling, or moves entries over from the sibling
+    fn rebalance(&mut self, parent: &mut Node, child: usize) -> io::Result<()> {
+        if parent.children.len() < 2 {
+            return Ok(());
+        }
+
+        let left_index: usize = if child > 0 { child - 1 } else { child };
+        let left_id: PageId = parent.children[left_index];
+        let right_id: PageId = parent.children[left_index + 1];
+        let mut merged: Node = try!(self.read_node(left_id));
+        let right: Node = try!(self.read_node(right_id));
+
+        let separator: Vec<u8> = parent.keys[left_index].clone();
+        if merged.is_leaf {
+            merged.keys.extend(right.keys);
+            merged.values.extend(right.values);
+            merged.next = right.next;
+        } else {
+            merged.keys.push(separator);
+            merged.keys.extend(right.keys);
+            merged.children.extend(right.children);
+        }
+
+        if merged.size() <= PAGE_SIZE {
+            parent.keys.remove(left_index);
+            parent.children.remove(left_index + 1);
+            try!(self.write_node(left_id, &merged));
+            return self.free(right_id);
+        }
+
+        let (separator, new_right) = split(&mut merged);
+        if merged.is_leaf {
+            merged.next = right_id;
+        }
+        parent.keys[left_index] = separator;
+        try!(self.write_node(left_id, &merged));
+        self.write_node(right_id, &new_right)
+    }
+
+    fn allocate(&mut self) -> io::Result<PageId> {
+        if self.free_head == 0 {
+            return self.pager.allocate();
+        }
+
+        let page_id: PageId = self.free_head;
+        let page: Vec<u8> = try!(self.pager.read(page_id));
+        self.free_head = read_u32(&page, 0);
+        try!(self.write_meta());
+        Ok(page_id)
+    }
+
+    // freed pages are chained from the meta page
+    fn free(&mut self, page_id: PageId) -> io::Result<()> {
+        let mut page: Vec<u8> = Vec::new();
+        push_u32(&mut page, self.free_head);
+        try!(self.pager.write(page_id, &page));
+        self.free_head = page_id;
+        self.write_meta()
+    }
+
+    fn read_node(&mut self, page_id: PageId) -> io::Result<Node> {
+        Node::decode(&try!(self.pager.read(page_id)))
+    }
+
+    fn write_node(&mut self, page_id: PageId, node: &Node) -> io::Result<()> {
+        self.pager.write(page_id, &node.encode())
+    }
+
+    fn write_meta(&mut self) -> io::Result<()> {
+        let mut page: Vec<u8> = MAGIC.to_vec();
+        push_u32(&mut page, self.root);
+        push_u32(&mut page, self.free_head);
+        self.pager.write(0, &page)
+    }
+}
+
+// splits the node in halves by bytes, and returns the separator with the right half.
+// the separator of leaves is the first key of the right half, while the one of internal nodes moves up
+fn split(node: &mut Node) -> (Vec<u8>, Node) {
+    let at: usize = node.split_point();
+    if node.is_leaf {
+        let mut right: Node = Node::new_leaf();
+        right.keys = node.keys.split_off(at);
+        right.values = node.values.split_off(at);
+        right.next = node.next;
+        (right.keys[0].clone(), right)
+    } else {
+        let right_keys: Vec<Vec<u8>> = node.keys.split_off(at + 1);
+        let separator: Vec<u8> = node.keys.pop().unwrap();
+        let right_children: Vec<PageId> = node.children.split_off(at + 1);
+        (separator, Node::new_internal(right_keys, right_children))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn gen_path(name: &str) -> PathBuf {
+        let path: PathBuf = env::temp_dir().join(format!("puresql_{}_{}.idx", name, ::std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn gen_key(i: u32) -> Vec<u8> {
+        // long keys make the tree a few levels deep
+        let mut key: Vec<u8> = format!("{:08}", i).into_bytes();
+        key.resize(200, b'x');
+        key
+    }
+
+    fn collect(tree: &mut BPlusTree, start: &[u8]) -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = Vec::new();
+        tree.scan_from(start, |key, _| {
+            keys.push(key.to_vec());
+            true
+        }).unwrap();
+        keys
+    }
+
+    #[test]
+    fn test_insert_and_scan() {
+        let path: PathBuf = gen_path("insert");
+        let mut tree: BPlusTree = BPlusTree::open(&path).unwrap();
+        for i in (0..500).rev() {
+            tree.insert(&gen_key(i), &[i as u8]).unwrap();
+        }
+        tree.insert(&gen_key(7), &[70]).unwrap();
+
+        assert_eq!(tree.get(&gen_key(7)).unwrap(), Some(vec![70]));
+        assert_eq!(tree.get(&gen_key(500)).unwrap(), None);
+        let keys: Vec<Vec<u8>> = collect(&mut tree, &gen_key(250));
+        assert_eq!(keys.len(), 250);
+        assert_eq!(keys[0], gen_key(250));
+        assert_eq!(keys[249], gen_key(499));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_remove_merges_pages() {
+        let path: PathBuf = gen_path("remove");
+        let mut tree: BPlusTree = BPlusTree::open(&path).unwrap();
+        for i in 0..500 {
+            tree.insert(&gen_key(i), &[]).unwrap();
+        }
+        let pages: u32 = tree.pager.page_count;
+        for i in 0..500 {
+            if i % 10 != 0 {
+                assert!(tree.remove(&gen_key(i)).unwrap());
+            }
+        }
+        assert!(!tree.remove(&gen_key(1)).unwrap());
+
+        let keys: Vec<Vec<u8>> = collect(&mut tree, &[]);
+        assert_eq!(keys, (0..50).map(|i| gen_key(i * 10)).collect::<Vec<Vec<u8>>>());
+
+        // pages freed by the merges are reused
+        for i in 0..500 {
+            tree.insert(&gen_key(i), &[]).unwrap();
+        }
+        assert_eq!(tree.pager.page_count, pages);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_reopen() {
+        let path: PathBuf = gen_path("reopen");
+        {
+            let mut tree: BPlusTree = BPlusTree::open(&path).unwrap();
+            for i in 0..300 {
+                tree.insert(&gen_key(i), &[1]).unwrap();
+            }
+            tree.sync().unwrap();
+        }
+
+        let mut tree: BPlusTree = BPlusTree::open(&path).unwrap();
+        assert_eq!(tree.get(&gen_key(299)).unwrap(), Some(vec![1]));
+        assert_eq!(collect(&mut tree, &[]).len(), 300);
+        let _ = fs::remove_file(&path);
+    }
+}
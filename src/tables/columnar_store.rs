@@ -0,0 +1,115 @@
+use tables::field::Field;
+use tables::tuple::Tuple;
+use tables::table_store::{StoreError, TableStore};
+
+// keeps every column in its own vector, the i-th value of each column belongs to record_ids[i]
+#[derive(Debug, Clone)]
+pub struct ColumnarStore {
+    pub record_ids: Vec<usize>,
+    pub columns: Vec<Vec<Field>>,
+}
+
+impl ColumnarStore {
+    pub fn new(column_length: usize) -> ColumnarStore {
+        ColumnarStore {
+            record_ids: Vec::new(),
+            columns: vec![Vec::new(); column_length],
+        }
+    }
+
+    fn position(&self, record_id: usize) -> Result<usize, usize> {
+        self.record_ids.binary_search(&record_id)
+    }
+
+    fn tuple_at(&self, pos: usize) -> Tuple {
+        Tuple::new(self.columns.iter().map(|column| column[pos].clone()).collect())
+    }
+}
+
+impl TableStore for ColumnarStore {
+    fn insert(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        let pos: usize = match self.position(record_id) {
+            Ok(_) => return Err(StoreError::DuplicateRecordError(record_id)),
+            Err(pos) => pos,
+        };
+
+        self.record_ids.insert(pos, record_id);
+        for (column, field) in self.columns.iter_mut().zip(tuple.fields) {
+            column.insert(pos, field);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        let pos: usize = match self.position(record_id) {
+            Ok(pos) => pos,
+            Err(_) => return Err(StoreError::RecordNotFoundError(record_id)),
+        };
+
+        for (column, field) in self.columns.iter_mut().zip(tuple.fields) {
+            column[pos] = field;
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        let pos: usize = match self.position(record_id) {
+            Ok(pos) => pos,
+            Err(_) => return Ok(None),
+        };
+
+        self.record_ids.remove(pos);
+        let fields: Vec<Field> = self.columns
+            .iter_mut()
+            .map(|column| column.remove(pos))
+            .collect();
+        Ok(Some(Tuple::new(fields)))
+    }
+
+    fn get(&self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        match self.position(record_id) {
+            Ok(pos) => Ok(Some(self.tuple_at(pos))),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn seek(&self, handle: usize) -> Option<usize> {
+        let pos: usize = match self.position(handle) {
+            Ok(pos) => pos,
+            Err(pos) => pos,
+        };
+        self.record_ids.get(pos).cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.record_ids.len()
+    }
+
+    fn box_clone(&self) -> Box<dyn TableStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columnar_store() {
+        let mut store: ColumnarStore = ColumnarStore::new(2);
+        for i in 1..5 {
+            let tuple: Tuple = Tuple::new(vec![Field::set_i64(i), Field::set_str("apple")]);
+            store.insert(i as usize * 2, tuple).unwrap();
+        }
+        store.update(4, Tuple::new(vec![Field::set_i64(20), Field::set_str("orange")])).unwrap();
+        store.delete(6).unwrap();
+
+        assert_eq!(store.record_ids(), vec![2, 4, 8]);
+        assert_eq!(store.seek(5), Some(8));
+        assert_eq!(
+            store.get(4).unwrap().unwrap().fields,
+            vec![Field::set_i64(20), Field::set_str("orange")]
+        );
+        assert_eq!(store.columns[0], vec![Field::set_i64(1), Field::set_i64(20), Field::set_i64(4)]);
+    }
+}
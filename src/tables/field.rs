@@ -153,6 +153,38 @@ impl Field {
         }
     }
 
+    // numbers of different kinds are compared as f64
+    pub fn compare(&self, other: &Field) -> Option<Ordering> {
+        if self.kind != other.kind && self.aggregatable() && other.aggregatable() {
+            return self.as_f64().partial_cmp(&other.as_f64());
+        }
+        self.compare_value(other)
+    }
+
+    // fields of different kinds can not be compared
+    fn compare_value(&self, other: &Field) -> Option<Ordering> {
+        if self.kind != other.kind {
+            return None;
+        }
+
+        match self.kind {
+            KIND_BOOL => self.get_bool().partial_cmp(&other.get_bool()),
+            KIND_I64 => self.get_i64().partial_cmp(&other.get_i64()),
+            KIND_U64 => self.get_u64().partial_cmp(&other.get_u64()),
+            KIND_F64 => self.get_f64().partial_cmp(&other.get_f64()),
+            KIND_STR => self.get_str().partial_cmp(&other.get_str()),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self.kind {
+            KIND_I64 => self.get_i64() as f64,
+            KIND_U64 => self.get_u64() as f64,
+            _ => self.get_f64(),
+        }
+    }
+
     pub fn to_string(&self) -> String {
         match self.kind {
             KIND_BOOL => self.get_bool().to_string(),
@@ -164,6 +196,26 @@ impl Field {
         }
     }
 
+    // written back as a literal, which gives the same field when it is parsed
+    pub fn to_sql(&self) -> String {
+        match self.to_literal() {
+            Ok(lit) => lit.to_sql(),
+            Err(_) => self.to_string(),
+        }
+    }
+
+    // a field without a value has no literal to be written as
+    pub fn to_literal(&self) -> Result<Literal, FieldError> {
+        match self.kind {
+            KIND_BOOL => Ok(Literal::Bool(self.get_bool() as u8)),
+            KIND_I64 => Ok(Literal::Int(self.get_i64())),
+            KIND_U64 => Ok(Literal::Int(self.get_u64() as i64)),
+            KIND_F64 => Ok(Literal::Float(self.get_f64())),
+            KIND_STR => Ok(Literal::String(self.get_str())),
+            _ => Err(FieldError::NoValueError),
+        }
+    }
+
     pub fn print(&self) {
         match self.kind {
             KIND_BOOL => println!("{}", self.get_bool()),
@@ -212,17 +264,16 @@ impl Hash for Field {
 
 impl PartialOrd for Field {
     fn partial_cmp(&self, other: &Field) -> Option<Ordering> {
-        if self.kind != other.kind {
-            return None;
-        }
+        Some(self.cmp(other))
+    }
+}
 
-        match self.kind {
-            KIND_BOOL => self.get_bool().partial_cmp(&other.get_bool()),
-            KIND_I64 => self.get_i64().partial_cmp(&other.get_i64()),
-            KIND_U64 => self.get_u64().partial_cmp(&other.get_u64()),
-            KIND_F64 => self.get_f64().partial_cmp(&other.get_f64()),
-            KIND_STR => self.get_str().partial_cmp(&other.get_str()),
-            _ => None,
+// total order used for index keys, fields of different kinds are ordered by their kind
+impl Ord for Field {
+    fn cmp(&self, other: &Field) -> Ordering {
+        match self.kind.cmp(&other.kind) {
+            Ordering::Equal => self.compare_value(other).unwrap_or(Ordering::Equal),
+            ordering => ordering,
         }
     }
 }
@@ -281,5 +332,10 @@ impl From<Literal> for Field {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum FieldError {
+    NoValueError,
+}
+
 #[cfg(test)]
 mod tests {}
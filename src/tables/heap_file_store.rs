@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tables::tuple::Tuple;
+use tables::key_encoding::{decode_key, encode_key, from_be_bytes, to_be_bytes};
+use tables::table_store::{StoreError, TableStore};
+
+// the length of a record which deletes the tuple of its record id
+const DELETED: u64 = 0xffff_ffff;
+// and of one which marks the tuple as deleted by a committed transaction
+const ENDED: u64 = 0xffff_fffe;
+
+// appends every written tuple to a file as [record id u64][length u32][encoded fields],
+// every delete as [record id u64][DELETED u32] and every end as [record id u64][ENDED u32].
+// only the offsets of the stored tuples are kept in memory, they are read back from the records
+// when the file is opened again. clones share the same file, so snapshots only roll back the offsets
+#[derive(Debug, Clone)]
+pub struct HeapFileStore {
+    pub file: Arc<Mutex<File>>,
+    pub offsets: BTreeMap<usize, u64>,
+    // the stored tuples which are ended
+    pub ended: BTreeSet<usize>,
+}
+
+impl HeapFileStore {
+    // the file is truncated, a new table starts without rows
+    pub fn create(path: &Path) -> Result<HeapFileStore, StoreError> {
+        let file: File = try!(
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)
+        );
+        Ok(HeapFileStore {
+            file: Arc::new(Mutex::new(file)),
+            offsets: BTreeMap::new(),
+            ended: BTreeSet::new(),
+        })
+    }
+
+    // keeps the tuples written before, the last record of each record id wins
+    pub fn open(path: &Path) -> Result<HeapFileStore, StoreError> {
+        let mut file: File = try!(OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path));
+        let end: u64 = try!(file.seek(SeekFrom::End(0)));
+        let mut offsets: BTreeMap<usize, u64> = BTreeMap::new();
+        let mut ended: BTreeSet<usize> = BTreeSet::new();
+        let mut offset: u64 = try!(file.seek(SeekFrom::Start(0)));
+        while offset < end {
+            let mut header: [u8; 12] = [0; 12];
+            try!(file.read_exact(&mut header));
+            let record_id: usize = from_be_bytes(&header[..8]) as usize;
+            let length: u64 = from_be_bytes(&header[8..]);
+            if length == DELETED {
+                offsets.remove(&record_id);
+                ended.remove(&record_id);
+                offset += 12;
+            } else if length == ENDED {
+                ended.insert(record_id);
+                offset += 12;
+            } else {
+                offsets.insert(record_id, offset + 8);
+                offset = try!(file.seek(SeekFrom::Current(length as i64)));
+            }
+        }
+        Ok(HeapFileStore {
+            file: Arc::new(Mutex::new(file)),
+            offsets: offsets,
+            ended: ended,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, File> {
+        match self.file.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    // returns the offset of the length, where the tuple is read from
+    fn append(&mut self, record_id: usize, tuple: &Tuple) -> Result<u64, StoreError> {
+        let encoded: Vec<u8> = encode_key(&tuple.fields);
+        let mut file: MutexGuard<'_, File> = self.lock();
+        let offset: u64 = try!(file.seek(SeekFrom::End(0)));
+        try!(file.write_all(&to_be_bytes(record_id as u64)));
+        try!(file.write_all(&to_be_bytes(encoded.len() as u64)[4..]));
+        try!(file.write_all(&encoded));
+        Ok(offset + 8)
+    }
+
+    fn append_marker(&mut self, record_id: usize, marker: u64) -> Result<(), StoreError> {
+        let mut file: MutexGuard<'_, File> = self.lock();
+        try!(file.seek(SeekFrom::End(0)));
+        try!(file.write_all(&to_be_bytes(record_id as u64)));
+        try!(file.write_all(&to_be_bytes(marker)[4..]));
+        Ok(())
+    }
+
+    fn read_at(&self, offset: u64) -> Result<Tuple, StoreError> {
+        let mut file: MutexGuard<'_, File> = self.lock();
+        try!(file.seek(SeekFrom::Start(offset)));
+        let mut length: [u8; 4] = [0; 4];
+        try!(file.read_exact(&mut length));
+        let mut encoded: Vec<u8> = vec![0; from_be_bytes(&length) as usize];
+        try!(file.read_exact(&mut encoded));
+
+        match decode_key(&encoded) {
+            Ok(fields) => Ok(Tuple::new(fields)),
+            Err(_) => Err(StoreError::IoError(format!("broken record at {}", offset))),
+        }
+    }
+}
+
+impl TableStore for HeapFileStore {
+    fn insert(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        if self.offsets.contains_key(&record_id) {
+            return Err(StoreError::DuplicateRecordError(record_id));
+        }
+        let offset: u64 = try!(self.append(record_id, &tuple));
+        self.offsets.insert(record_id, offset);
+        Ok(())
+    }
+
+    // the old record is left in the file and just becomes unreachable
+    fn update(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        if !self.offsets.contains_key(&record_id) {
+            return Err(StoreError::RecordNotFoundError(record_id));
+        }
+        let offset: u64 = try!(self.append(record_id, &tuple));
+        self.offsets.insert(record_id, offset);
+        Ok(())
+    }
+
+    fn delete(&mut self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        let tuple: Option<Tuple> = try!(self.get(record_id));
+        if tuple.is_some() {
+            try!(self.append_marker(record_id, DELETED));
+        }
+        self.offsets.remove(&record_id);
+        self.ended.remove(&record_id);
+        Ok(tuple)
+    }
+
+    fn end(&mut self, record_id: usize) -> Result<(), StoreError> {
+        if !self.offsets.contains_key(&record_id) {
+            return Err(StoreError::RecordNotFoundError(record_id));
+        }
+        try!(self.append_marker(record_id, ENDED));
+        self.ended.insert(record_id);
+        Ok(())
+    }
+
+    fn get(&self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        match self.offsets.get(&record_id) {
+            None => Ok(None),
+            Some(offset) => Ok(Some(try!(self.read_at(*offset)))),
+        }
+    }
+
+    fn seek(&self, handle: usize) -> Option<usize> {
+        self.offsets.range(handle..).next().map(|(record_id, _)| *record_id)
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn box_clone(&self) -> Box<dyn TableStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use tables::field::Field;
+
+    #[test]
+    fn test_heap_file_store() {
+        let path: PathBuf = env::temp_dir().join(format!("puresql_heap_{}.heap", ::std::process::id()));
+        let mut store: HeapFileStore = HeapFileStore::create(&path).unwrap();
+        for i in 0..10 {
+            let tuple: Tuple = Tuple::new(vec![Field::set_i64(i), Field::set_str("apple")]);
+            store.insert(i as usize, tuple).unwrap();
+        }
+        store.update(3, Tuple::new(vec![Field::set_i64(30), Field::set_str("orange")])).unwrap();
+        let removed: Option<Tuple> = store.delete(4).unwrap();
+
+        assert_eq!(removed.unwrap().fields, vec![Field::set_i64(4), Field::set_str("apple")]);
+        assert_eq!(store.len(), 9);
+        assert_eq!(store.seek(4), Some(5));
+        assert_eq!(
+            store.get(3).unwrap().unwrap().fields,
+            vec![Field::set_i64(30), Field::set_str("orange")]
+        );
+        assert_eq!(store.scan().unwrap().len(), 9);
+
+        // the file is read back as it was left, and truncated only when created again
+        let reopened: HeapFileStore = HeapFileStore::open(&path).unwrap();
+        let rows = |store: &HeapFileStore| -> Vec<(usize, Vec<Field>)> {
+            store.scan().unwrap().into_iter().map(|(record_id, tuple)| (record_id, tuple.fields)).collect()
+        };
+        assert_eq!(rows(&reopened), rows(&store));
+        assert!(reopened.ended.is_empty());
+
+        // an ended tuple is still stored, and known to be ended when read back
+        store.end(5).unwrap();
+        let reopened: HeapFileStore = HeapFileStore::open(&path).unwrap();
+        assert_eq!(reopened.len(), 9);
+        assert_eq!(reopened.ended.iter().cloned().collect::<Vec<usize>>(), vec![5]);
+        assert_eq!(HeapFileStore::create(&path).unwrap().len(), 0);
+        assert_eq!(HeapFileStore::open(&path).unwrap().len(), 0);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,30 +1,317 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::Bound::{Included, Unbounded};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use Field;
+use columns::range::KeyRange;
 use meta::index_info::IndexInfo;
+use parser::statement::IndexType;
+use tables::bplus_tree::tree::BPlusTree;
+use tables::key_encoding::{decode_key, encode_key, from_be_bytes, to_be_bytes};
 
-#[derive(Debug)]
-pub struct Index<T> {
-    // only adapt BTree index
-    // TODO: impl BTree+, Range, Hash index
-    pub tree: BTreeMap<usize, T>,
+// a key with the record ids stored under it
+pub type Entry = (Vec<Field>, Vec<usize>);
+
+#[derive(Debug, Clone)]
+pub enum IndexTree {
+    BTree(BTreeMap<Vec<Field>, Vec<usize>>),
+    // supports only the lookups by the whole key
+    Hash(HashMap<Vec<Field>, Vec<usize>>),
+    // every record id is stored as its own entry, the encoded key followed by the id,
+    // clones share the same file
+    Disk(Arc<Mutex<BPlusTree>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Index {
+    pub tree: IndexTree,
     pub meta: IndexInfo,
 }
 
-impl<T> Index<T> {
-    pub fn new(index_info: IndexInfo) -> Index<T> {
+impl Index {
+    pub fn new(index_info: IndexInfo) -> Index {
+        let tree: IndexTree = match index_info.index_type {
+            IndexType::BTree => IndexTree::BTree(BTreeMap::new()),
+            IndexType::Hash => IndexTree::Hash(HashMap::new()),
+        };
+
         Index {
-            tree: BTreeMap::new(),
+            tree: tree,
+            meta: index_info,
+        }
+    }
+
+    // an ordered index kept in a B+tree file, which is created when it does not exist
+    pub fn open(index_info: IndexInfo, path: &Path) -> Result<Index, IndexError> {
+        let tree: BPlusTree = try!(BPlusTree::open(path));
+        Ok(Index {
+            tree: IndexTree::Disk(Arc::new(Mutex::new(tree))),
             meta: index_info,
+        })
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.meta.is_pk_index || self.meta.is_unique
+    }
+
+    pub fn extract_key(&self, fields: &[Field]) -> Vec<Field> {
+        self.meta
+            .columns
+            .iter()
+            .map(|column| fields[column.offset].clone())
+            .collect()
+    }
+
+    pub fn lookup(&self, key: &[Field]) -> Result<Vec<usize>, IndexError> {
+        match self.tree {
+            IndexTree::BTree(ref tree) => Ok(tree.get(key).cloned().unwrap_or_default()),
+            IndexTree::Hash(ref tree) => Ok(tree.get(key).cloned().unwrap_or_default()),
+            IndexTree::Disk(ref tree) => {
+                let encoded: Vec<u8> = encode_key(key);
+                let mut record_ids: Vec<usize> = Vec::new();
+                try!(lock(tree).scan_from(&encoded, |entry, _| {
+                    let is_same_key: bool = entry.len() == encoded.len() + 8 && entry.starts_with(&encoded);
+                    if is_same_key {
+                        record_ids.push(from_be_bytes(&entry[encoded.len()..]) as usize);
+                    }
+                    is_same_key
+                }));
+                Ok(record_ids)
+            }
         }
     }
 
-    // TODO: IMPL some response as API
-    pub fn insert(&mut self, internal_id: usize, value: T) {
-        if !self.tree.contains_key(&internal_id) {
-            &mut self.tree.insert(internal_id, value);
+    // every key with its record ids, sorted by the key
+    pub fn entries(&self) -> Result<Vec<Entry>, IndexError> {
+        let mut entries: Vec<Entry> = match self.tree {
+            IndexTree::BTree(ref tree) => tree.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            IndexTree::Hash(ref tree) => tree.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            IndexTree::Disk(_) => return self.scan_disk(&KeyRange::new(vec![], Unbounded, Unbounded)),
+        };
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+
+    pub fn insert(&mut self, fields: &[Field], internal_id: usize) -> Result<(), IndexError> {
+        let key: Vec<Field> = self.extract_key(fields);
+        let record_ids: &mut Vec<usize> = match self.tree {
+            IndexTree::BTree(ref mut tree) => tree.entry(key).or_insert_with(Vec::new),
+            IndexTree::Hash(ref mut tree) => tree.entry(key).or_insert_with(Vec::new),
+            IndexTree::Disk(ref tree) => {
+                try!(lock(tree).insert(&disk_entry(&key, internal_id), &[]));
+                return Ok(());
+            }
+        };
+        if !record_ids.contains(&internal_id) {
+            record_ids.push(internal_id);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, fields: &[Field], internal_id: usize) -> Result<(), IndexError> {
+        let key: Vec<Field> = self.extract_key(fields);
+        let found: Option<&mut Vec<usize>> = match self.tree {
+            IndexTree::BTree(ref mut tree) => tree.get_mut(&key),
+            IndexTree::Hash(ref mut tree) => tree.get_mut(&key),
+            IndexTree::Disk(ref tree) => {
+                try!(lock(tree).remove(&disk_entry(&key, internal_id)));
+                return Ok(());
+            }
+        };
+        let is_empty: bool = match found {
+            None => return Ok(()),
+            Some(record_ids) => {
+                record_ids.retain(|id| *id != internal_id);
+                record_ids.is_empty()
+            }
+        };
+
+        if is_empty {
+            match self.tree {
+                IndexTree::BTree(ref mut tree) => {
+                    tree.remove(&key);
+                }
+                IndexTree::Hash(ref mut tree) => {
+                    tree.remove(&key);
+                }
+                IndexTree::Disk(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    // record ids of the keys in the range, in the order of the keys
+    pub fn scan(&self, range: &KeyRange) -> Result<Vec<usize>, IndexError> {
+        let mut record_ids: Vec<usize> = Vec::new();
+        match self.tree {
+            IndexTree::BTree(ref tree) => for (key, ids) in tree.range((Included(range.start_key()), Unbounded)) {
+                if range.is_passed(key) {
+                    break;
+                }
+                if range.contains(key) {
+                    record_ids.extend(ids.iter().cloned());
+                }
+            },
+            IndexTree::Hash(_) => {
+                if range.prefix.len() == self.meta.columns.len() {
+                    return self.lookup(&range.prefix);
+                }
+                for (key, ids) in try!(self.entries()) {
+                    if range.contains(&key) {
+                        record_ids.extend(ids);
+                    }
+                }
+            }
+            IndexTree::Disk(_) => {
+                for (_, ids) in try!(self.scan_disk(range)) {
+                    record_ids.extend(ids);
+                }
+            }
         }
+        Ok(record_ids)
+    }
+
+    fn scan_disk(&self, range: &KeyRange) -> Result<Vec<Entry>, IndexError> {
+        let tree: &Arc<Mutex<BPlusTree>> = match self.tree {
+            IndexTree::Disk(ref tree) => tree,
+            _ => return Ok(Vec::new()),
+        };
+
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut broken: bool = false;
+        try!(lock(tree).scan_from(&encode_key(&range.start_key()), |entry, _| {
+            let (encoded, id) = entry.split_at(entry.len() - 8);
+            let key: Vec<Field> = match decode_key(encoded) {
+                Ok(key) => key,
+                Err(_) => {
+                    broken = true;
+                    return false;
+                }
+            };
+            if range.is_passed(&key) {
+                return false;
+            }
+
+            if range.contains(&key) {
+                let record_id: usize = from_be_bytes(id) as usize;
+                let is_last_key: bool = entries.last().is_some_and(|last| last.0 == key);
+                if is_last_key {
+                    entries.last_mut().unwrap().1.push(record_id);
+                } else {
+                    entries.push((key, vec![record_id]));
+                }
+            }
+            true
+        }));
+
+        if broken {
+            return Err(IndexError::StorageError("broken index key".to_string()));
+        }
+        Ok(entries)
+    }
+
+    // checks that writing the given rows keeps every key unique among the stored records which are taken,
+    // rows already stored under the same record id are treated as overwritten
+    pub fn validate_unique(&self, rows: &[(usize, Vec<Field>)], is_taken: &dyn Fn(usize) -> bool) -> Result<(), IndexError> {
+        if !self.is_unique() {
+            return Ok(());
+        }
+
+        let written_ids: HashSet<usize> = rows.iter().map(|row| row.0).collect();
+        let mut written_keys: HashSet<Vec<Field>> = HashSet::new();
+        for (_, fields) in rows {
+            let key: Vec<Field> = self.extract_key(fields);
+            let is_taken: bool = try!(self.lookup(&key))
+                .iter()
+                .any(|id| !written_ids.contains(id) && is_taken(*id));
+
+            if is_taken || written_keys.contains(&key) {
+                return Err(IndexError::DuplicateKeyError(self.meta.name.clone(), key));
+            }
+            written_keys.insert(key);
+        }
+        Ok(())
+    }
+}
+
+fn disk_entry(key: &[Field], internal_id: usize) -> Vec<u8> {
+    let mut entry: Vec<u8> = encode_key(key);
+    entry.extend_from_slice(&to_be_bytes(internal_id as u64));
+    entry
+}
+
+fn lock(tree: &Arc<Mutex<BPlusTree>>) -> MutexGuard<'_, BPlusTree> {
+    match tree.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IndexError {
+    DuplicateKeyError(String, Vec<Field>),
+    StorageError(String),
+}
+
+impl From<io::Error> for IndexError {
+    fn from(err: io::Error) -> IndexError {
+        IndexError::StorageError(err.to_string())
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::collections::Bound;
+    use data_type::DataType;
+    use meta::column_info::ColumnInfo;
+
+    fn gen_index_info() -> IndexInfo {
+        IndexInfo {
+            id: 1,
+            name: "price".to_string(),
+            table_name: "shohin".to_string(),
+            columns: vec![ColumnInfo {
+                name: "price".to_string(),
+                dtype: DataType::Int,
+                offset: 1,
+                default: None,
+            }],
+            is_pk_index: false,
+            is_unique: false,
+            index_type: IndexType::BTree,
+        }
+    }
+
+    #[test]
+    fn test_disk_index() {
+        let path: PathBuf = env::temp_dir().join(format!("puresql_index_{}.idx", ::std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut index: Index = Index::open(gen_index_info(), &path).unwrap();
+        for i in 0..100 {
+            let row: Vec<Field> = vec![Field::set_i64(i), Field::set_i64(i % 10)];
+            index.insert(&row, i as usize).unwrap();
+        }
+        index.remove(&[Field::set_i64(3), Field::set_i64(3)], 3).unwrap();
+
+        assert_eq!(index.lookup(&[Field::set_i64(3)]), Ok(vec![13, 23, 33, 43, 53, 63, 73, 83, 93]));
+        let range: KeyRange = KeyRange::new(
+            vec![],
+            Bound::Excluded(Field::set_i64(7)),
+            Bound::Unbounded,
+        );
+        assert_eq!(index.scan(&range).unwrap().len(), 20);
+        assert_eq!(index.entries().unwrap().len(), 10);
+
+        drop(index);
+        let index: Index = Index::open(gen_index_info(), &path).unwrap();
+        assert_eq!(index.lookup(&[Field::set_i64(9)]).unwrap().len(), 10);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,141 @@
+use tables::field::*;
+
+// encodes fields into bytes which compare in the same order as the fields themselves,
+// every field starts with its kind so fields of different kinds are ordered by the kind
+pub fn encode_key(fields: &[Field]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for field in fields {
+        bytes.push(field.kind as u8);
+        match field.kind {
+            KIND_BOOL => bytes.push(if field.get_bool() { 1 } else { 0 }),
+            KIND_I64 => bytes.extend_from_slice(&to_be_bytes((field.get_i64() as u64) ^ (1 << 63))),
+            KIND_U64 => bytes.extend_from_slice(&to_be_bytes(field.get_u64())),
+            KIND_F64 => {
+                let bits: u64 = field.get_f64().to_bits();
+                let ordered: u64 = if bits >> 63 == 1 { !bits } else { bits ^ (1 << 63) };
+                bytes.extend_from_slice(&to_be_bytes(ordered));
+            }
+            KIND_STR => {
+                // zero bytes are escaped so that the terminator sorts before any character
+                for b in field.get_str().as_bytes() {
+                    bytes.push(*b);
+                    if *b == 0 {
+                        bytes.push(0xff);
+                    }
+                }
+                bytes.push(0);
+                bytes.push(0);
+            }
+            _ => {}
+        }
+    }
+    bytes
+}
+
+pub fn decode_key(bytes: &[u8]) -> Result<Vec<Field>, KeyEncodingError> {
+    let mut fields: Vec<Field> = Vec::new();
+    let mut pos: usize = 0;
+    while pos < bytes.len() {
+        let kind: usize = bytes[pos] as usize;
+        pos += 1;
+        match kind {
+            INIT => fields.push(Field::set_init()),
+            KIND_BOOL => {
+                fields.push(Field::set_bool(try!(read_bytes(bytes, &mut pos, 1))[0] == 1));
+            }
+            KIND_I64 => {
+                let value: u64 = from_be_bytes(try!(read_bytes(bytes, &mut pos, 8)));
+                fields.push(Field::set_i64((value ^ (1 << 63)) as i64));
+            }
+            KIND_U64 => {
+                fields.push(Field::set_u64(from_be_bytes(try!(read_bytes(bytes, &mut pos, 8)))));
+            }
+            KIND_F64 => {
+                let ordered: u64 = from_be_bytes(try!(read_bytes(bytes, &mut pos, 8)));
+                let bits: u64 = if ordered >> 63 == 1 { ordered ^ (1 << 63) } else { !ordered };
+                fields.push(Field::set_f64(f64::from_bits(bits)));
+            }
+            KIND_STR => {
+                let mut raw: Vec<u8> = Vec::new();
+                loop {
+                    let b: u8 = try!(read_bytes(bytes, &mut pos, 1))[0];
+                    if b != 0 {
+                        raw.push(b);
+                        continue;
+                    }
+                    match try!(read_bytes(bytes, &mut pos, 1))[0] {
+                        0 => break,
+                        _ => raw.push(0),
+                    }
+                }
+                match String::from_utf8(raw) {
+                    Ok(s) => fields.push(Field::set_str(&s)),
+                    Err(_) => return Err(KeyEncodingError::InvalidKeyError),
+                }
+            }
+            _ => return Err(KeyEncodingError::InvalidKeyError),
+        }
+    }
+    Ok(fields)
+}
+
+pub fn to_be_bytes(value: u64) -> [u8; 8] {
+    let mut bytes: [u8; 8] = [0; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (value >> (56 - i * 8)) as u8;
+    }
+    bytes
+}
+
+pub fn from_be_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, b| (value << 8) | (*b as u64))
+}
+
+fn read_bytes<'b>(bytes: &'b [u8], pos: &mut usize, length: usize) -> Result<&'b [u8], KeyEncodingError> {
+    if *pos + length > bytes.len() {
+        return Err(KeyEncodingError::InvalidKeyError);
+    }
+    let read: &[u8] = &bytes[*pos..*pos + length];
+    *pos += length;
+    Ok(read)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum KeyEncodingError {
+    InvalidKeyError,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_encoding_keeps_order() {
+        let keys: Vec<Vec<Field>> = vec![
+            vec![Field::set_bool(false)],
+            vec![Field::set_bool(true)],
+            vec![Field::set_i64(-300)],
+            vec![Field::set_i64(-1)],
+            vec![Field::set_i64(0)],
+            vec![Field::set_i64(2)],
+            vec![Field::set_f64(-2.5)],
+            vec![Field::set_f64(-0.5)],
+            vec![Field::set_f64(1.5)],
+            vec![Field::set_f64(300.0)],
+            vec![Field::set_str("")],
+            vec![Field::set_str("a\u{0}b")],
+            vec![Field::set_str("ab")],
+            vec![Field::set_str("ab"), Field::set_i64(1)],
+            vec![Field::set_str("b")],
+        ];
+
+        for pair in keys.windows(2) {
+            assert_eq!(pair[0].cmp(&pair[1]), Ordering::Less);
+            assert!(encode_key(&pair[0]) < encode_key(&pair[1]));
+        }
+        for key in keys {
+            assert_eq!(decode_key(&encode_key(&key)), Ok(key));
+        }
+    }
+}
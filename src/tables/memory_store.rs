@@ -0,0 +1,94 @@
+use std::collections::Bound::{Included, Unbounded};
+use std::collections::BTreeMap;
+
+use tables::tuple::Tuple;
+use tables::table_store::{StoreError, TableStore};
+
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    pub tree: BTreeMap<usize, Tuple>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore {
+            tree: BTreeMap::new(),
+        }
+    }
+}
+
+impl TableStore for MemoryStore {
+    fn insert(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        if self.tree.contains_key(&record_id) {
+            return Err(StoreError::DuplicateRecordError(record_id));
+        }
+        self.tree.insert(record_id, tuple);
+        Ok(())
+    }
+
+    fn update(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError> {
+        match self.tree.get_mut(&record_id) {
+            None => Err(StoreError::RecordNotFoundError(record_id)),
+            Some(stored) => {
+                *stored = tuple;
+                Ok(())
+            }
+        }
+    }
+
+    fn delete(&mut self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        Ok(self.tree.remove(&record_id))
+    }
+
+    fn get(&self, record_id: usize) -> Result<Option<Tuple>, StoreError> {
+        Ok(self.tree.get(&record_id).cloned())
+    }
+
+    // record ids are sparse after deletes, so the next one is searched in the tree
+    fn seek(&self, current_handle: usize) -> Option<usize> {
+        self.tree
+            .range((Included(current_handle), Unbounded))
+            .next()
+            .map(|(record_id, _)| *record_id)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn record_ids(&self) -> Vec<usize> {
+        self.tree.keys().cloned().collect()
+    }
+
+    fn scan(&self) -> Result<Vec<(usize, Tuple)>, StoreError> {
+        Ok(self.tree.iter().map(|(record_id, tuple)| (*record_id, tuple.clone())).collect())
+    }
+
+    fn box_clone(&self) -> Box<dyn TableStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tables::field::Field;
+
+    #[test]
+    fn test_seek_sparse_record_ids() {
+        let mut store: MemoryStore = MemoryStore::new();
+        for i in 1..100001 {
+            store.insert(i, Tuple::new(vec![Field::set_u64(i as u64)])).unwrap();
+        }
+        for i in 1..99991 {
+            store.delete(i).unwrap();
+        }
+
+        // only the ids above len() are left
+        assert_eq!(store.len(), 10);
+        assert_eq!(store.seek(0), Some(99991));
+        assert_eq!(store.seek(99995), Some(99995));
+        assert_eq!(store.seek(100001), None);
+        assert_eq!(store.record_ids(), (99991..100001).collect::<Vec<usize>>());
+    }
+}
@@ -1,5 +1,10 @@
 pub mod field;
 pub mod tuple;
 pub mod index;
+pub mod key_encoding;
+pub mod bplus_tree;
+pub mod table_store;
+pub mod memory_store;
+pub mod heap_file_store;
+pub mod columnar_store;
 pub mod table;
-pub mod memory_table;
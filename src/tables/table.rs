@@ -1,97 +1,443 @@
-use std::collections::Bound::Included;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use columns::column::Column;
 use Field;
 use Tuple;
-use Index;
-use meta::table_info::TableInfo;
+use tables::index::{Index, IndexError};
+use tables::table_store::{StoreError, TableStore};
+use tables::memory_store::MemoryStore;
+use tables::heap_file_store::HeapFileStore;
+use tables::columnar_store::ColumnarStore;
+use meta::table_info::{TableInfo, TableInfoError};
+use meta::index_info::IndexInfo;
+use parser::statement::{Engine, IndexType};
+use meta::column_info::ColumnInfo;
+use allocators::allocator::Allocator;
+use transaction::{Snapshot, TransactionError, TxId, Version, FROZEN_TXID};
 
-// TODO: rm pk_index and generalize impl of index
-// index<T> might be usize or RID for indexed data on disk
-#[derive(Debug)]
-pub struct Table<'t> {
+#[derive(Debug, Clone)]
+pub struct Table {
     pub id: usize,
     pub name: String,
     pub columns: Vec<Column>,
-    pub pk_index: Option<Index<Tuple>>,
-    pub indices: Vec<Index<usize>>,
-    pub meta: &'t mut TableInfo,
+    pub meta: TableInfo,
+    pub store: Box<dyn TableStore>,
+    pub indices: Vec<Index>,
+    // every stored record is a version of a row
+    pub versions: BTreeMap<usize, Version>,
+    // record ids of the ended versions which are not reclaimed yet
+    pub dead: Vec<usize>,
+    // where the b-tree indices are put as files, none when the rows are kept in memory
+    pub data_dir: Option<PathBuf>,
 }
 
-impl<'t> Table<'t> {
-    pub fn new(meta: &'t mut TableInfo) -> Table<'t> {
+impl Table {
+    pub fn new(meta: TableInfo, store: Box<dyn TableStore>) -> Table {
         let mut columns: Vec<Column> = Vec::new();
         for column_info in &meta.columns {
             columns.push(column_info.to_column(&meta.name));
         }
 
-        let mut pk_index: Option<Index<Tuple>> = None;
-        let mut indices: Vec<Index<usize>> = Vec::new();
-        for index_info in &meta.indices {
-            if index_info.is_pk_index {
-                pk_index = Some(Index::new(index_info.clone()));
-            } else {
-                indices.push(Index::new(index_info.clone()));
-            }
-        }
+        let indices: Vec<Index> = meta.indices
+            .iter()
+            .map(|index_info| Index::new(index_info.clone()))
+            .collect();
 
         Table {
             id: meta.id,
             name: meta.name.clone(),
             columns: columns,
-            pk_index: pk_index,
+            store: store,
             indices: indices,
+            versions: BTreeMap::new(),
+            dead: Vec::new(),
+            data_dir: None,
             meta: meta,
         }
     }
 
-    // TODO: IMPL some response as API
-    pub fn insert(&mut self, fields: Vec<Field>) {
-        let internal_id: usize = self.meta.next_record_id.base;
-        let tuple: Tuple = Tuple::new(fields.clone());
-        match self.pk_index {
-            None => {}
-            Some(ref mut idx) => idx.insert(internal_id, tuple),
+    // creates the store of the engine in the meta, files are put under the data directory
+    // and emptied, a new table starts without rows
+    pub fn create(meta: TableInfo, data_dir: &Path) -> Result<Table, TableError> {
+        let store: Box<dyn TableStore> = match meta.engine {
+            Engine::Memory => Box::new(MemoryStore::new()),
+            Engine::Heap => {
+                let store: HeapFileStore = try!(HeapFileStore::create(&heap_path(&meta, data_dir)));
+                return Table::on_disk(meta, Box::new(store), data_dir, true);
+            }
+            Engine::Columnar => Box::new(ColumnarStore::new(meta.columns.len())),
+        };
+        Ok(Table::new(meta, store))
+    }
+
+    // opens the table again with the rows left in its files, as committed before any running
+    // transaction. the b-tree index files are used as they are, the other indices are built
+    // again from the rows
+    pub fn open(meta: TableInfo, data_dir: &Path) -> Result<Table, TableError> {
+        let store: HeapFileStore = match meta.engine {
+            Engine::Heap => try!(HeapFileStore::open(&heap_path(&meta, data_dir))),
+            _ => return Table::create(meta, data_dir),
+        };
+        let ended: BTreeSet<usize> = store.ended.clone();
+        let kept: Vec<String> = meta.indices
+            .iter()
+            .filter(|index_info| index_info.index_type == IndexType::BTree)
+            .filter(|index_info| index_path(&meta, index_info, data_dir).exists())
+            .map(|index_info| index_info.name.clone())
+            .collect();
+
+        let mut table: Table = try!(Table::on_disk(meta, Box::new(store), data_dir, false));
+        for (record_id, tuple) in try!(table.store.scan()) {
+            for index in table.indices.iter_mut().filter(|index| !kept.contains(&index.meta.name)) {
+                try!(index.insert(&tuple.fields, record_id));
+            }
+            // the rows deleted by a commit are left to the vacuum
+            let mut version: Version = Version::new(FROZEN_TXID);
+            if ended.contains(&record_id) {
+                version.end = Some(FROZEN_TXID);
+                table.dead.push(record_id);
+            }
+            table.versions.insert(record_id, version);
+            if record_id >= table.meta.next_record_id.base {
+                table.meta.next_record_id = Allocator::new(record_id + 1);
+            }
+        }
+        Ok(table)
+    }
+
+    fn on_disk(meta: TableInfo, store: Box<dyn TableStore>, data_dir: &Path, is_new: bool) -> Result<Table, TableError> {
+        let mut table: Table = Table::new(meta, store);
+        table.data_dir = Some(data_dir.to_path_buf());
+        table.indices = try!(
+            table
+                .meta
+                .indices
+                .iter()
+                .map(|index_info| table.new_index(index_info.clone(), is_new))
+                .collect()
+        );
+        Ok(table)
+    }
+
+    // a b-tree index of a table in files is a B+tree file next to the table. the file left by
+    // an index of the same name is emptied for a new one, and kept when the table is opened again
+    fn new_index(&self, index_info: IndexInfo, is_new: bool) -> Result<Index, TableError> {
+        match self.data_dir {
+            Some(ref data_dir) if index_info.index_type == IndexType::BTree => {
+                let path: PathBuf = index_path(&self.meta, &index_info, data_dir);
+                if is_new && path.exists() {
+                    try!(fs::remove_file(&path).map_err(IndexError::from));
+                }
+                Ok(try!(Index::open(index_info, &path)))
+            }
+            _ => Ok(Index::new(index_info)),
+        }
+    }
+
+    pub fn insert(&mut self, fields: Vec<Field>, snapshot: &Snapshot) -> Result<usize, TableError> {
+        let record_ids: Vec<usize> = try!(self.insert_all(vec![fields], snapshot));
+        Ok(record_ids[0])
+    }
+
+    // either all rows are stored or none of them, each as a new version written by the transaction
+    pub fn insert_all(&mut self, rows: Vec<Vec<Field>>, snapshot: &Snapshot) -> Result<Vec<usize>, TableError> {
+        let base: usize = self.meta.next_record_id.base;
+        let rows: Vec<(usize, Vec<Field>)> = rows.into_iter()
+            .enumerate()
+            .map(|(i, fields)| (base + i, fields))
+            .collect();
+        try!(self.validate_checks(&rows));
+        try!(self.validate_unique(&rows, snapshot));
+
+        let mut record_ids: Vec<usize> = Vec::new();
+        for (_, fields) in rows {
+            record_ids.push(try!(self.write_version(fields, snapshot.txid)));
         }
+        Ok(record_ids)
+    }
 
-        //for ref mut index in self.indices.iter_mut() {
-        //    &mut index.insert(internal_id, Tuple::new(fields.clone()));
-        //}
+    fn write_version(&mut self, fields: Vec<Field>, txid: TxId) -> Result<usize, TableError> {
+        let record_id: usize = self.meta.next_record_id.base;
+        for index in self.indices.iter_mut() {
+            try!(index.insert(&fields, record_id));
+        }
+        try!(self.store.insert(record_id, Tuple::new(fields)));
+        self.versions.insert(record_id, Version::new(txid));
+        self.meta.next_record_id.increment();
+        Ok(record_id)
+    }
 
-        &mut self.meta.next_record_id.increment();
+    // ends the version the transaction sees, a version ended by someone else is a write conflict
+    fn end_version(&mut self, record_id: usize, snapshot: &Snapshot) -> Result<(), TableError> {
+        let version: &mut Version = match self.versions.get_mut(&record_id) {
+            Some(ref version) if !snapshot.is_visible(version) => {
+                return Err(TableError::RecordNotFoundError(record_id))
+            }
+            None => return Err(TableError::RecordNotFoundError(record_id)),
+            Some(version) => version,
+        };
+        if version.end.is_some() {
+            return Err(TableError::from(TransactionError::WriteConflictError(self.name.clone(), record_id)));
+        }
+        version.end = Some(snapshot.txid);
+        self.dead.push(record_id);
+        Ok(())
     }
 
-    pub fn get_tuple(&self, internal_id: usize) -> Tuple {
-        match self.pk_index {
-            None => Tuple::new(vec![]),
-            Some(ref idx) => match idx.tree.get(&internal_id) {
-                None => Tuple::new(vec![]),
-                Some(value) => value.clone(),
+    // either all rows are overwritten or none of them,
+    // returns the record ids of the new versions in the order of the rows
+    pub fn update_all(&mut self, rows: Vec<(usize, Vec<Field>)>, snapshot: &Snapshot) -> Result<Vec<usize>, TableError> {
+        try!(self.validate_checks(&rows));
+        try!(self.validate_unique(&rows, snapshot));
+        for &(record_id, _) in rows.iter() {
+            try!(self.validate_writable(record_id, snapshot));
+        }
+
+        let mut record_ids: Vec<usize> = Vec::new();
+        for (record_id, fields) in rows {
+            try!(self.end_version(record_id, snapshot));
+            record_ids.push(try!(self.write_version(fields, snapshot.txid)));
+        }
+        Ok(record_ids)
+    }
+
+    // returns the removed tuples, which stay stored until nobody can see them
+    pub fn delete_all(&mut self, record_ids: &[usize], snapshot: &Snapshot) -> Result<Vec<(usize, Tuple)>, TableError> {
+        let mut removed: Vec<(usize, Tuple)> = Vec::new();
+        for record_id in record_ids {
+            if !self.is_visible(*record_id, snapshot) {
+                continue;
+            }
+            try!(self.end_version(*record_id, snapshot));
+            removed.push((*record_id, self.get_tuple(*record_id)));
+        }
+        Ok(removed)
+    }
+
+    fn validate_writable(&self, record_id: usize, snapshot: &Snapshot) -> Result<(), TableError> {
+        match self.versions.get(&record_id) {
+            Some(version) if snapshot.is_visible(version) => match version.end {
+                None => Ok(()),
+                Some(_) => Err(TableError::from(TransactionError::WriteConflictError(self.name.clone(), record_id))),
             },
+            _ => Err(TableError::RecordNotFoundError(record_id)),
         }
     }
 
-    pub fn seek(&self, current_handle: usize) -> Option<usize> {
-        match self.pk_index {
-            None => None,
-            Some(ref idx) => {
-                let offset: usize = idx.tree.len();
-                if current_handle > offset {
-                    return None;
+    // removes a version written by a transaction which is rolled back
+    pub fn undo_insert(&mut self, record_id: usize) -> Result<(), TableError> {
+        self.versions.remove(&record_id);
+        self.remove_record(record_id)
+    }
+
+    // the delete of the version is committed, the files of the table keep it from coming back
+    pub fn commit_delete(&mut self, record_id: usize) -> Result<(), TableError> {
+        try!(self.store.end(record_id));
+        Ok(())
+    }
+
+    // the files of a table which is not created after all
+    pub fn remove_files(&self) -> Result<(), TableError> {
+        if let Some(ref data_dir) = self.data_dir {
+            try!(fs::remove_file(heap_path(&self.meta, data_dir)).map_err(StoreError::from));
+            for index in self.indices.iter().filter(|index| index.meta.index_type == IndexType::BTree) {
+                try!(fs::remove_file(index_path(&self.meta, &index.meta, data_dir)).map_err(IndexError::from));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn undo_delete(&mut self, record_id: usize) {
+        if let Some(version) = self.versions.get_mut(&record_id) {
+            version.end = None;
+        }
+    }
+
+    // drops the versions ended below the horizon and the ones of the aborted transactions,
+    // returns how many are reclaimed.
+    // the versions which are not reclaimed yet are kept for the next vacuum, even on a failure
+    pub fn vacuum(&mut self, horizon: TxId, aborted: &HashSet<TxId>) -> Result<usize, TableError> {
+        let dead: Vec<usize> = self.dead.drain(..).collect();
+        let mut reclaimed: usize = 0;
+        for (i, record_id) in dead.iter().enumerate() {
+            let end: Option<TxId> = self.versions.get(record_id).and_then(|version| version.end);
+            match end {
+                // undone deletes leave the version alive
+                None => {}
+                // and so do the deletes of an aborted transaction, which were not undone
+                Some(end) if aborted.contains(&end) => self.undo_delete(*record_id),
+                Some(end) if end < horizon => {
+                    if let Err(err) = self.remove_record(*record_id) {
+                        self.dead.extend_from_slice(&dead[i..]);
+                        return Err(err);
+                    }
+                    self.versions.remove(record_id);
+                    reclaimed += 1;
                 }
+                Some(_) => self.dead.push(*record_id),
+            }
+        }
+
+        // the versions written by an aborted transaction are seen by nobody
+        let written: Vec<usize> = self.versions
+            .iter()
+            .filter(|&(_, version)| aborted.contains(&version.begin))
+            .map(|(record_id, _)| *record_id)
+            .collect();
+        for record_id in written {
+            try!(self.remove_record(record_id));
+            self.versions.remove(&record_id);
+            reclaimed += 1;
+        }
+        Ok(reclaimed)
+    }
 
-                match idx.tree
-                    .range((Included(&current_handle), Included(&offset)))
-                    .next()
-                {
-                    None => self.seek(current_handle + 1),
-                    Some(node) => Some(node.0.clone()),
+    fn remove_record(&mut self, record_id: usize) -> Result<(), TableError> {
+        if let Some(tuple) = try!(self.store.delete(record_id)) {
+            for index in self.indices.iter_mut() {
+                try!(index.remove(&tuple.fields, record_id));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_visible(&self, record_id: usize, snapshot: &Snapshot) -> bool {
+        self.versions.get(&record_id).is_some_and(|version| snapshot.is_visible(version))
+    }
+
+    // not deleted by anyone, committed or not
+    pub fn is_live(&self, record_id: usize) -> bool {
+        self.versions.get(&record_id).is_some_and(|version| version.end.is_none())
+    }
+
+    // looks the key up through an index on exactly these columns, or scans the rows without one
+    pub fn find_records(
+        &self,
+        columns: &[ColumnInfo],
+        key: &[Field],
+        snapshot: &Snapshot,
+    ) -> Result<Vec<usize>, TableError> {
+        for index in &self.indices {
+            let indexed: bool = index.meta.columns.len() == columns.len()
+                && index.meta.columns.iter().zip(columns.iter()).all(|(a, b)| a.name == b.name);
+            if indexed {
+                return Ok(try!(index.lookup(key))
+                    .into_iter()
+                    .filter(|record_id| self.is_visible(*record_id, snapshot))
+                    .collect());
+            }
+        }
+
+        Ok(try!(self.rows(snapshot))
+            .into_iter()
+            .filter(|(_, tuple)| {
+                columns
+                    .iter()
+                    .zip(key.iter())
+                    .all(|(column, field)| &tuple.fields[column.offset] == field)
+            })
+            .map(|(record_id, _)| record_id)
+            .collect())
+    }
+
+    // builds the index from every stored version, a unique index fails on duplicates among the live ones
+    pub fn create_index(
+        &mut self,
+        name: &str,
+        column_names: Vec<&str>,
+        is_unique: bool,
+        index_type: IndexType,
+    ) -> Result<(), TableError> {
+        let mut meta: TableInfo = self.meta.clone();
+        let index_info: IndexInfo = try!(IndexInfo::new(
+            &mut meta,
+            Some(name),
+            column_names,
+            false,
+            is_unique,
+            index_type
+        ));
+
+        let mut index: Index = try!(self.new_index(index_info, true));
+        let rows: Vec<(usize, Vec<Field>)> = try!(self.store.scan())
+            .into_iter()
+            .map(|(record_id, tuple)| (record_id, tuple.fields))
+            .collect();
+        let live_rows: Vec<(usize, Vec<Field>)> = rows.iter()
+            .filter(|row| self.versions.get(&row.0).is_some_and(|version| version.end.is_none()))
+            .cloned()
+            .collect();
+        try!(index.validate_unique(&live_rows, &|_| true));
+        for (record_id, fields) in rows {
+            try!(index.insert(&fields, record_id));
+        }
+
+        self.indices.push(index);
+        self.meta = meta;
+        Ok(())
+    }
+
+    pub fn drop_index(&mut self, name: &str) -> Result<IndexInfo, TableError> {
+        match self.indices.iter().position(|index| index.meta.name == name) {
+            None => Err(TableError::IndexNotFoundError(name.to_string())),
+            Some(i) => {
+                self.meta.indices.retain(|index_info| index_info.name != name);
+                Ok(self.indices.remove(i).meta)
+            }
+        }
+    }
+
+    fn validate_checks(&self, rows: &[(usize, Vec<Field>)]) -> Result<(), TableError> {
+        for (_, fields) in rows {
+            let tuple: Tuple = Tuple::new(fields.clone());
+            for check in &self.meta.checks {
+                if !check.eval(&tuple, &self.columns) {
+                    return Err(TableError::CheckViolationError(check.name.clone()));
                 }
             }
         }
+        Ok(())
+    }
+
+    // a stored version takes its key unless the transaction sees it deleted,
+    // so keys of versions written or deleted by running transactions are taken too
+    fn validate_unique(&self, rows: &[(usize, Vec<Field>)], snapshot: &Snapshot) -> Result<(), TableError> {
+        let is_taken = |record_id: usize| match self.versions.get(&record_id) {
+            None => false,
+            Some(version) => !version.end.is_some_and(|end| snapshot.sees(end)),
+        };
+        for index in &self.indices {
+            try!(index.validate_unique(rows, &is_taken));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, record_id: usize) -> Result<Option<Tuple>, TableError> {
+        Ok(try!(self.store.get(record_id)))
+    }
+
+    pub fn get_tuple(&self, internal_id: usize) -> Tuple {
+        match self.store.get(internal_id) {
+            Ok(Some(tuple)) => tuple,
+            _ => Tuple::new(vec![]),
+        }
+    }
+
+    pub fn seek(&self, current_handle: usize) -> Option<usize> {
+        self.store.seek(current_handle)
+    }
+
+    // every row the snapshot sees in the order of the record ids
+    pub fn rows(&self, snapshot: &Snapshot) -> Result<Vec<(usize, Tuple)>, TableError> {
+        Ok(try!(self.store.scan())
+            .into_iter()
+            .filter(|&(record_id, _)| self.is_visible(record_id, snapshot))
+            .collect())
     }
 
-    pub fn print(&self) {
+    pub fn print(&self, snapshot: &Snapshot) {
         let mut col_buffer: String = String::new();
         for col in &self.columns {
             col_buffer += "|";
@@ -99,14 +445,55 @@ impl<'t> Table<'t> {
         }
         println!("{}", col_buffer);
 
-        match self.pk_index {
-            None => {}
-            Some(ref idx) => for value in idx.tree.values() {
-                value.print();
-            },
+        for (_, tuple) in self.rows(snapshot).unwrap_or_default() {
+            tuple.print();
         }
     }
 }
 
+fn heap_path(meta: &TableInfo, data_dir: &Path) -> PathBuf {
+    data_dir.join(format!("{}.heap", meta.get_bin_path()))
+}
+
+fn index_path(meta: &TableInfo, index_info: &IndexInfo, data_dir: &Path) -> PathBuf {
+    data_dir.join(format!("{}.{}.idx", meta.get_bin_path(), index_info.name))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TableError {
+    StorageFileNotFoundError,
+    RecordNotFoundError(usize),
+    CheckViolationError(String),
+    IndexNotFoundError(String),
+    IndexError(IndexError),
+    TableInfoError(TableInfoError),
+    StoreError(StoreError),
+    TransactionError(TransactionError),
+}
+
+impl From<IndexError> for TableError {
+    fn from(err: IndexError) -> TableError {
+        TableError::IndexError(err)
+    }
+}
+
+impl From<TableInfoError> for TableError {
+    fn from(err: TableInfoError) -> TableError {
+        TableError::TableInfoError(err)
+    }
+}
+
+impl From<StoreError> for TableError {
+    fn from(err: StoreError) -> TableError {
+        TableError::StoreError(err)
+    }
+}
+
+impl From<TransactionError> for TableError {
+    fn from(err: TransactionError) -> TableError {
+        TableError::TransactionError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,73 @@
+use std::fmt::Debug;
+use std::io;
+
+use tables::tuple::Tuple;
+
+// the rows of a table keyed by record id, implemented by each storage engine.
+// clones are used as snapshots of the rows
+pub trait TableStore: Debug + Send + Sync {
+    fn insert(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError>;
+
+    fn update(&mut self, record_id: usize, tuple: Tuple) -> Result<(), StoreError>;
+
+    // returns the removed tuple
+    fn delete(&mut self, record_id: usize) -> Result<Option<Tuple>, StoreError>;
+
+    fn get(&self, record_id: usize) -> Result<Option<Tuple>, StoreError>;
+
+    // the tuple is deleted by a committed transaction, but stays until it is vacuumed.
+    // only the stores in files keep it, so that it is not taken as live when opened again
+    fn end(&mut self, _record_id: usize) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    // the first record id at or after the handle
+    fn seek(&self, handle: usize) -> Option<usize>;
+
+    fn len(&self) -> usize;
+
+    fn box_clone(&self) -> Box<dyn TableStore>;
+
+    fn record_ids(&self) -> Vec<usize> {
+        let mut record_ids: Vec<usize> = Vec::new();
+        let mut handle: usize = 0;
+        while let Some(record_id) = self.seek(handle) {
+            record_ids.push(record_id);
+            handle = record_id + 1;
+        }
+        record_ids
+    }
+
+    fn scan(&self) -> Result<Vec<(usize, Tuple)>, StoreError> {
+        let mut rows: Vec<(usize, Tuple)> = Vec::new();
+        for record_id in self.record_ids() {
+            match try!(self.get(record_id)) {
+                None => {}
+                Some(tuple) => rows.push((record_id, tuple)),
+            }
+        }
+        Ok(rows)
+    }
+}
+
+impl Clone for Box<dyn TableStore> {
+    fn clone(&self) -> Box<dyn TableStore> {
+        self.box_clone()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StoreError {
+    RecordNotFoundError(usize),
+    DuplicateRecordError(usize),
+    IoError(String),
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> StoreError {
+        StoreError::IoError(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {}
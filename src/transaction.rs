@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use meta::index_info::IndexInfo;
+use meta::view_info::ViewInfo;
+use meta::table_info::TableInfo;
+use tables::table::Table;
+
+pub type TxId = u64;
+
+// the writer of the versions read back from the files, every snapshot sees them
+pub const FROZEN_TXID: TxId = 0;
+
+// the transactions which wrote a row version and which deleted it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Version {
+    pub begin: TxId,
+    pub end: Option<TxId>,
+}
+
+impl Version {
+    pub fn new(begin: TxId) -> Version {
+        Version {
+            begin: begin,
+            end: None,
+        }
+    }
+}
+
+// a transaction sees its own writes, and the ones committed before it began
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub txid: TxId,
+    // transactions from this id on had not begun yet
+    pub xmax: TxId,
+    pub active: HashSet<TxId>,
+    pub aborted: HashSet<TxId>,
+}
+
+impl Snapshot {
+    pub fn sees(&self, txid: TxId) -> bool {
+        txid == self.txid || (txid < self.xmax && !self.active.contains(&txid) && !self.aborted.contains(&txid))
+    }
+
+    pub fn is_visible(&self, version: &Version) -> bool {
+        self.sees(version.begin) && !version.end.is_some_and(|end| self.sees(end))
+    }
+
+    // no transaction below this id is hidden from the snapshot
+    pub fn xmin(&self) -> TxId {
+        self.active.iter().cloned().fold(self.txid, |xmin, txid| xmin.min(txid))
+    }
+}
+
+// hands out transaction ids and keeps the running transactions with their xmin
+#[derive(Debug, Clone)]
+pub struct TransactionManager {
+    pub next_txid: TxId,
+    pub active: BTreeMap<TxId, TxId>,
+    // rolled back without undoing every write, what is left of the writes is never seen.
+    // each is kept with the id of the database it wrote, until the vacuum there reclaims the writes
+    pub aborted: BTreeMap<TxId, usize>,
+}
+
+impl Default for TransactionManager {
+    fn default() -> TransactionManager {
+        TransactionManager::new()
+    }
+}
+
+impl TransactionManager {
+    pub fn new() -> TransactionManager {
+        TransactionManager {
+            next_txid: 1,
+            active: BTreeMap::new(),
+            aborted: BTreeMap::new(),
+        }
+    }
+
+    pub fn begin(&mut self) -> Transaction {
+        let snapshot: Snapshot = self.snapshot();
+        self.active.insert(snapshot.txid, snapshot.xmin());
+        self.next_txid += 1;
+        Transaction::new(snapshot)
+    }
+
+    pub fn end(&mut self, txid: TxId) {
+        self.active.remove(&txid);
+    }
+
+    pub fn abort(&mut self, txid: TxId, database_id: usize) {
+        self.active.remove(&txid);
+        self.aborted.insert(txid, database_id);
+    }
+
+    pub fn aborted_in(&self, database_id: usize) -> HashSet<TxId> {
+        self.aborted
+            .iter()
+            .filter(|&(_, id)| *id == database_id)
+            .map(|(txid, _)| *txid)
+            .collect()
+    }
+
+    // nothing the transactions wrote is left, so they need not be hidden anymore
+    pub fn forget_aborted(&mut self, txids: &HashSet<TxId>) {
+        for txid in txids {
+            self.aborted.remove(txid);
+        }
+    }
+
+    // a read only view of every committed write, it takes no transaction id
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            txid: self.next_txid,
+            xmax: self.next_txid,
+            active: self.active.keys().cloned().collect(),
+            aborted: self.aborted.keys().cloned().collect(),
+        }
+    }
+
+    // versions deleted below the horizon are invisible to every running transaction
+    pub fn horizon(&self) -> TxId {
+        self.active.values().cloned().fold(self.next_txid, |horizon, xmin| horizon.min(xmin))
+    }
+}
+
+// how to revert a write of the transaction
+#[derive(Debug, Clone)]
+pub enum UndoEntry {
+    Insert(String, usize),
+    Delete(String, usize),
+    CreateTable(String),
+    CreateIndex(String, String),
+    DropIndex(String, IndexInfo),
+    CreateView(String),
+    DropView(ViewInfo),
+    // the dropped table is kept aside until the transaction ends
+    DropTable(TableInfo, Arc<RwLock<Table>>),
+    CreateMaterializedView(String),
+    DropMaterializedView(ViewInfo),
+}
+
+// writes are visible to the others only after the commit,
+// savepoints and rollbacks revert them through the undo log
+#[derive(Debug)]
+pub struct Transaction {
+    pub snapshot: Snapshot,
+    pub undo_log: Vec<UndoEntry>,
+    pub savepoints: Vec<(String, usize)>,
+    // how long to wait for a lock, forever when none
+    pub lock_timeout: Option<Duration>,
+}
+
+impl Transaction {
+    pub fn new(snapshot: Snapshot) -> Transaction {
+        Transaction {
+            snapshot: snapshot,
+            undo_log: Vec::new(),
+            savepoints: Vec::new(),
+            lock_timeout: None,
+        }
+    }
+
+    pub fn txid(&self) -> TxId {
+        self.snapshot.txid
+    }
+
+    // a savepoint with the same name as an older one hides it
+    pub fn savepoint(&mut self, name: String) {
+        let mark: usize = self.undo_log.len();
+        self.savepoints.push((name, mark));
+    }
+
+    fn find_savepoint(&self, name: &str) -> Result<usize, TransactionError> {
+        match self.savepoints.iter().rposition(|savepoint| savepoint.0 == name) {
+            None => Err(TransactionError::SavepointNotFoundError(name.to_string())),
+            Some(i) => Ok(i),
+        }
+    }
+
+    // the length of the undo log at the savepoint, which is kept so that it can be rolled back to again
+    pub fn rollback_to(&mut self, name: &str) -> Result<usize, TransactionError> {
+        let i: usize = try!(self.find_savepoint(name));
+        self.savepoints.truncate(i + 1);
+        Ok(self.savepoints[i].1)
+    }
+
+    // forgets the savepoint and every later one, the changes are kept
+    pub fn release(&mut self, name: &str) -> Result<(), TransactionError> {
+        let i: usize = try!(self.find_savepoint(name));
+        self.savepoints.truncate(i);
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TransactionError {
+    NoActiveTransactionError,
+    ActiveTransactionError,
+    SavepointNotFoundError(String),
+    WriteConflictError(String, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_visibility() {
+        let mut manager: TransactionManager = TransactionManager::new();
+        let first: Transaction = manager.begin();
+        let second: Transaction = manager.begin();
+        manager.end(first.txid());
+        let third: Transaction = manager.begin();
+
+        let written_by_first: Version = Version::new(first.txid());
+        assert!(first.snapshot.is_visible(&written_by_first));
+        assert!(!second.snapshot.is_visible(&written_by_first));
+        assert!(third.snapshot.is_visible(&written_by_first));
+
+        let deleted_by_second: Version = Version {
+            begin: first.txid(),
+            end: Some(second.txid()),
+        };
+        assert!(!second.snapshot.is_visible(&deleted_by_second));
+        assert!(third.snapshot.is_visible(&deleted_by_second));
+
+        // the second one began while the first one was running, so it still sees what the first one deletes
+        assert_eq!(manager.horizon(), first.txid());
+        manager.end(second.txid());
+        assert_eq!(manager.horizon(), second.txid());
+        manager.end(third.txid());
+        assert_eq!(manager.horizon(), 4);
+    }
+
+    #[test]
+    fn test_aborted_writes_stay_hidden() {
+        let mut manager: TransactionManager = TransactionManager::new();
+        let aborted: Transaction = manager.begin();
+        let written: Version = Version::new(aborted.txid());
+        manager.abort(aborted.txid(), 0);
+
+        let next: Transaction = manager.begin();
+        assert!(!next.snapshot.is_visible(&written));
+        assert!(!manager.snapshot().is_visible(&written));
+        manager.end(next.txid());
+        assert_eq!(manager.horizon(), 3);
+    }
+}